@@ -8,7 +8,11 @@
 // Software.
 
 use anyhow::Result;
-use sn_api::{fetch::SafeData, BootstrapConfig, Safe, SafeUrl};
+use sn_api::{
+    fetch::SafeData,
+    files::{CreateOptions, SymlinkPolicy},
+    BootstrapConfig, Safe, SafeUrl,
+};
 use std::{env::temp_dir, fs::File, io::Write, path::PathBuf};
 
 const FILE_TO_UPLOAD: &str = "file_to_upload.rs";
@@ -35,12 +39,22 @@ async fn main() -> Result<()> {
     let location = file_path.display().to_string();
     let dest = None; // root path at destination container
     let recursive = false; // do not do a recursive look up of files on local path
-    let follow_links = false; // do not attempt to follow local links
+    let symlinks = SymlinkPolicy::Preserve; // do not attempt to follow local links
+    let private = false; // store it as public content
     let dry_run = false; // commit the operation on the network
 
     println!("Uploading '{}' to Safe ...", location);
     let (xorurl, _, _) = safe
-        .files_container_create(Some(&location), dest, recursive, follow_links, dry_run)
+        .files_container_create(
+            Some(&location),
+            dest,
+            &CreateOptions {
+                recursive,
+                symlinks,
+                private,
+                dry_run,
+            },
+        )
         .await?;
     // The 'files_container_create' API returns (among other information) the
     // XOR-URL of the FilesContainer where the file was uplaoded to
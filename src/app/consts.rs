@@ -26,6 +26,7 @@ pub const PREDICATE_ORIGINAL_MODIFIED: &str = "o_modified";
 pub const PREDICATE_ORIGINAL_CREATED: &str = "o_created";
 pub const PREDICATE_READONLY: &str = "readonly";
 pub const PREDICATE_MODE_BITS: &str = "mode_bits";
+pub const PREDICATE_CHECKSUM: &str = "checksum";
 
 // see: https://stackoverflow.com/questions/18869772/mime-type-for-a-directory
 // We will use the FreeDesktop standard for directories and symlinks.
@@ -35,3 +36,9 @@ pub const PREDICATE_MODE_BITS: &str = "mode_bits";
 //      maybe files.rs or xorurl_media_types.rs?
 pub const MIMETYPE_FILESYSTEM_DIR: &str = "inode/directory";
 pub const MIMETYPE_FILESYSTEM_SYMLINK: &str = "inode/symlink";
+
+// Vocabularies used for the RDF representation of FilesMaps and NrsMaps,
+// their terms are described in resources/vocab/safe-vocab.ttl
+pub const SAFE_FILES_VOCAB: &str = "https://safenetwork.tech/ns/files#";
+pub const SAFE_NRS_VOCAB: &str = "https://safenetwork.tech/ns/nrs#";
+pub const XSD_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema#";
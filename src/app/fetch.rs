@@ -8,7 +8,7 @@
 // Software.
 
 use super::{
-    files::{FileItem, FileMeta, FilesMap, RealPath},
+    files::{verify_file_item_checksum, FileItem, FileItemType, FilesMap, RAW_MEDIA_TYPE},
     multimap::MultimapKeyValues,
     nrs::NrsMap,
     register::{Entry, EntryHash},
@@ -49,6 +49,14 @@ pub enum SafeData {
         metadata: Option<FileItem>,
         resolved_from: String,
     },
+    PrivateBlob {
+        xorurl: String,
+        xorname: XorName,
+        data: Vec<u8>,
+        media_type: Option<String>,
+        metadata: Option<FileItem>,
+        resolved_from: String,
+    },
     NrsMapContainer {
         public_name: Option<String>,
         xorurl: String,
@@ -105,6 +113,7 @@ impl SafeData {
             SafeKey { xorurl, .. }
             | FilesContainer { xorurl, .. }
             | PublicBlob { xorurl, .. }
+            | PrivateBlob { xorurl, .. }
             | NrsMapContainer { xorurl, .. }
             | Multimap { xorurl, .. }
             | PublicSequence { xorurl, .. }
@@ -120,6 +129,7 @@ impl SafeData {
             SafeKey { resolved_from, .. }
             | FilesContainer { resolved_from, .. }
             | PublicBlob { resolved_from, .. }
+            | PrivateBlob { resolved_from, .. }
             | NrsMapContainer { resolved_from, .. }
             | Multimap { resolved_from, .. }
             | PrivateSequence { resolved_from, .. }
@@ -137,13 +147,13 @@ impl Safe {
     ///
     /// ### Fetch FilesContainer relative path file
     /// ```no_run
-    /// # use sn_api::{Safe, fetch::SafeData};
+    /// # use sn_api::{Safe, fetch::SafeData, files::CreateOptions};
     /// # use std::collections::BTreeMap;
     /// # let mut safe = Safe::default();
     /// # let rt = tokio::runtime::Runtime::new().unwrap();
     /// # rt.block_on(async {
     /// #   safe.connect(None, None, None).await.unwrap();
-    ///     let (xorurl, _, _) = safe.files_container_create(Some("../testdata/"), None, true, false, false).await.unwrap();
+    ///     let (xorurl, _, _) = safe.files_container_create(Some("../testdata/"), None, &CreateOptions::default()).await.unwrap();
     ///
     ///     let safe_data = safe.fetch( &format!( "{}/test.md", &xorurl.replace("?v=0", "") ), None ).await.unwrap();
     ///     let data_string = match safe_data {
@@ -180,13 +190,13 @@ impl Safe {
     ///
     /// ### Inspect FilesContainer relative path file
     /// ```no_run
-    /// # use sn_api::{Safe, fetch::SafeData};
+    /// # use sn_api::{Safe, fetch::SafeData, files::CreateOptions};
     /// # use std::collections::BTreeMap;
     /// # let rt = tokio::runtime::Runtime::new().unwrap();
     /// # rt.block_on(async {
     /// #   let mut safe = Safe::default();
     /// #   safe.connect(None, None, None).await.unwrap();
-    ///     let (container_xorurl, _, _) = safe.files_container_create(Some("../testdata/"), None, true, false, false).await.unwrap();
+    ///     let (container_xorurl, _, _) = safe.files_container_create(Some("../testdata/"), None, &CreateOptions::default()).await.unwrap();
     ///
     ///     let inspected_content = safe.inspect( &format!( "{}/test.md", &container_xorurl.replace("?v=0", "") ) ).await.unwrap();
     ///     match &inspected_content[0] {
@@ -283,7 +293,18 @@ impl Safe {
                     return Err(Error::InvalidXorUrl(msg));
                 }
 
-                let (version, files_map) = self.fetch_files_container(&the_xor).await?;
+                // When resolving a path, only the part of the FilesMap needed
+                // to resolve it is fetched if the FilesMap is sharded
+                let path = the_xor.path_decoded()?;
+                let (version, files_map, realpath) =
+                    if resolve_path && path != "/" && !path.is_empty() {
+                        let (version, files_map, realpath) =
+                            self.fetch_files_container_path(&the_xor, &path).await?;
+                        (version, files_map, Some(realpath))
+                    } else {
+                        let (version, files_map) = self.fetch_files_container(&the_xor).await?;
+                        (version, files_map, None)
+                    };
                 debug!(
                     "Files container found with v:{}, on data type: {}, containing: {:?}",
                     version,
@@ -291,46 +312,31 @@ impl Safe {
                     files_map
                 );
 
-                let path = the_xor.path_decoded()?;
-                let (files_map, next) = if resolve_path && path != "/" && !path.is_empty() {
-                    // TODO: Move this logic (path resolver) to the FilesMap struct
-                    let realpath = files_map.realpath(&path)?;
+                let (files_map, next) = if let Some(realpath) = realpath {
                     match &files_map.get(&realpath) {
-                        Some(file_item) => match file_item.get("type") {
-                            Some(file_type) => {
-                                if FileMeta::filetype_is_file(file_type) {
-                                    match file_item.get("link") {
-                                        Some(link) => {
-                                            let new_target_xorurl = SafeUrl::from_url(link)?;
-                                            let mut metadata = (*file_item).clone();
-                                            Path::new(&path).file_name().map(|name| {
-                                                name.to_str().map(|str| {
-                                                    metadata
-                                                        .insert("name".to_string(), str.to_string())
-                                                })
-                                            });
-                                            (files_map, Some((new_target_xorurl, Some(metadata))))
-                                        }
-                                        None => {
-                                            let msg = format!("FileItem is corrupt. It is missing a \"link\" property at path, \"{}\" on the FilesContainer at: {} ", path, xorurl);
-                                            return Err(Error::ContentError(msg));
-                                        }
-                                    }
-                                } else if FileMeta::filetype_is_symlink(file_type) {
-                                    let msg = format!(
-                                        "symlink should not be present in resolved real path. {}",
-                                        realpath
-                                    );
-                                    return Err(Error::ContentError(msg));
-                                } else {
-                                    // Must be a directory.
-                                    (gen_filtered_filesmap(&realpath, &files_map, &xorurl)?, None)
-                                }
+                        Some(file_item) => match &file_item.file_type {
+                            FileItemType::File { link, .. } => {
+                                let new_target_xorurl = SafeUrl::from_url(link)?;
+                                let mut metadata = (*file_item).clone();
+                                Path::new(&path).file_name().map(|name| {
+                                    name.to_str().map(|str| {
+                                        metadata
+                                            .extensions
+                                            .insert("name".to_string(), str.to_string())
+                                    })
+                                });
+                                (files_map, Some((new_target_xorurl, Some(metadata))))
                             }
-                            None => {
-                                let msg = format!("FileItem is corrupt. It is missing a \"type\" property at path, \"{}\" on the FilesContainer at: {} ", path, xorurl);
+                            FileItemType::Symlink { .. } => {
+                                let msg = format!(
+                                    "symlink should not be present in resolved real path. {}",
+                                    realpath
+                                );
                                 return Err(Error::ContentError(msg));
                             }
+                            FileItemType::Dir => {
+                                (gen_filtered_filesmap(&realpath, &files_map, &xorurl)?, None)
+                            }
                         },
                         None => (gen_filtered_filesmap(&realpath, &files_map, &xorurl)?, None),
                     }
@@ -441,7 +447,7 @@ impl Safe {
                         };
                         Ok((safe_data, None))
                     }
-                    SafeDataType::PublicBlob => {
+                    SafeDataType::PublicBlob | SafeDataType::PrivateBlob => {
                         self.retrieve_blob(&the_xor, retrieve_data, None, &metadata, range)
                             .await
                     }
@@ -530,7 +536,7 @@ impl Safe {
                 }
 
                 match the_xor.data_type() {
-                    SafeDataType::PublicBlob => {
+                    SafeDataType::PublicBlob | SafeDataType::PrivateBlob => {
                         self.retrieve_blob(
                             &the_xor,
                             retrieve_data,
@@ -568,27 +574,56 @@ impl Safe {
         };
 
         let data = if retrieve_data {
-            self.safe_client
-                .get_public_blob(the_xor.xorname(), range)
-                .await?
+            let data = self.fetch_blob(the_xor, range).await?;
+            // Only the whole content of a file can be verified against its checksum
+            if let (Some(file_item), true, None) =
+                (metadata, self.files_config.verify_checksums, range)
+            {
+                verify_file_item_checksum(file_item, &data)?;
+            }
+            data
         } else {
             vec![]
         };
 
-        let safe_data = SafeData::PublicBlob {
-            xorurl: the_xor.to_xorurl_string(),
-            xorname: the_xor.xorname(),
-            data,
-            media_type,
-            metadata: metadata.clone(),
-            resolved_from: the_xor.to_string(),
+        // Media types not supported by the XOR-URL encoding are only found in the FileItem
+        let media_type = media_type.or_else(|| {
+            metadata
+                .as_ref()
+                .map(|file_item| file_item.media_type())
+                .filter(|media_type| *media_type != RAW_MEDIA_TYPE)
+                .map(|media_type| media_type.to_string())
+        });
+
+        let safe_data = if the_xor.data_type() == SafeDataType::PrivateBlob {
+            SafeData::PrivateBlob {
+                xorurl: the_xor.to_xorurl_string(),
+                xorname: the_xor.xorname(),
+                data,
+                media_type,
+                metadata: metadata.clone(),
+                resolved_from: the_xor.to_string(),
+            }
+        } else {
+            SafeData::PublicBlob {
+                xorurl: the_xor.to_xorurl_string(),
+                xorname: the_xor.xorname(),
+                data,
+                media_type,
+                metadata: metadata.clone(),
+                resolved_from: the_xor.to_string(),
+            }
         };
 
         Ok((safe_data, None))
     }
 }
 
-fn gen_filtered_filesmap(urlpath: &str, files_map: &FilesMap, xorurl: &str) -> Result<FilesMap> {
+pub(crate) fn gen_filtered_filesmap(
+    urlpath: &str,
+    files_map: &FilesMap,
+    xorurl: &str,
+) -> Result<FilesMap> {
     let mut filtered_filesmap = FilesMap::default();
     let folder_path = if !urlpath.ends_with('/') {
         format!("{}/", urlpath)
@@ -619,7 +654,7 @@ type NextStepInfo = (SafeUrl, Option<FileItem>);
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{app::test_helpers::new_safe_instance, retry_loop, SafeUrl};
+    use crate::{app::test_helpers::new_safe_instance, files::CreateOptions, retry_loop, SafeUrl};
     use anyhow::{anyhow, bail, Context, Result};
     use rand::{distributions::Alphanumeric, thread_rng, Rng};
     use std::io::Read;
@@ -628,7 +663,7 @@ mod tests {
     async fn test_fetch_files_container() -> Result<()> {
         let mut safe = new_safe_instance().await?;
         let (xorurl, _, files_map) = safe
-            .files_container_create(Some("../testdata/"), None, true, false, false)
+            .files_container_create(Some("../testdata/"), None, &CreateOptions::default())
             .await?;
 
         let safe_url = SafeUrl::from_url(&xorurl)?;
@@ -669,7 +704,7 @@ mod tests {
         let mut safe = new_safe_instance().await?;
 
         let (xorurl, _, the_files_map) = safe
-            .files_container_create(Some("../testdata/"), None, true, false, false)
+            .files_container_create(Some("../testdata/"), None, &CreateOptions::default())
             .await?;
         let _ = retry_loop!(safe.fetch(&xorurl, None));
 
@@ -719,7 +754,7 @@ mod tests {
 
         let mut safe = new_safe_instance().await?;
         let (xorurl, _, _the_files_map) = safe
-            .files_container_create(Some("../testdata/"), None, true, false, false)
+            .files_container_create(Some("../testdata/"), None, &CreateOptions::default())
             .await?;
         let _ = retry_loop!(safe.fetch(&xorurl, None));
 
@@ -832,7 +867,7 @@ mod tests {
         let site_name: String = thread_rng().sample_iter(&Alphanumeric).take(15).collect();
 
         let (xorurl, _, _files_map) = safe
-            .files_container_create(Some("../testdata/"), None, true, false, false)
+            .files_container_create(Some("../testdata/"), None, &CreateOptions::default())
             .await?;
         let _ = retry_loop!(safe.fetch(&xorurl, None));
 
@@ -884,6 +919,52 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_fetch_private_files_container() -> Result<()> {
+        use std::fs::File;
+        let mut safe = new_safe_instance().await?;
+
+        let (xorurl, _, files_map) = safe
+            .files_container_create(
+                Some("../testdata/"),
+                None,
+                &CreateOptions {
+                    private: true,
+                    ..CreateOptions::default()
+                },
+            )
+            .await?;
+        let content = retry_loop!(safe.fetch(&xorurl, None));
+        match &content {
+            SafeData::FilesContainer {
+                files_map: fetched_files_map,
+                data_type,
+                ..
+            } => {
+                assert_eq!(*data_type, SafeDataType::PrivateSequence);
+                assert_eq!(*fetched_files_map, files_map);
+            }
+            other => bail!("Content fetched is not a FilesContainer: {:?}", other),
+        }
+
+        let mut file = File::open("../testdata/test.md")
+            .context("Failed to open local file: ../testdata/test.md".to_string())?;
+        let mut file_data = Vec::new();
+        file.read_to_end(&mut file_data)
+            .context("Failed to read local file: ../testdata/test.md".to_string())?;
+
+        let content = retry_loop!(safe.fetch(&format!("{}/test.md", xorurl), None));
+        if let SafeData::PrivateBlob { data, .. } = &content {
+            assert_eq!(data.clone(), file_data);
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "Content fetched is not a PrivateBlob: {:?}",
+                content
+            ))
+        }
+    }
+
     #[tokio::test]
     async fn test_fetch_public_sequence() -> Result<()> {
         let mut safe = new_safe_instance().await?;
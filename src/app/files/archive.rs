@@ -0,0 +1,442 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{
+    builder::{ancestors, FileAttributes},
+    checksums::checksum_data,
+    media_type::{detect_media_type, xorurl_media_type, RAW_MEDIA_TYPE},
+    metadata::FileMeta,
+    normalise_files_map_path, resolve_symlinks_target_type, FileItemType, FilesMap, ProcessedFiles,
+    SymlinkTargetType,
+};
+use crate::{app::consts::*, Error, Result, Safe, SafeUrl, XorUrl};
+use chrono::{DateTime, TimeZone, Utc};
+use flate2::read::GzDecoder;
+use log::{debug, info, warn};
+use relative_path::{Component, RelativePath};
+use std::{
+    io::{self, Read, Write},
+    path::Path,
+};
+
+// Mask of the file type bits of a unix mode, and the value for symlinks
+const UNIX_FILE_TYPE_MASK: u32 = 0o170_000;
+const UNIX_SYMLINK_TYPE: u32 = 0o120_000;
+
+// Mask of the permission bits of a unix mode, which are the only ones written to archives
+const UNIX_PERMISSIONS_MASK: u32 = 0o7777;
+
+/// Formats of the archives a FilesContainer can be created from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// Uncompressed tar archive
+    Tar,
+    /// Gzip compressed tar archive
+    TarGz,
+    /// Zip archive
+    Zip,
+}
+
+// Content of an archive entry
+enum ArchiveEntryKind {
+    File(Vec<u8>),
+    Dir,
+    Symlink(String),
+}
+
+// An entry read from an archive, with the metadata recorded for it
+struct ArchiveEntry {
+    path: String,
+    kind: ArchiveEntryKind,
+    mode_bits: Option<u32>,
+    modified: Option<DateTime<Utc>>,
+}
+
+// State of the import of an archive's entries into a new FilesMap
+struct ArchiveImport {
+    dest_base_path: String,
+    private: bool,
+    dry_run: bool,
+    processed_files: ProcessedFiles,
+    files_map: FilesMap,
+}
+
+impl Safe {
+    /// # Create a FilesContainer from the content of an archive.
+    ///
+    /// The archive is read as a stream, its files are uploaded as they are read, and the
+    /// FileItems keep the permissions and modification times recorded in the archive.
+    /// Nothing is written to the local filesystem. Entries other than files, directories
+    /// and symlinks, e.g. hard links or devices, are skipped.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::{Safe, files::ArchiveFormat};
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    ///     safe.connect(None, None, None).await.unwrap();
+    ///     let archive = std::fs::File::open("release.tar.gz").unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create_from_archive(archive, ArchiveFormat::TarGz, None, false, false).await.unwrap();
+    ///     assert!(xorurl.contains("safe://"))
+    /// # });
+    /// ```
+    pub async fn files_container_create_from_archive<R: Read>(
+        &mut self,
+        archive: R,
+        format: ArchiveFormat,
+        dest: Option<&str>,
+        private: bool,
+        dry_run: bool,
+    ) -> Result<(XorUrl, ProcessedFiles, FilesMap)> {
+        let mut import = ArchiveImport {
+            dest_base_path: normalise_files_map_path(dest.unwrap_or("/")),
+            private,
+            dry_run,
+            processed_files: ProcessedFiles::default(),
+            files_map: FilesMap::default(),
+        };
+
+        match format {
+            ArchiveFormat::Tar => {
+                self.import_tar(tar::Archive::new(archive), &mut import)
+                    .await?
+            }
+            ArchiveFormat::TarGz => {
+                self.import_tar(tar::Archive::new(GzDecoder::new(archive)), &mut import)
+                    .await?
+            }
+            ArchiveFormat::Zip => self.import_zip(archive, &mut import).await?,
+        }
+
+        // Archives don't necessarily have entries for all the directories
+        import.add_implicit_dirs();
+
+        // Now that we know all the paths we can tell what the symlinks point to
+        let mut files_map = import.files_map;
+        resolve_symlinks_target_type(&mut files_map);
+        self.apply_files_timestamps(&mut files_map);
+
+        let xorurl = if dry_run {
+            "".to_string()
+        } else {
+            self.store_new_files_container(&files_map, private).await?
+        };
+
+        Ok((xorurl, import.processed_files, files_map))
+    }
+
+    /// # Export a version of a FilesContainer as a tar archive.
+    ///
+    /// The archive is streamed to the writer provided, fetching each file as it's written,
+    /// and recording the permissions and original modification times of the FileItems.
+    /// If the URL doesn't specify a version, the latest version is exported.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::{Safe, files::{CreateOptions, SymlinkPolicy}};
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    ///     safe.connect(None, None, None).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata"), None, &CreateOptions { symlinks: SymlinkPolicy::FollowAll, ..CreateOptions::default() }).await.unwrap();
+    ///     let mut archive = vec![];
+    ///     safe.files_container_export_archive(&xorurl, &mut archive).await.unwrap();
+    /// # });
+    /// ```
+    pub async fn files_container_export_archive<W: Write>(
+        &mut self,
+        url: &str,
+        writer: W,
+    ) -> Result<()> {
+        let (safe_url, _) = self.parse_and_resolve_url(url).await?;
+        let (version, files_map) = self.fetch_files_container(&safe_url).await?;
+        info!(
+            "Exporting version {} of FilesContainer at {} as a tar archive",
+            version, url
+        );
+
+        let mut builder = tar::Builder::new(writer);
+        for (path, file_item) in &files_map {
+            let name = path.trim_start_matches('/');
+            if name.is_empty() {
+                continue;
+            }
+
+            let mut header = tar::Header::new_gnu();
+            let mtime = file_item.original_modified.unwrap_or(file_item.modified);
+            header.set_mtime(mtime.timestamp().max(0) as u64);
+
+            let result = match &file_item.file_type {
+                FileItemType::File { link, .. } => {
+                    let data = self.fetch_blob(&SafeUrl::from_url(link)?, None).await?;
+                    header.set_entry_type(tar::EntryType::Regular);
+                    header.set_mode(archive_mode(file_item.mode_bits, 0o644));
+                    header.set_size(data.len() as u64);
+                    builder.append_data(&mut header, name, data.as_slice())
+                }
+                FileItemType::Dir => {
+                    header.set_entry_type(tar::EntryType::Directory);
+                    header.set_mode(archive_mode(file_item.mode_bits, 0o755));
+                    header.set_size(0);
+                    builder.append_data(&mut header, name, io::empty())
+                }
+                FileItemType::Symlink { target, .. } => {
+                    header.set_entry_type(tar::EntryType::Symlink);
+                    header.set_mode(archive_mode(file_item.mode_bits, 0o777));
+                    header.set_size(0);
+                    builder.append_link(&mut header, name, target)
+                }
+            };
+            result.map_err(|err| {
+                Error::FileSystemError(format!(
+                    "Failed to write '{}' to the tar archive: {}",
+                    path, err
+                ))
+            })?;
+        }
+
+        builder
+            .into_inner()
+            .and_then(|mut writer| writer.flush())
+            .map_err(|err| {
+                Error::FileSystemError(format!("Failed to write the tar archive: {}", err))
+            })
+    }
+
+    // Private helper to import the entries of a tar archive
+    async fn import_tar<R: Read>(
+        &mut self,
+        mut archive: tar::Archive<R>,
+        import: &mut ArchiveImport,
+    ) -> Result<()> {
+        let entries = archive
+            .entries()
+            .map_err(|err| archive_error("tar archive", err))?;
+        for entry in entries {
+            let mut entry = entry.map_err(|err| archive_error("tar archive", err))?;
+            let path = entry
+                .path()
+                .map_err(|err| archive_error("tar entry", err))?
+                .display()
+                .to_string();
+            let header = entry.header();
+            let mode_bits = header.mode().ok().map(|mode| mode & UNIX_PERMISSIONS_MASK);
+            let modified = header
+                .mtime()
+                .ok()
+                .and_then(|mtime| Utc.timestamp_opt(mtime as i64, 0).single());
+
+            let kind = match header.entry_type() {
+                tar::EntryType::Regular | tar::EntryType::Continuous => {
+                    let mut data = vec![];
+                    entry
+                        .read_to_end(&mut data)
+                        .map_err(|err| archive_error(&path, err))?;
+                    ArchiveEntryKind::File(data)
+                }
+                tar::EntryType::Directory => ArchiveEntryKind::Dir,
+                tar::EntryType::Symlink => {
+                    let target = entry
+                        .link_name()
+                        .map_err(|err| archive_error(&path, err))?
+                        .map(|target| target.display().to_string())
+                        .unwrap_or_default();
+                    ArchiveEntryKind::Symlink(target)
+                }
+                other => {
+                    warn!("Skipping '{}' of unsupported type: {:?}", path, other);
+                    continue;
+                }
+            };
+
+            let entry = ArchiveEntry {
+                path,
+                kind,
+                mode_bits,
+                modified,
+            };
+            import.add_entry(self, entry).await;
+        }
+
+        Ok(())
+    }
+
+    // Private helper to import the entries of a zip archive, reading them sequentially
+    async fn import_zip<R: Read>(
+        &mut self,
+        mut archive: R,
+        import: &mut ArchiveImport,
+    ) -> Result<()> {
+        loop {
+            let entry = match zip::read::read_zipfile_from_stream(&mut archive) {
+                Ok(Some(mut file)) => {
+                    let path = file.name().to_string();
+                    let mode_bits = file.unix_mode();
+                    let last_modified = file.last_modified();
+                    let modified = Utc
+                        .with_ymd_and_hms(
+                            i32::from(last_modified.year()),
+                            u32::from(last_modified.month()),
+                            u32::from(last_modified.day()),
+                            u32::from(last_modified.hour()),
+                            u32::from(last_modified.minute()),
+                            u32::from(last_modified.second()),
+                        )
+                        .single();
+
+                    let kind = if file.is_dir() {
+                        ArchiveEntryKind::Dir
+                    } else {
+                        let mut data = vec![];
+                        file.read_to_end(&mut data)
+                            .map_err(|err| archive_error(&path, err))?;
+                        match mode_bits {
+                            Some(mode) if mode & UNIX_FILE_TYPE_MASK == UNIX_SYMLINK_TYPE => {
+                                ArchiveEntryKind::Symlink(
+                                    String::from_utf8_lossy(&data).to_string(),
+                                )
+                            }
+                            _ => ArchiveEntryKind::File(data),
+                        }
+                    };
+
+                    ArchiveEntry {
+                        path,
+                        kind,
+                        mode_bits: mode_bits.map(|mode| mode & UNIX_PERMISSIONS_MASK),
+                        modified,
+                    }
+                }
+                Ok(None) => return Ok(()),
+                Err(err) => return Err(archive_error("zip archive", err)),
+            };
+
+            import.add_entry(self, entry).await;
+        }
+    }
+}
+
+impl ArchiveImport {
+    // Add an entry read from the archive to the FilesMap, uploading its content if it's a file
+    async fn add_entry(&mut self, safe: &mut Safe, entry: ArchiveEntry) {
+        let path = RelativePath::new(&entry.path).normalize();
+        if path.as_str().is_empty() {
+            return;
+        }
+        if matches!(path.components().next(), Some(Component::ParentDir)) {
+            self.processed_files.insert(
+                entry.path.clone(),
+                (
+                    CONTENT_ERROR_SIGN.to_string(),
+                    "<Path ascends beyond the root of the archive>".to_string(),
+                ),
+            );
+            info!("Skipping archive entry \"{}\"", entry.path);
+            return;
+        }
+        let final_name = normalise_files_map_path(&format!(
+            "{}/{}",
+            self.dest_base_path.trim_end_matches('/'),
+            path
+        ));
+
+        let mut checksum = None;
+        let (file_type, file_size, file_item_type) = match entry.kind {
+            ArchiveEntryKind::File(data) => {
+                let media_type = detect_media_type(Path::new(path.as_str()), &data);
+                let result = safe
+                    .files_store_blob(
+                        &data,
+                        xorurl_media_type(media_type.as_deref()),
+                        self.private,
+                        self.dry_run,
+                    )
+                    .await;
+                let link = match result {
+                    Ok(link) => link,
+                    Err(err) => {
+                        self.processed_files.insert(
+                            entry.path.clone(),
+                            (CONTENT_ERROR_SIGN.to_string(), format!("<{}>", err)),
+                        );
+                        info!("Skipping archive entry \"{}\": {:?}", entry.path, err);
+                        return;
+                    }
+                };
+                checksum = safe
+                    .files_config
+                    .checksum
+                    .map(|algorithm| checksum_data(algorithm, &data));
+                let media_type = media_type.unwrap_or_else(|| RAW_MEDIA_TYPE.to_string());
+                (
+                    media_type.clone(),
+                    data.len() as u64,
+                    FileItemType::File { media_type, link },
+                )
+            }
+            ArchiveEntryKind::Dir => (MIMETYPE_FILESYSTEM_DIR.to_string(), 0, FileItemType::Dir),
+            ArchiveEntryKind::Symlink(target) => (
+                MIMETYPE_FILESYSTEM_SYMLINK.to_string(),
+                0,
+                FileItemType::Symlink {
+                    target,
+                    target_type: SymlinkTargetType::Unknown,
+                },
+            ),
+        };
+
+        let attributes = FileAttributes {
+            mode_bits: entry.mode_bits,
+            modified: entry.modified,
+            ..FileAttributes::default()
+        };
+        let file_meta = FileMeta::from_attributes(&file_type, file_size, &attributes);
+        let mut file_item = file_meta.to_file_item(file_item_type);
+        file_item.checksum = checksum;
+        debug!("New FileItem item from archive: {:?}", file_item);
+
+        self.processed_files.insert(
+            entry.path,
+            (
+                CONTENT_ADDED_SIGN.to_string(),
+                file_item.link().unwrap_or_default().to_string(),
+            ),
+        );
+        self.files_map.insert(final_name, file_item);
+    }
+
+    // Add a Dir FileItem for each ancestor of the entries imported which has none
+    fn add_implicit_dirs(&mut self) {
+        let implicit_dirs: Vec<String> = self
+            .files_map
+            .keys()
+            .flat_map(|path| ancestors(path))
+            .filter(|dir| !self.files_map.contains_key(dir))
+            .collect();
+        for dir in implicit_dirs {
+            let file_meta =
+                FileMeta::from_attributes(MIMETYPE_FILESYSTEM_DIR, 0, &FileAttributes::default());
+            debug!("New implicit directory from archive: {}", dir);
+            self.files_map
+                .insert(dir, file_meta.to_file_item(FileItemType::Dir));
+        }
+    }
+}
+
+// Mode to record in an archive for a FileItem, using the default provided if it has none
+fn archive_mode(mode_bits: Option<u32>, default: u32) -> u32 {
+    mode_bits.map_or(default, |mode| mode & UNIX_PERMISSIONS_MASK)
+}
+
+fn archive_error<E: std::fmt::Display>(what: &str, err: E) -> Error {
+    Error::InvalidInput(format!("Failed to read {}: {}", what, err))
+}
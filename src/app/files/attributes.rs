@@ -0,0 +1,332 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{
+    normalise_files_map_path, validate_files_add_params, FileAttributes, FileItem, FilesMap,
+    ProcessedFiles, UpdateOptions,
+};
+use crate::{
+    app::{consts::*, helpers::gen_datetime_secs},
+    Error, Result, Safe,
+};
+use std::collections::BTreeMap;
+
+// Separator between the namespace and the name of custom attributes
+const NAMESPACE_SEPARATOR: char = ':';
+
+impl FileItem {
+    /// Returns the custom attributes set on the FileItem within a namespace, keyed by their name
+    pub fn custom_attributes(&self, namespace: &str) -> BTreeMap<String, String> {
+        let prefix = format!("{}{}", namespace, NAMESPACE_SEPARATOR);
+        self.extensions
+            .iter()
+            .filter_map(|(key, value)| {
+                key.strip_prefix(&prefix)
+                    .map(|name| (name.to_string(), value.clone()))
+            })
+            .collect()
+    }
+
+    // Set the metadata attributes provided, keeping the others, and returns whether
+    // any of them changed. The readonly flag is derived from the mode bits if not set.
+    pub(crate) fn set_metadata(&mut self, metadata: &FileAttributes) -> bool {
+        let readonly = metadata
+            .readonly
+            .or_else(|| metadata.mode_bits.map(|mode| mode & 0o222 == 0));
+
+        let mut changed = false;
+        if let Some(mode_bits) = metadata.mode_bits {
+            changed |= self.mode_bits.replace(mode_bits) != Some(mode_bits);
+        }
+        if let Some(readonly) = readonly {
+            changed |= self.readonly.replace(readonly) != Some(readonly);
+        }
+        if let Some(created) = metadata.created {
+            changed |= self.original_created.replace(created) != Some(created);
+        }
+        if let Some(modified) = metadata.modified {
+            changed |= self.original_modified.replace(modified) != Some(modified);
+        }
+        changed
+    }
+
+    /// Returns the value of a custom attribute set on the FileItem, if any
+    pub fn custom_attribute(&self, namespace: &str, name: &str) -> Option<&str> {
+        self.extensions
+            .get(&custom_attribute_key(namespace, name))
+            .map(String::as_str)
+    }
+}
+
+impl Safe {
+    /// # Set custom attributes on a path of a FilesContainer.
+    ///
+    /// The attributes are set within a namespace, e.g. the name of the application setting
+    /// them, replacing any attribute with the same name in it. They are committed as a new
+    /// version of the FilesContainer without uploading any content. The path is the one in
+    /// the URL, and it's not resolved if it's a symlink, the attributes are set on it.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::{Safe, files::{CreateOptions, SymlinkPolicy, UpdateOptions}};
+    /// # use std::collections::BTreeMap;
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, &CreateOptions { symlinks: SymlinkPolicy::FollowAll, ..CreateOptions::default() }).await.unwrap();
+    ///     let mut attributes = BTreeMap::new();
+    ///     attributes.insert("cache-control".to_string(), "max-age=3600".to_string());
+    ///     let url = format!("{}/test.md", xorurl);
+    ///     let (version, _processed_files, _files_map) = safe.files_container_set_attributes(&url, "http", attributes, &UpdateOptions::default()).await.unwrap();
+    ///     println!("FilesContainer is now at version: {}", version);
+    /// # });
+    /// ```
+    pub async fn files_container_set_attributes(
+        &mut self,
+        url: &str,
+        namespace: &str,
+        attributes: BTreeMap<String, String>,
+        options: &UpdateOptions,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        validate_namespace(namespace)?;
+        for name in attributes.keys() {
+            validate_attribute_name(namespace, name)?;
+        }
+
+        self.update_file_item_attributes(url, options, |file_item| {
+            let mut changed = false;
+            for (name, value) in attributes {
+                let key = custom_attribute_key(namespace, &name);
+                if file_item.extensions.get(&key) != Some(&value) {
+                    file_item.extensions.insert(key, value);
+                    changed = true;
+                }
+            }
+            changed
+        })
+        .await
+    }
+
+    /// # Remove custom attributes from a path of a FilesContainer.
+    ///
+    /// The attributes with the names provided are removed from the namespace, or all the
+    /// attributes of the namespace if no names are provided. They are committed as a new
+    /// version of the FilesContainer, unless none of them was set.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::{Safe, files::{CreateOptions, SymlinkPolicy, UpdateOptions}};
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, &CreateOptions { symlinks: SymlinkPolicy::FollowAll, ..CreateOptions::default() }).await.unwrap();
+    ///     let url = format!("{}/test.md", xorurl);
+    ///     let (version, _processed_files, _files_map) = safe.files_container_remove_attributes(&url, "http", &["cache-control"], &UpdateOptions::default()).await.unwrap();
+    ///     println!("FilesContainer is now at version: {}", version);
+    /// # });
+    /// ```
+    pub async fn files_container_remove_attributes(
+        &mut self,
+        url: &str,
+        namespace: &str,
+        names: &[&str],
+        options: &UpdateOptions,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        validate_namespace(namespace)?;
+        for name in names {
+            validate_attribute_name(namespace, name)?;
+        }
+
+        self.update_file_item_attributes(url, options, |file_item| {
+            let keys: Vec<String> = if names.is_empty() {
+                file_item
+                    .custom_attributes(namespace)
+                    .keys()
+                    .map(|name| custom_attribute_key(namespace, name))
+                    .collect()
+            } else {
+                names
+                    .iter()
+                    .map(|name| custom_attribute_key(namespace, name))
+                    .collect()
+            };
+
+            let mut changed = false;
+            for key in keys {
+                changed |= file_item.extensions.remove(&key).is_some();
+            }
+            changed
+        })
+        .await
+    }
+
+    /// # Update the metadata of a path of a FilesContainer.
+    ///
+    /// Only the attributes which are set are updated, the others are kept. The timestamps
+    /// provided are recorded as the original creation and modification times, and the
+    /// readonly flag is derived from the mode bits if they are set and the flag is not.
+    /// The changes are committed as a new version of the FilesContainer, unless there are none.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::{Safe, files::{CreateOptions, FileAttributes, SymlinkPolicy, UpdateOptions}};
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, &CreateOptions { symlinks: SymlinkPolicy::FollowAll, ..CreateOptions::default() }).await.unwrap();
+    ///     let metadata = FileAttributes { mode_bits: Some(0o100755), ..FileAttributes::default() };
+    ///     let url = format!("{}/test.md", xorurl);
+    ///     let (version, _processed_files, _files_map) = safe.files_container_set_metadata(&url, metadata, &UpdateOptions::default()).await.unwrap();
+    ///     println!("FilesContainer is now at version: {}", version);
+    /// # });
+    /// ```
+    pub async fn files_container_set_metadata(
+        &mut self,
+        url: &str,
+        metadata: FileAttributes,
+        options: &UpdateOptions,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        self.update_file_item_attributes(url, options, |file_item| {
+            file_item.set_metadata(&metadata)
+        })
+        .await
+    }
+
+    /// # Get the custom attributes set on a path of a FilesContainer within a namespace.
+    ///
+    /// The URL can target any version of the FilesContainer.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::{Safe, files::{CreateOptions, SymlinkPolicy}};
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, &CreateOptions { symlinks: SymlinkPolicy::FollowAll, ..CreateOptions::default() }).await.unwrap();
+    ///     let url = format!("{}/test.md", xorurl);
+    ///     let attributes = safe.files_container_get_attributes(&url, "http").await.unwrap();
+    ///     println!("Attributes set on the file: {:?}", attributes);
+    /// # });
+    /// ```
+    pub async fn files_container_get_attributes(
+        &mut self,
+        url: &str,
+        namespace: &str,
+    ) -> Result<BTreeMap<String, String>> {
+        validate_namespace(namespace)?;
+        let (safe_url, _) = self.parse_and_resolve_url(url).await?;
+        let path = normalise_files_map_path(safe_url.path());
+        let (_, files_map) = self.fetch_files_container(&safe_url).await?;
+        let file_item = files_map
+            .get(&path)
+            .ok_or_else(|| no_content_error(&path))?;
+
+        Ok(file_item.custom_attributes(namespace))
+    }
+
+    // Private helper to update the attributes of the FileItem at the path of the URL,
+    // committing a new version of the FilesContainer if the update changed it
+    async fn update_file_item_attributes<F>(
+        &mut self,
+        url: &str,
+        options: &UpdateOptions,
+        update: F,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)>
+    where
+        F: FnOnce(&mut FileItem) -> bool,
+    {
+        let UpdateOptions {
+            update_nrs,
+            expected_version,
+            dry_run,
+        } = *options;
+
+        let (safe_url, current_version, mut files_map) =
+            validate_files_add_params(self, "", url, update_nrs, expected_version).await?;
+
+        let path = normalise_files_map_path(safe_url.path());
+        let file_item = files_map
+            .get_mut(&path)
+            .ok_or_else(|| no_content_error(&path))?;
+
+        let mut processed_files = ProcessedFiles::new();
+        let success_count = if update(file_item) {
+            file_item.modified = gen_datetime_secs();
+            processed_files.insert(
+                path,
+                (
+                    CONTENT_UPDATED_SIGN.to_string(),
+                    file_item.link().unwrap_or_default().to_string(),
+                ),
+            );
+            1
+        } else {
+            0
+        };
+
+        let (version, files_map) = self
+            .append_version_to_files_container(
+                success_count,
+                current_version,
+                files_map,
+                url,
+                safe_url,
+                expected_version,
+                dry_run,
+                update_nrs,
+            )
+            .await?;
+
+        Ok((version, processed_files, files_map))
+    }
+}
+
+// Key the custom attribute is stored with among the FileItem's extension attributes
+fn custom_attribute_key(namespace: &str, name: &str) -> String {
+    format!("{}{}{}", namespace, NAMESPACE_SEPARATOR, name)
+}
+
+// Namespaces cannot contain the separator, thus custom attributes never clash
+// with the extension attributes which are not namespaced, like the file's name
+fn validate_namespace(namespace: &str) -> Result<()> {
+    if namespace.is_empty() || namespace.contains(NAMESPACE_SEPARATOR) {
+        return Err(Error::InvalidInput(format!(
+            "Invalid namespace '{}', it must be non-empty and cannot contain '{}'",
+            namespace, NAMESPACE_SEPARATOR
+        )));
+    }
+
+    Ok(())
+}
+
+fn validate_attribute_name(namespace: &str, name: &str) -> Result<()> {
+    if name.is_empty() {
+        return Err(Error::InvalidInput(format!(
+            "The name of the attributes in namespace '{}' cannot be empty",
+            namespace
+        )));
+    }
+
+    Ok(())
+}
+
+fn no_content_error(path: &str) -> Error {
+    Error::ContentError(format!(
+        "No content found matching the \"{}\" path on the target FilesContainer",
+        path
+    ))
+}
@@ -0,0 +1,489 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{
+    checksums::checksum_data,
+    is_private_files_container, is_same_file_item_content, is_same_or_subpath,
+    media_type::{detect_media_type, xorurl_media_type, RAW_MEDIA_TYPE},
+    metadata::FileMeta,
+    normalise_files_map_path, resolve_symlinks_target_type, validate_files_add_params, FileItem,
+    FileItemType, FilesMap, ProcessedFiles, SymlinkTargetType, UpdateOptions,
+};
+use crate::{app::consts::*, Error, Result, Safe, XorUrl};
+use chrono::{DateTime, Utc};
+use log::debug;
+use relative_path::{Component, RelativePath};
+use std::{collections::BTreeMap, io::Read, path::Path};
+
+/// Attributes which can be set for the entries of a FilesContainerBuilder, they
+/// are recorded in the FileItems as the original attributes of the files
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileAttributes {
+    /// Unix permissions mode bits
+    pub mode_bits: Option<u32>,
+    /// Read-only flag, derived from the mode bits if they are set and this is not
+    pub readonly: Option<bool>,
+    /// Creation timestamp
+    pub created: Option<DateTime<Utc>>,
+    /// Modification timestamp
+    pub modified: Option<DateTime<Utc>>,
+}
+
+// Content of an entry of a FilesContainerBuilder
+#[derive(Debug)]
+enum BuilderEntryKind {
+    File {
+        data: Vec<u8>,
+        media_type: Option<String>,
+    },
+    Dir,
+    Symlink(String),
+}
+
+#[derive(Debug)]
+struct BuilderEntry {
+    kind: BuilderEntryKind,
+    attributes: FileAttributes,
+}
+
+/// Builder of the content of a FilesContainer from in-memory data. Files, directories
+/// and symlinks are added to it, and then all of them are committed either as a new
+/// FilesContainer, or as a single new version of an existing FilesContainer.
+/// The parent directories of the entries added are created implicitly.
+#[derive(Debug, Default)]
+pub struct FilesContainerBuilder {
+    entries: BTreeMap<String, BuilderEntry>,
+}
+
+impl FilesContainerBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file with the content provided. If the media type is not
+    /// provided it's guessed from the path's extension.
+    pub fn add_file(
+        &mut self,
+        path: &str,
+        data: impl Into<Vec<u8>>,
+        media_type: Option<&str>,
+    ) -> Result<&mut Self> {
+        let kind = BuilderEntryKind::File {
+            data: data.into(),
+            media_type: media_type.map(|media_type| media_type.to_string()),
+        };
+        self.add_entry(path, kind)
+    }
+
+    /// Add a file with the content read from the reader provided
+    pub fn add_file_from_reader<R: Read>(
+        &mut self,
+        path: &str,
+        mut reader: R,
+        media_type: Option<&str>,
+    ) -> Result<&mut Self> {
+        let mut data = vec![];
+        reader.read_to_end(&mut data).map_err(|err| {
+            Error::InvalidInput(format!("Failed to read content for '{}': {}", path, err))
+        })?;
+        self.add_file(path, data, media_type)
+    }
+
+    /// Add a directory, which is only needed for empty directories or to set its attributes
+    pub fn add_dir(&mut self, path: &str) -> Result<&mut Self> {
+        self.add_entry(path, BuilderEntryKind::Dir)
+    }
+
+    /// Add a symlink pointing to the target provided, either an absolute path
+    /// within the FilesContainer or a path relative to the symlink
+    pub fn add_symlink(&mut self, path: &str, target: &str) -> Result<&mut Self> {
+        if target.is_empty() {
+            return Err(Error::InvalidInput(format!(
+                "The target of the symlink '{}' cannot be empty",
+                path
+            )));
+        }
+        self.add_entry(path, BuilderEntryKind::Symlink(target.to_string()))
+    }
+
+    /// Set the attributes of an entry previously added
+    pub fn set_attributes(&mut self, path: &str, attributes: FileAttributes) -> Result<&mut Self> {
+        let path = builder_path(path)?;
+        match self.entries.get_mut(&path) {
+            Some(entry) => {
+                entry.attributes = attributes;
+                Ok(self)
+            }
+            None => Err(Error::InvalidInput(format!(
+                "No entry was added at '{}' to set its attributes",
+                path
+            ))),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Add an entry, making sure none of its ancestors was added as a file or symlink,
+    // and that no entry was added under it if it's a file or symlink.
+    // An entry previously added at the same path is replaced.
+    fn add_entry(&mut self, path: &str, kind: BuilderEntryKind) -> Result<&mut Self> {
+        let path = builder_path(path)?;
+        if let Some(ancestor) = ancestors(&path).into_iter().find(|ancestor| {
+            self.entries
+                .get(ancestor)
+                .map_or(false, |entry| !matches!(entry.kind, BuilderEntryKind::Dir))
+        }) {
+            return Err(Error::InvalidInput(format!(
+                "Cannot add '{}' since '{}' was not added as a directory",
+                path, ancestor
+            )));
+        }
+
+        if !matches!(kind, BuilderEntryKind::Dir) {
+            let prefix = format!("{}/", path);
+            if let Some(descendant) = self.entries.keys().find(|other| other.starts_with(&prefix)) {
+                return Err(Error::InvalidInput(format!(
+                    "Cannot add '{}' as a file or symlink since '{}' was added under it",
+                    path, descendant
+                )));
+            }
+        }
+
+        let entry = BuilderEntry {
+            kind,
+            attributes: FileAttributes::default(),
+        };
+        self.entries.insert(path, entry);
+        Ok(self)
+    }
+}
+
+impl Safe {
+    /// # Create a FilesContainer from the content of a FilesContainerBuilder.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::{Safe, files::FilesContainerBuilder};
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    ///     safe.connect(None, None, None).await.unwrap();
+    ///     let mut builder = FilesContainerBuilder::new();
+    ///     builder.add_file("/index.html", "<h1>Hello!</h1>", None).unwrap()
+    ///         .add_symlink("/home.html", "index.html").unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create_from_builder(builder, false, false).await.unwrap();
+    ///     assert!(xorurl.contains("safe://"))
+    /// # });
+    /// ```
+    pub async fn files_container_create_from_builder(
+        &mut self,
+        builder: FilesContainerBuilder,
+        private: bool,
+        dry_run: bool,
+    ) -> Result<(XorUrl, ProcessedFiles, FilesMap)> {
+        let (processed_files, mut files_map, _) = files_map_from_builder(
+            self,
+            builder,
+            FilesMap::default(),
+            "/",
+            false,
+            private,
+            dry_run,
+        )
+        .await?;
+        self.apply_files_timestamps(&mut files_map);
+
+        let xorurl = if dry_run {
+            "".to_string()
+        } else {
+            self.store_new_files_container(&files_map, private).await?
+        };
+
+        Ok((xorurl, processed_files, files_map))
+    }
+
+    /// # Update a FilesContainer with the content of a FilesContainerBuilder.
+    ///
+    /// All the changes are committed as a single new version of the FilesContainer. The
+    /// entries are placed under the path of the URL, if any. Existing entries are replaced,
+    /// and if `delete` is set the entries not found in the builder are removed.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::{Safe, files::{CreateOptions, FilesContainerBuilder, SymlinkPolicy, UpdateOptions}};
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    ///     safe.connect(None, None, None).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata"), None, &CreateOptions { symlinks: SymlinkPolicy::FollowAll, ..CreateOptions::default() }).await.unwrap();
+    ///     let mut builder = FilesContainerBuilder::new();
+    ///     builder.add_file("/generated/page.html", "<h1>Generated</h1>", None).unwrap();
+    ///     let (version, _processed_files, _files_map) = safe.files_container_update_from_builder(&xorurl, builder, false, &UpdateOptions::default()).await.unwrap();
+    ///     println!("FilesContainer updated to version: {}", version);
+    /// # });
+    /// ```
+    pub async fn files_container_update_from_builder(
+        &mut self,
+        url: &str,
+        builder: FilesContainerBuilder,
+        delete: bool,
+        options: &UpdateOptions,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        let UpdateOptions {
+            update_nrs,
+            expected_version,
+            dry_run,
+        } = *options;
+
+        let (safe_url, current_version, current_files_map) =
+            validate_files_add_params(self, "", url, update_nrs, expected_version).await?;
+
+        let private = is_private_files_container(&safe_url);
+        let dest_base_path = normalise_files_map_path(safe_url.path());
+        let (processed_files, new_files_map, success_count) = files_map_from_builder(
+            self,
+            builder,
+            current_files_map,
+            &dest_base_path,
+            delete,
+            private,
+            dry_run,
+        )
+        .await?;
+
+        let (version, new_files_map) = self
+            .append_version_to_files_container(
+                success_count,
+                current_version,
+                new_files_map,
+                url,
+                safe_url,
+                expected_version,
+                dry_run,
+                update_nrs,
+            )
+            .await?;
+
+        Ok((version, processed_files, new_files_map))
+    }
+}
+
+// Apply the entries of a builder onto a FilesMap, uploading the files' content,
+// placing them under the base path provided. Entries of the FilesMap not found
+// in the builder are removed if requested.
+async fn files_map_from_builder(
+    safe: &mut Safe,
+    builder: FilesContainerBuilder,
+    mut files_map: FilesMap,
+    dest_base_path: &str,
+    delete: bool,
+    private: bool,
+    dry_run: bool,
+) -> Result<(ProcessedFiles, FilesMap, u64)> {
+    let mut processed_files = ProcessedFiles::new();
+    let mut success_count = 0;
+
+    let mut entries = BTreeMap::new();
+    for (path, entry) in builder.entries {
+        let final_path = normalise_files_map_path(&format!(
+            "{}/{}",
+            dest_base_path.trim_end_matches('/'),
+            path
+        ));
+        entries.insert(final_path, entry);
+    }
+
+    // Create the implicit parent directories which don't exist yet
+    let implicit_dirs: Vec<String> = entries
+        .keys()
+        .flat_map(|path| ancestors(path))
+        .filter(|dir| !entries.contains_key(dir))
+        .collect();
+    for dir in implicit_dirs {
+        if !files_map.get(&dir).map_or(false, FileItem::is_dir) {
+            let entry = BuilderEntry {
+                kind: BuilderEntryKind::Dir,
+                attributes: FileAttributes::default(),
+            };
+            entries.insert(dir, entry);
+        }
+    }
+
+    if delete {
+        let to_remove: Vec<String> = files_map
+            .keys()
+            .filter(|path| {
+                is_same_or_subpath(path, dest_base_path)
+                    && !entries.contains_key(*path)
+                    && !entries.keys().any(|entry| is_same_or_subpath(entry, path))
+            })
+            .cloned()
+            .collect();
+        for path in to_remove {
+            if let Some(file_item) = files_map.remove(&path) {
+                processed_files.insert(
+                    path,
+                    (
+                        CONTENT_DELETED_SIGN.to_string(),
+                        file_item.link().unwrap_or_default().to_string(),
+                    ),
+                );
+                success_count += 1;
+            }
+        }
+    }
+
+    for (path, entry) in entries {
+        let mut checksum = None;
+        let (file_type, file_size, file_item_type) = match entry.kind {
+            BuilderEntryKind::File { data, media_type } => {
+                let media_type = media_type.or_else(|| detect_media_type(Path::new(&path), &data));
+                let result = safe
+                    .files_store_blob(
+                        &data,
+                        xorurl_media_type(media_type.as_deref()),
+                        private,
+                        dry_run,
+                    )
+                    .await;
+                let link = match result {
+                    Ok(link) => link,
+                    Err(err) => {
+                        processed_files
+                            .insert(path, (CONTENT_ERROR_SIGN.to_string(), format!("<{}>", err)));
+                        continue;
+                    }
+                };
+                checksum = safe
+                    .files_config
+                    .checksum
+                    .map(|algorithm| checksum_data(algorithm, &data));
+                let media_type = media_type.unwrap_or_else(|| RAW_MEDIA_TYPE.to_string());
+                (
+                    media_type.clone(),
+                    data.len() as u64,
+                    FileItemType::File { media_type, link },
+                )
+            }
+            BuilderEntryKind::Dir => (MIMETYPE_FILESYSTEM_DIR.to_string(), 0, FileItemType::Dir),
+            BuilderEntryKind::Symlink(target) => (
+                MIMETYPE_FILESYSTEM_SYMLINK.to_string(),
+                0,
+                FileItemType::Symlink {
+                    target,
+                    target_type: SymlinkTargetType::Unknown,
+                },
+            ),
+        };
+
+        let file_meta = FileMeta::from_attributes(&file_type, file_size, &entry.attributes);
+        let mut new_file_item = file_meta.to_file_item(file_item_type);
+        new_file_item.checksum = checksum;
+        let change = match files_map.get(&path) {
+            Some(file_item)
+                if is_same_file_item_content(file_item, &new_file_item)
+                    && has_attributes(file_item, &new_file_item, &entry.attributes) =>
+            {
+                debug!("Skipping unchanged entry of the builder: {}", path);
+                continue;
+            }
+            Some(_) => CONTENT_UPDATED_SIGN,
+            None => CONTENT_ADDED_SIGN,
+        };
+
+        // A file or symlink replacing a directory replaces all its content as well
+        if !new_file_item.is_dir() && files_map.get(&path).map_or(false, FileItem::is_dir) {
+            let replaced: Vec<String> = files_map
+                .keys()
+                .filter(|child| *child != &path && is_same_or_subpath(child, &path))
+                .cloned()
+                .collect();
+            for child in replaced {
+                if let Some(file_item) = files_map.remove(&child) {
+                    processed_files.insert(
+                        child,
+                        (
+                            CONTENT_DELETED_SIGN.to_string(),
+                            file_item.link().unwrap_or_default().to_string(),
+                        ),
+                    );
+                    success_count += 1;
+                }
+            }
+        }
+
+        processed_files.insert(
+            path.clone(),
+            (
+                change.to_string(),
+                new_file_item.link().unwrap_or_default().to_string(),
+            ),
+        );
+        files_map.insert(path, new_file_item);
+        success_count += 1;
+    }
+
+    resolve_symlinks_target_type(&mut files_map);
+
+    Ok((processed_files, files_map, success_count))
+}
+
+// Whether a FileItem already has the attributes explicitly set for an entry of a builder,
+// as found in the new FileItem generated for it. Those not set are not compared, e.g. the
+// timestamps of a FileItem which was added from a local file.
+fn has_attributes(
+    file_item: &FileItem,
+    new_file_item: &FileItem,
+    attributes: &FileAttributes,
+) -> bool {
+    let mode_bits_match =
+        attributes.mode_bits.is_none() || file_item.mode_bits == new_file_item.mode_bits;
+    let readonly_match = (attributes.readonly.is_none() && attributes.mode_bits.is_none())
+        || file_item.readonly == new_file_item.readonly;
+    let created_match = attributes.created.is_none()
+        || file_item.original_created == new_file_item.original_created;
+    let modified_match = attributes.modified.is_none()
+        || file_item.original_modified == new_file_item.original_modified;
+    mode_bits_match && readonly_match && created_match && modified_match
+}
+
+// Normalise a path of an entry added to a builder, which cannot be the root
+// nor ascend beyond it
+pub(super) fn builder_path(path: &str) -> Result<String> {
+    let relative = RelativePath::new(path.trim_start_matches('/')).normalize();
+    if matches!(relative.components().next(), Some(Component::ParentDir)) {
+        return Err(Error::InvalidInput(format!(
+            "The path '{}' ascends beyond the root",
+            path
+        )));
+    }
+    if relative.as_str().is_empty() {
+        return Err(Error::InvalidInput(
+            "The path of an entry cannot be the root".to_string(),
+        ));
+    }
+
+    Ok(normalise_files_map_path(relative.as_str()))
+}
+
+// The ancestor directories of a path, excluding the root
+pub(super) fn ancestors(path: &str) -> Vec<String> {
+    let parts: Vec<&str> = path.split('/').filter(|part| !part.is_empty()).collect();
+    (1..parts.len())
+        .map(|len| format!("/{}", parts[..len].join("/")))
+        .collect()
+}
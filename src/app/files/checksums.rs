@@ -0,0 +1,210 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::FileItem;
+use crate::{Error, Result};
+use log::debug;
+use sha2::{Digest, Sha256};
+use sha3::Sha3_256;
+use std::{
+    fmt,
+    fs::File,
+    io::{self, Read},
+    path::Path,
+    str::FromStr,
+};
+
+// Size of the chunks a file is read in when computing its checksum
+const CHECKSUM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Digest algorithm used for the checksums of the files stored in FilesContainers.
+/// Checksums are recorded as the name of the algorithm followed by the hex-encoded
+/// digest, e.g. `sha256:<hex digest>`, so they can be verified with standard tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    Sha256,
+    Sha3_256,
+}
+
+impl ChecksumAlgorithm {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::Sha3_256 => "sha3-256",
+        }
+    }
+}
+
+impl fmt::Display for ChecksumAlgorithm {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.as_str())
+    }
+}
+
+impl FromStr for ChecksumAlgorithm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "sha256" => Ok(Self::Sha256),
+            "sha3-256" => Ok(Self::Sha3_256),
+            other => Err(Error::InvalidInput(format!(
+                "Unsupported checksum algorithm: '{}'",
+                other
+            ))),
+        }
+    }
+}
+
+// Digest being computed incrementally with any of the supported algorithms
+enum ChecksumHasher {
+    Sha256(Sha256),
+    Sha3_256(Sha3_256),
+}
+
+impl ChecksumHasher {
+    fn new(algorithm: ChecksumAlgorithm) -> Self {
+        match algorithm {
+            ChecksumAlgorithm::Sha256 => Self::Sha256(Sha256::new()),
+            ChecksumAlgorithm::Sha3_256 => Self::Sha3_256(Sha3_256::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Sha256(hasher) => hasher.update(data),
+            Self::Sha3_256(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finalize(self) -> String {
+        let (algorithm, digest) = match self {
+            Self::Sha256(hasher) => (ChecksumAlgorithm::Sha256, hasher.finalize().to_vec()),
+            Self::Sha3_256(hasher) => (ChecksumAlgorithm::Sha3_256, hasher.finalize().to_vec()),
+        };
+        format!("{}:{}", algorithm, hex::encode(digest))
+    }
+}
+
+// Checksum of some content held in memory
+pub(crate) fn checksum_data(algorithm: ChecksumAlgorithm, data: &[u8]) -> String {
+    let mut hasher = ChecksumHasher::new(algorithm);
+    hasher.update(data);
+    hasher.finalize()
+}
+
+// Read the content of a local file, computing its checksum with the algorithm
+// provided, if any, from the same chunks as they are read
+pub(crate) fn read_file_with_checksum(
+    path: &Path,
+    algorithm: Option<ChecksumAlgorithm>,
+) -> io::Result<(Vec<u8>, Option<String>)> {
+    let mut file = File::open(path)?;
+    let mut hasher = algorithm.map(ChecksumHasher::new);
+    let mut data = Vec::new();
+    let mut chunk = vec![0; CHECKSUM_CHUNK_SIZE];
+    loop {
+        let read = match file.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        if let Some(hasher) = hasher.as_mut() {
+            hasher.update(&chunk[..read]);
+        }
+        data.extend_from_slice(&chunk[..read]);
+    }
+
+    Ok((data, hasher.map(ChecksumHasher::finalize)))
+}
+
+// Verify the content of a file against the checksum recorded in its FileItem, if any.
+// Checksums computed with algorithms not supported are not verified.
+pub(crate) fn verify_file_item_checksum(file_item: &FileItem, data: &[u8]) -> Result<()> {
+    let checksum = match &file_item.checksum {
+        Some(checksum) => checksum,
+        None => return Ok(()),
+    };
+
+    let algorithm = match checksum.split(':').next().map(ChecksumAlgorithm::from_str) {
+        Some(Ok(algorithm)) => algorithm,
+        _ => {
+            debug!(
+                "Skipping verification of unsupported checksum: {}",
+                checksum
+            );
+            return Ok(());
+        }
+    };
+
+    let actual = checksum_data(algorithm, data);
+    if actual.eq_ignore_ascii_case(checksum) {
+        Ok(())
+    } else {
+        Err(Error::ChecksumMismatch {
+            expected: checksum.to_string(),
+            actual,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::files::{FileItemType, FileMeta};
+
+    #[test]
+    fn test_checksums() -> Result<()> {
+        assert_eq!(
+            checksum_data(ChecksumAlgorithm::Sha256, b"abc"),
+            "sha256:ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            checksum_data(ChecksumAlgorithm::Sha3_256, b"abc"),
+            "sha3-256:3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+        );
+
+        let mut file_item =
+            FileMeta::from_type_and_size("text/plain", 3).to_file_item(FileItemType::File {
+                media_type: "text/plain".to_string(),
+                link: "safe://link".to_string(),
+            });
+        assert!(verify_file_item_checksum(&file_item, b"abc").is_ok());
+        file_item.checksum = Some(checksum_data(ChecksumAlgorithm::Sha3_256, b"abc"));
+        assert!(verify_file_item_checksum(&file_item, b"abc").is_ok());
+        assert!(matches!(
+            verify_file_item_checksum(&file_item, b"abd"),
+            Err(Error::ChecksumMismatch { .. })
+        ));
+        file_item.checksum = Some("md5:900150983cd24fb0d6963f7d28e17f72".to_string());
+        assert!(verify_file_item_checksum(&file_item, b"abd").is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_file_with_checksum() -> io::Result<()> {
+        let path = Path::new("../testdata/test.md");
+        let content = std::fs::read(path)?;
+
+        let (data, checksum) = read_file_with_checksum(path, Some(ChecksumAlgorithm::Sha256))?;
+        assert_eq!(data, content);
+        assert_eq!(
+            checksum,
+            Some(checksum_data(ChecksumAlgorithm::Sha256, &content))
+        );
+
+        let (data, checksum) = read_file_with_checksum(path, None)?;
+        assert_eq!(data, content);
+        assert!(checksum.is_none());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{
+    ChecksumAlgorithm, DeletionSafeguards, FilesMapEncoding, FilesMapLayout, FilesTimestamps,
+    DEFAULT_SYMLINKS_MAX_DEPTH,
+};
+use safe_network::types::Token;
+
+/// Settings used by a `Safe` instance for the FilesContainers it creates, updates and
+/// fetches. The ones about how FilesMaps are stored only apply to the FilesContainers
+/// created with it, existing FilesContainers keep the format they were created with.
+#[derive(Debug, Clone)]
+pub struct FilesConfig {
+    /// Encoding used for the FilesMaps of the FilesContainers created
+    pub files_map_encoding: FilesMapEncoding,
+    /// Layout used for the FilesMaps of the FilesContainers created
+    pub files_map_layout: FilesMapLayout,
+    /// When set, new versions of FilesContainers are stored as deltas against their previous
+    /// version, with a complete snapshot of the FilesMap every this number of versions.
+    pub files_map_snapshot_interval: Option<u64>,
+    /// Storage cost per MiB used to estimate the cost of the upload plans of FilesContainers.
+    /// The network doesn't quote storage costs yet, thus no estimate is made if not set.
+    pub storage_cost_per_mib: Option<Token>,
+    /// Maximum number of nested symlinks followed on any path when reading local folders
+    /// with a `SymlinkPolicy` which follows them, deeper symlinks are reported as errors.
+    pub symlinks_max_depth: usize,
+    /// Safeguards against deleting paths of FilesContainers by accident when syncing them
+    pub deletion_safeguards: DeletionSafeguards,
+    /// Source of the timestamps set on the files stored in FilesContainers. Any other than
+    /// the current time makes identical content always produce the same FilesMap address.
+    pub timestamps: FilesTimestamps,
+    /// When set, the checksum of the files uploaded is recorded in their FileItems,
+    /// computed with this algorithm
+    pub checksum: Option<ChecksumAlgorithm>,
+    /// Verify the content of the files fetched against their checksum, when
+    /// they have one recorded and the whole content is fetched
+    pub verify_checksums: bool,
+}
+
+impl Default for FilesConfig {
+    fn default() -> Self {
+        Self {
+            files_map_encoding: FilesMapEncoding::default(),
+            files_map_layout: FilesMapLayout::default(),
+            files_map_snapshot_interval: None,
+            storage_cost_per_mib: None,
+            symlinks_max_depth: DEFAULT_SYMLINKS_MAX_DEPTH,
+            deletion_safeguards: DeletionSafeguards::default(),
+            timestamps: FilesTimestamps::default(),
+            checksum: None,
+            verify_checksums: true,
+        }
+    }
+}
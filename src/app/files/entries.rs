@@ -0,0 +1,178 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{
+    builder::{ancestors, builder_path},
+    metadata::FileMeta,
+    resolve_symlinks_target_type, validate_files_add_params, FileItemType, FilesMap,
+    ProcessedFiles, SymlinkTargetType, UpdateOptions,
+};
+use crate::{app::consts::*, Error, Result, Safe};
+
+impl Safe {
+    /// # Create an empty directory on a FilesContainer.
+    ///
+    /// The directory is created at the path of the URL, along with any of its parent
+    /// directories which don't exist yet, and committed as a new version of the
+    /// FilesContainer. Nothing is committed if the directory already exists.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::{Safe, files::{CreateOptions, UpdateOptions}};
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, &CreateOptions::default()).await.unwrap();
+    ///     let url = format!("{}/assets/images", xorurl);
+    ///     let (version, _processed_files, _files_map) = safe.files_container_mkdir(&url, &UpdateOptions::default()).await.unwrap();
+    ///     println!("FilesContainer is now at version: {}", version);
+    /// # });
+    /// ```
+    pub async fn files_container_mkdir(
+        &mut self,
+        url: &str,
+        options: &UpdateOptions,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        self.add_files_map_entry(url, FileItemType::Dir, false, options)
+            .await
+    }
+
+    /// # Create a symlink on a FilesContainer.
+    ///
+    /// The symlink is created at the path of the URL pointing to the target provided, either
+    /// an absolute path within the FilesContainer or a path relative to the symlink. The
+    /// target doesn't need to exist. Any of its parent directories which don't exist yet are
+    /// created, and all of them are committed as a new version of the FilesContainer.
+    /// An existing file or symlink at the same path is only replaced if `force` is set.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::{Safe, files::{CreateOptions, UpdateOptions}};
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, &CreateOptions::default()).await.unwrap();
+    ///     let url = format!("{}/latest.md", xorurl);
+    ///     let (version, _processed_files, _files_map) = safe.files_container_symlink(&url, "test.md", false, &UpdateOptions::default()).await.unwrap();
+    ///     println!("FilesContainer is now at version: {}", version);
+    /// # });
+    /// ```
+    pub async fn files_container_symlink(
+        &mut self,
+        url: &str,
+        target: &str,
+        force: bool,
+        options: &UpdateOptions,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        if target.is_empty() {
+            return Err(Error::InvalidInput(
+                "The target of the symlink cannot be empty".to_string(),
+            ));
+        }
+
+        let file_type = FileItemType::Symlink {
+            target: target.to_string(),
+            target_type: SymlinkTargetType::Unknown,
+        };
+        self.add_files_map_entry(url, file_type, force, options)
+            .await
+    }
+
+    // Private helper to add a directory or symlink at the path of the URL, creating its
+    // missing parent directories, and commit them as a new version of the FilesContainer
+    async fn add_files_map_entry(
+        &mut self,
+        url: &str,
+        file_type: FileItemType,
+        force: bool,
+        options: &UpdateOptions,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        let UpdateOptions {
+            update_nrs,
+            expected_version,
+            dry_run,
+        } = *options;
+        let (safe_url, current_version, mut files_map) =
+            validate_files_add_params(self, "", url, update_nrs, expected_version).await?;
+
+        let path = builder_path(safe_url.path())?;
+        let mut processed_files = ProcessedFiles::new();
+        for ancestor in ancestors(&path) {
+            match files_map.get(&ancestor) {
+                Some(file_item) if file_item.is_dir() => {}
+                Some(_) => {
+                    return Err(Error::InvalidInput(format!(
+                        "Cannot create '{}' since '{}' is not a directory",
+                        path, ancestor
+                    )))
+                }
+                None => {
+                    let file_item = FileMeta::from_type_and_size(MIMETYPE_FILESYSTEM_DIR, 0)
+                        .to_file_item(FileItemType::Dir);
+                    files_map.insert(ancestor.clone(), file_item);
+                    processed_files.insert(
+                        ancestor,
+                        (CONTENT_ADDED_SIGN.to_string(), String::default()),
+                    );
+                }
+            }
+        }
+
+        let is_dir = file_type == FileItemType::Dir;
+        let change = match files_map.get(&path) {
+            None => Some(CONTENT_ADDED_SIGN),
+            // Creating an existing directory is not an error, there is nothing to do
+            Some(file_item) if file_item.is_dir() && is_dir => None,
+            Some(file_item) if file_item.is_dir() => {
+                return Err(Error::InvalidInput(format!(
+                    "A directory already exists at '{}', it cannot be replaced",
+                    path
+                )))
+            }
+            Some(_) if force && !is_dir => Some(CONTENT_UPDATED_SIGN),
+            Some(_) => {
+                return Err(Error::InvalidInput(format!(
+                    "An entry already exists at '{}'. Use the 'force' flag to replace it",
+                    path
+                )))
+            }
+        };
+
+        if let Some(change) = change {
+            let media_type = if is_dir {
+                MIMETYPE_FILESYSTEM_DIR
+            } else {
+                MIMETYPE_FILESYSTEM_SYMLINK
+            };
+            let file_item = FileMeta::from_type_and_size(media_type, 0).to_file_item(file_type);
+            files_map.insert(path.clone(), file_item);
+            processed_files.insert(path, (change.to_string(), String::default()));
+            resolve_symlinks_target_type(&mut files_map);
+        }
+
+        let (version, files_map) = self
+            .append_version_to_files_container(
+                processed_files.len() as u64,
+                current_version,
+                files_map,
+                url,
+                safe_url,
+                expected_version,
+                dry_run,
+                update_nrs,
+            )
+            .await?;
+
+        Ok((version, processed_files, files_map))
+    }
+}
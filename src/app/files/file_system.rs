@@ -7,39 +7,56 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-use super::{metadata::get_metadata, ProcessedFiles};
+use super::{
+    checksums::read_file_with_checksum,
+    media_type::{detect_media_type, xorurl_media_type},
+    metadata::get_metadata,
+    ProcessedFiles, SymlinkPolicy,
+};
 use crate::{app::consts::*, Error, Result, Safe, XorUrl};
 use log::info;
-use std::{collections::BTreeMap, fs, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
 use walkdir::{DirEntry, WalkDir};
 
 const MAX_RECURSIVE_DEPTH: usize = 10_000;
 
-// Upload a files to the Network as a Public Blob
+// Checksums of the local files uploaded, keyed by their local paths
+pub(crate) type FileChecksums = BTreeMap<String, String>;
+
+// Upload a files to the Network as a Public Blob, or as a Private Blob if requested.
+// The checksum of the content uploaded is returned along with its link if enabled,
+// except on a dry run since nothing is recorded then.
 pub(crate) async fn upload_file_to_net(
     safe: &mut Safe,
     path: &Path,
+    private: bool,
     dry_run: bool,
-) -> Result<XorUrl> {
-    let data = fs::read(path).map_err(|err| {
+) -> Result<(XorUrl, Option<String>)> {
+    let algorithm = if dry_run {
+        None
+    } else {
+        safe.files_config.checksum
+    };
+    let (data, checksum) = read_file_with_checksum(path, algorithm).map_err(|err| {
         Error::InvalidInput(format!("Failed to read file from local location: {}", err))
     })?;
 
-    let mime_type = mime_guess::from_path(&path);
-    match safe
-        .files_store_public_blob(&data, mime_type.first_raw(), dry_run)
-        .await
-    {
-        Ok(xorurl) => Ok(xorurl),
-        Err(err) => {
-            // Let's then upload it and set media-type to be simply raw content
-            if let Error::InvalidMediaType(_) = err {
-                safe.files_store_public_blob(&data, None, dry_run).await
-            } else {
-                Err(err)
-            }
-        }
-    }
+    // Media types not supported by the XOR-URL encoding are only kept in the FileItem
+    let media_type = detect_media_type(path, &data);
+    let xorurl = safe
+        .files_store_blob(
+            &data,
+            xorurl_media_type(media_type.as_deref()),
+            private,
+            dry_run,
+        )
+        .await?;
+
+    Ok((xorurl, checksum))
 }
 
 // Simply change Windows style path separator into `/`
@@ -54,30 +71,113 @@ pub(crate) async fn file_system_dir_walk(
     safe: &mut Safe,
     location: &str,
     recursive: bool,
-    follow_links: bool,
+    symlinks: SymlinkPolicy,
+    private: bool,
+    dry_run: bool,
+) -> Result<ProcessedFiles> {
+    let mut checksums = FileChecksums::new();
+    dir_walk(
+        safe,
+        location,
+        recursive,
+        symlinks,
+        private,
+        dry_run,
+        &mut checksums,
+    )
+    .await
+}
+
+// Same as `file_system_dir_walk`, also returning the checksums of the files uploaded
+// if enabled, as they are computed from the same content the files were uploaded with
+pub(crate) async fn file_system_dir_walk_with_checksums(
+    safe: &mut Safe,
+    location: &str,
+    recursive: bool,
+    symlinks: SymlinkPolicy,
+    private: bool,
+    dry_run: bool,
+) -> Result<(ProcessedFiles, FileChecksums)> {
+    let mut checksums = FileChecksums::new();
+    let processed_files = dir_walk(
+        safe,
+        location,
+        recursive,
+        symlinks,
+        private,
+        dry_run,
+        &mut checksums,
+    )
+    .await?;
+    Ok((processed_files, checksums))
+}
+
+async fn dir_walk(
+    safe: &mut Safe,
+    location: &str,
+    recursive: bool,
+    symlinks: SymlinkPolicy,
+    private: bool,
     dry_run: bool,
+    checksums: &mut FileChecksums,
 ) -> Result<ProcessedFiles> {
     let file_path = Path::new(location);
     info!("Reading files from {}", file_path.display());
-    let (metadata, _) = get_metadata(file_path, follow_links)?;
+    let (metadata, _) = get_metadata(file_path, follows_symlinks(symlinks))?;
     if metadata.is_dir() || !recursive {
-        // TODO: option to enable following symlinks?
         // We now compare both FilesMaps to upload the missing files
         let max_depth = if recursive { MAX_RECURSIVE_DEPTH } else { 1 };
         let mut processed_files = BTreeMap::new();
-        let children_to_process = WalkDir::new(file_path)
-            .follow_links(follow_links)
+
+        let root = canonical_root(file_path);
+        let max_links_depth = safe.files_config.symlinks_max_depth;
+        // Number of symlinks followed to reach each of the dirs walked, so
+        // the walk doesn't go deeper than allowed into nested symlinks
+        let mut links_depth = HashMap::<PathBuf, usize>::new();
+        let entries = WalkDir::new(file_path)
+            .follow_links(follows_symlinks(symlinks))
             .into_iter()
-            .filter_entry(|e| valid_depth(e, max_depth))
-            .filter_map(|v| v.ok());
+            .filter_entry(|entry| {
+                valid_depth(entry, max_depth)
+                    && followed_symlink_allowed(
+                        entry,
+                        symlinks,
+                        root.as_deref(),
+                        max_links_depth,
+                        &mut links_depth,
+                        &mut processed_files,
+                    )
+            })
+            .collect::<Vec<_>>();
 
-        for (idx, child) in children_to_process.enumerate() {
+        let mut children_to_process = Vec::new();
+        for entry in entries {
+            match entry {
+                Ok(child) => children_to_process.push(child),
+                Err(err) => {
+                    if let Some(path) = err.path() {
+                        let normalised_path = normalise_path_separator(path.to_str().unwrap_or(""));
+                        let msg = walk_error_msg(&err, path);
+                        info!("Skipping \"{}\". {}", normalised_path, msg);
+                        processed_files.insert(
+                            normalised_path,
+                            (CONTENT_ERROR_SIGN.to_string(), format!("<{}>", msg)),
+                        );
+                    } else {
+                        info!("Skipping entry which failed to be read: {}", err);
+                    }
+                }
+            }
+        }
+
+        for (idx, child) in children_to_process.iter().enumerate() {
             let current_file_path = child.path();
             let current_path_str = current_file_path.to_str().unwrap_or("").to_string();
             info!("Processing {}...", current_path_str);
             let normalised_path = normalise_path_separator(&current_path_str);
 
-            let result = get_metadata(current_file_path, follow_links);
+            let follow = follows_symlink_at(symlinks, current_file_path, root.as_deref());
+            let result = get_metadata(current_file_path, follow);
             match result {
                 Ok((metadata, _)) => {
                     if metadata.file_type().is_dir() {
@@ -104,14 +204,21 @@ pub(crate) async fn file_system_dir_walk(
                         );
                     }
                     if metadata.file_type().is_symlink() {
+                        if symlinks == SymlinkPolicy::Skip {
+                            info!("Skipping symlink \"{}\"", normalised_path);
+                            continue;
+                        }
                         processed_files.insert(
                             normalised_path.clone(),
                             (CONTENT_ADDED_SIGN.to_string(), String::default()),
                         );
                     }
                     if metadata.file_type().is_file() {
-                        match upload_file_to_net(safe, current_file_path, dry_run).await {
-                            Ok(xorurl) => {
+                        match upload_file_to_net(safe, current_file_path, private, dry_run).await {
+                            Ok((xorurl, checksum)) => {
+                                if let Some(checksum) = checksum {
+                                    checksums.insert(normalised_path.clone(), checksum);
+                                }
                                 processed_files.insert(
                                     normalised_path,
                                     (CONTENT_ADDED_SIGN.to_string(), xorurl),
@@ -151,6 +258,130 @@ pub(crate) async fn file_system_dir_walk(
     }
 }
 
+// Whether the policy follows any symlink at all
+fn follows_symlinks(symlinks: SymlinkPolicy) -> bool {
+    match symlinks {
+        SymlinkPolicy::FollowWithinRoot | SymlinkPolicy::FollowAll => true,
+        SymlinkPolicy::Preserve | SymlinkPolicy::Skip => false,
+    }
+}
+
+// Canonical path of the folder being read, or the parent folder if it's a single file
+fn canonical_root(location: &Path) -> Option<PathBuf> {
+    let root = fs::canonicalize(location).ok()?;
+    if root.is_dir() {
+        Some(root)
+    } else {
+        root.parent().map(Path::to_path_buf)
+    }
+}
+
+// Whether the policy follows the symlink found at a path, if it's a symlink at all,
+// given the canonical root of the local folder being read
+fn follows_symlink_at(symlinks: SymlinkPolicy, path: &Path, root: Option<&Path>) -> bool {
+    match symlinks {
+        SymlinkPolicy::FollowAll => true,
+        SymlinkPolicy::FollowWithinRoot => match (fs::canonicalize(path), root) {
+            (Ok(target), Some(root)) => target.starts_with(root),
+            _ => false,
+        },
+        SymlinkPolicy::Preserve | SymlinkPolicy::Skip => false,
+    }
+}
+
+// Whether the metadata of a local path shall be read following the symlink found
+// at it according to the policy, within the root of the `location` being read
+pub(crate) fn follow_symlink(symlinks: SymlinkPolicy, path: &Path, location: &str) -> bool {
+    follows_symlinks(symlinks)
+        && follows_symlink_at(
+            symlinks,
+            path,
+            canonical_root(Path::new(location)).as_deref(),
+        )
+}
+
+// Checks if the walk can go through an entry when following symlinks. Symlinks nested
+// deeper than allowed are recorded as errors, and those pointing outside of the root
+// with the follow-within-root policy are recorded to be preserved, not walked through.
+fn followed_symlink_allowed(
+    entry: &DirEntry,
+    symlinks: SymlinkPolicy,
+    root: Option<&Path>,
+    max_links_depth: usize,
+    links_depth: &mut HashMap<PathBuf, usize>,
+    processed_files: &mut ProcessedFiles,
+) -> bool {
+    if !follows_symlinks(symlinks) {
+        return true;
+    }
+
+    let parent_depth = entry
+        .path()
+        .parent()
+        .and_then(|parent| links_depth.get(parent))
+        .copied()
+        .unwrap_or(0);
+    let depth = parent_depth + usize::from(entry.path_is_symlink());
+    if entry.path_is_symlink() && entry.depth() > 0 {
+        let normalised_path = normalise_path_separator(entry.path().to_str().unwrap_or(""));
+        if depth > max_links_depth {
+            let msg = format!(
+                "Too many levels of symbolic links, no more than {} nested symlinks are followed",
+                max_links_depth
+            );
+            info!("Skipping \"{}\". {}", normalised_path, msg);
+            processed_files.insert(
+                normalised_path,
+                (CONTENT_ERROR_SIGN.to_string(), format!("<{}>", msg)),
+            );
+            return false;
+        }
+
+        if !follows_symlink_at(symlinks, entry.path(), root) {
+            info!(
+                "Preserving symlink \"{}\" since it points outside of the folder being read",
+                normalised_path
+            );
+            processed_files.insert(
+                normalised_path,
+                (CONTENT_ADDED_SIGN.to_string(), String::default()),
+            );
+            return false;
+        }
+    }
+
+    if entry.file_type().is_dir() {
+        links_depth.insert(entry.path().to_path_buf(), depth);
+    }
+    true
+}
+
+// Describe the error found when walking a local path, making it clear
+// when it's due to a symlink loop or a dangling symlink
+fn walk_error_msg(err: &walkdir::Error, path: &Path) -> String {
+    if let Some(ancestor) = err.loop_ancestor() {
+        return format!(
+            "Symlink loop detected, it points to its ancestor '{}'",
+            ancestor.display()
+        );
+    }
+
+    let is_symlink = fs::symlink_metadata(path)
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false);
+    if is_symlink && fs::metadata(path).is_err() {
+        match fs::read_link(path) {
+            Ok(target) => format!(
+                "Dangling symlink, its target '{}' doesn't exist",
+                target.display()
+            ),
+            Err(_) => "Dangling symlink, its target doesn't exist".to_string(),
+        }
+    } else {
+        err.to_string()
+    }
+}
+
 // Checks if the depth in the dir hierarchy is under a threshold
 fn valid_depth(entry: &DirEntry, max_depth: usize) -> bool {
     entry
@@ -166,6 +397,7 @@ fn valid_depth(entry: &DirEntry, max_depth: usize) -> bool {
 pub(crate) async fn file_system_single_file(
     safe: &mut Safe,
     location: &str,
+    private: bool,
     dry_run: bool,
 ) -> Result<ProcessedFiles> {
     let file_path = Path::new(location);
@@ -181,8 +413,8 @@ pub(crate) async fn file_system_single_file(
             location
         )))
     } else {
-        match upload_file_to_net(safe, file_path, dry_run).await {
-            Ok(xorurl) => {
+        match upload_file_to_net(safe, file_path, private, dry_run).await {
+            Ok((xorurl, _)) => {
                 processed_files.insert(normalised_path, (CONTENT_ADDED_SIGN.to_string(), xorurl));
             }
             Err(err) => {
@@ -12,30 +12,916 @@ use super::{
     metadata::FileMeta,
     ProcessedFiles,
 };
-use crate::{app::consts::*, Error, Result, Safe};
+use crate::{
+    app::{
+        consts::*,
+        helpers::{datetime_to_rfc3339, rfc3339_to_datetime},
+        rdf::{RdfFormat, ToRdf, TurtleNode, TurtleObject, JSONLD_CONTEXT, JSONLD_TYPE},
+    },
+    Error, Result, Safe, SafeUrl,
+};
+use chrono::{DateTime, Utc};
 use log::{debug, info};
-use std::{collections::BTreeMap, fs, path::Path};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+    fmt, fs,
+    path::Path,
+};
+
+/// Version of the schema used when serialising FilesMaps. FilesMaps serialised
+/// before the schema was versioned are read as version 0.
+pub const FILES_MAP_SCHEMA_VERSION: u64 = 1;
+
+// Keys used in the serialised FilesMap
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+const FILES_KEY: &str = "files";
+const PATH_KEY: &str = "path";
+const SHARDS_KEY: &str = "shards";
+const BASE_VERSION_KEY: &str = "base_version";
+const ADDED_KEY: &str = "added";
+const MODIFIED_KEY: &str = "modified";
+const REMOVED_KEY: &str = "removed";
+
+// Query params used in the link to a FilesMap to record the encoding and layout it was stored with
+const FILES_MAP_ENCODING_QUERY_KEY: &str = "encoding";
+const MSGPACK_ZSTD_ENCODING: &str = "msgpack-zstd";
+const FILES_MAP_LAYOUT_QUERY_KEY: &str = "layout";
+const SHARDED_LAYOUT: &str = "sharded";
+const FILES_MAP_DELTA_QUERY_KEY: &str = "delta";
+const ZSTD_COMPRESSION_LEVEL: i32 = 3;
+const EXTENSIONS_KEY: &str = "extensions";
+const SYMLINK_TARGET_KEY: &str = "symlink_target";
+const SYMLINK_TARGET_TYPE_KEY: &str = "symlink_target_type";
 
 // To use for mapping files names (with path in a flattened hierarchy) to FileItems
 pub type FilesMap = BTreeMap<String, FileItem>;
 
-// Each FileItem contains file metadata and the link to the file's Blob XOR-URL
-pub type FileItem = BTreeMap<String, String>;
+/// Encoding used to serialise the FilesMaps of a FilesContainer. It's chosen when the
+/// FilesContainer is created, and recorded in the link to each FilesMap version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilesMapEncoding {
+    /// JSON-LD document, which can be consumed with standard linked-data tools
+    JsonLd,
+    /// MessagePack document compressed with zstd, much more compact
+    /// for FilesContainers with a large number of files
+    MsgPackZstd,
+}
+
+impl Default for FilesMapEncoding {
+    fn default() -> Self {
+        Self::JsonLd
+    }
+}
+
+/// Layout used to store the FilesMaps of a FilesContainer. It's chosen when the
+/// FilesContainer is created, and recorded in the link to each FilesMap version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilesMapLayout {
+    /// The whole FilesMap is stored in a single Blob
+    Single,
+    /// The FilesMap is split into one shard per directory, each of them stored in its own
+    /// Blob and linking to the shards of its subdirectories. Only the shards on a path
+    /// need to be fetched to resolve it, and only the shards which changed are stored
+    /// for new versions, which makes it suitable for very large directory trees.
+    Sharded,
+}
+
+impl Default for FilesMapLayout {
+    fn default() -> Self {
+        Self::Single
+    }
+}
+
+// How a version of a FilesMap is stored, as recorded in the link to it. If the
+// version is stored as a delta, the version it's based on is also recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct FilesMapFormat {
+    pub(crate) encoding: FilesMapEncoding,
+    pub(crate) layout: FilesMapLayout,
+    pub(crate) delta_base: Option<u64>,
+}
+
+impl FilesMapFormat {
+    // Returns the format recorded in the link to a FilesMap, links without the
+    // query params are for JSON single Blob FilesMaps, for backward compatibility.
+    pub(crate) fn from_files_map_link(link: &SafeUrl) -> Result<Self> {
+        let encoding = match link.query_key_first(FILES_MAP_ENCODING_QUERY_KEY) {
+            None => FilesMapEncoding::JsonLd,
+            Some(encoding) if encoding == MSGPACK_ZSTD_ENCODING => FilesMapEncoding::MsgPackZstd,
+            Some(other) => {
+                return Err(Error::ContentError(format!(
+                    "Unsupported encoding '{}' found for the FilesMap stored in the FilesContainer",
+                    other
+                )))
+            }
+        };
+        let layout = match link.query_key_first(FILES_MAP_LAYOUT_QUERY_KEY) {
+            None => FilesMapLayout::Single,
+            Some(layout) if layout == SHARDED_LAYOUT => FilesMapLayout::Sharded,
+            Some(other) => {
+                return Err(Error::ContentError(format!(
+                    "Unsupported layout '{}' found for the FilesMap stored in the FilesContainer",
+                    other
+                )))
+            }
+        };
+
+        let delta_base = match link.query_key_first(FILES_MAP_DELTA_QUERY_KEY) {
+            None => None,
+            Some(base) => Some(base.parse::<u64>().map_err(|_| {
+                Error::ContentError(format!(
+                    "Invalid base version '{}' found for the FilesMap delta stored in the FilesContainer",
+                    base
+                ))
+            })?),
+        };
+
+        Ok(Self {
+            encoding,
+            layout,
+            delta_base,
+        })
+    }
+
+    // Records the format in the link to a FilesMap
+    pub(crate) fn set_in_files_map_link(&self, link: &mut SafeUrl) -> Result<()> {
+        if self.encoding == FilesMapEncoding::MsgPackZstd {
+            link.set_query_key(FILES_MAP_ENCODING_QUERY_KEY, Some(MSGPACK_ZSTD_ENCODING))?;
+        }
+        if self.layout == FilesMapLayout::Sharded {
+            link.set_query_key(FILES_MAP_LAYOUT_QUERY_KEY, Some(SHARDED_LAYOUT))?;
+        }
+        if let Some(base) = self.delta_base {
+            link.set_query_key(FILES_MAP_DELTA_QUERY_KEY, Some(&base.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+// A shard of a sharded FilesMap: the FileItems found directly in a directory,
+// and the links to the shards of its subdirectories, keyed by their path
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct FilesMapShard {
+    pub(crate) files: FilesMap,
+    pub(crate) shards: BTreeMap<String, String>,
+}
+
+// The changes made to a FilesMap from the version it's based on. It's stored
+// instead of the complete FilesMap for versions stored as deltas.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct FilesMapDelta {
+    pub(crate) base_version: u64,
+    pub(crate) added: FilesMap,
+    pub(crate) modified: FilesMap,
+    pub(crate) removed: BTreeSet<String>,
+}
+
+impl FilesMapDelta {
+    // Generate the delta which turns the base FilesMap into the target FilesMap
+    pub(crate) fn new(base_version: u64, base: &FilesMap, target: &FilesMap) -> Self {
+        let mut delta = Self {
+            base_version,
+            ..Self::default()
+        };
+        for (path, file_item) in target {
+            match base.get(path) {
+                None => {
+                    delta.added.insert(path.clone(), file_item.clone());
+                }
+                Some(base_item) if base_item != file_item => {
+                    delta.modified.insert(path.clone(), file_item.clone());
+                }
+                Some(_) => {}
+            }
+        }
+        delta.removed = base
+            .keys()
+            .filter(|path| !target.contains_key(*path))
+            .cloned()
+            .collect();
+
+        delta
+    }
+
+    // Apply the changes to the FilesMap of the base version
+    pub(crate) fn apply(&self, files_map: &mut FilesMap) {
+        for path in &self.removed {
+            files_map.remove(path);
+        }
+        for (path, file_item) in self.added.iter().chain(self.modified.iter()) {
+            files_map.insert(path.clone(), file_item.clone());
+        }
+    }
+}
+
+/// Type of the entry a symlink points to. This is a hint for windows-platform
+/// clients to be able to call symlink_dir() or symlink_file(), on unix there's no need.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymlinkTargetType {
+    File,
+    Dir,
+    /// The target type couldn't be determined, e.g. a broken link
+    Unknown,
+}
+
+impl SymlinkTargetType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::File => "file",
+            Self::Dir => "dir",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for SymlinkTargetType {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.as_str())
+    }
+}
+
+/// The type of a FileItem, together with the properties specific to it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileItemType {
+    /// A file, with the media type of its content and the XOR-URL of the Blob storing it
+    File { media_type: String, link: String },
+    /// A directory
+    Dir,
+    /// A symlink, with the (absolute or relative) path it points to
+    Symlink {
+        target: String,
+        target_type: SymlinkTargetType,
+    },
+}
+
+/// Each FileItem contains file metadata and, for files, the link to the file's Blob XOR-URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileItem {
+    pub file_type: FileItemType,
+    /// Size of the file content in bytes, 0 for directories and symlinks
+    pub size: u64,
+    /// When the FileItem was created in the FilesContainer
+    pub created: DateTime<Utc>,
+    /// When the FileItem was last modified in the FilesContainer
+    pub modified: DateTime<Utc>,
+    /// Creation time of the original file on the local filesystem, if available
+    pub original_created: Option<DateTime<Utc>>,
+    /// Modification time of the original file on the local filesystem, if available
+    pub original_modified: Option<DateTime<Utc>>,
+    pub readonly: Option<bool>,
+    /// Unix permission mode bits, not available on all platforms
+    pub mode_bits: Option<u32>,
+    /// Checksum of the file content, if it was recorded when uploaded, as the
+    /// name of the algorithm followed by the hex-encoded digest
+    pub checksum: Option<String>,
+    /// Any additional attributes not part of the schema
+    pub extensions: BTreeMap<String, String>,
+}
+
+impl FileItem {
+    /// Returns the media type of the FileItem, which is the FreeDesktop
+    /// standard one in the case of directories and symlinks
+    pub fn media_type(&self) -> &str {
+        match &self.file_type {
+            FileItemType::File { media_type, .. } => media_type,
+            FileItemType::Dir => MIMETYPE_FILESYSTEM_DIR,
+            FileItemType::Symlink { .. } => MIMETYPE_FILESYSTEM_SYMLINK,
+        }
+    }
+
+    /// Returns the link to the file's content, directories and symlinks don't have one
+    pub fn link(&self) -> Option<&str> {
+        match &self.file_type {
+            FileItemType::File { link, .. } => Some(link),
+            _ => None,
+        }
+    }
+
+    /// Returns the target path if the FileItem is a symlink
+    pub fn symlink_target(&self) -> Option<&str> {
+        match &self.file_type {
+            FileItemType::Symlink { target, .. } => Some(target),
+            _ => None,
+        }
+    }
+
+    pub fn is_file(&self) -> bool {
+        matches!(self.file_type, FileItemType::File { .. })
+    }
+
+    pub fn is_dir(&self) -> bool {
+        matches!(self.file_type, FileItemType::Dir)
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        matches!(self.file_type, FileItemType::Symlink { .. })
+    }
+
+    // Instantiates a FileItem from its serialised attributes, accepting both the current
+    // schema and the legacy one where every attribute was stored as a string.
+    fn from_attrs(mut attrs: BTreeMap<String, Value>) -> Result<Self> {
+        let media_type = take_required(&mut attrs, PREDICATE_TYPE, take_string)?;
+        let file_type = match media_type.as_str() {
+            MIMETYPE_FILESYSTEM_DIR => FileItemType::Dir,
+            MIMETYPE_FILESYSTEM_SYMLINK => {
+                let target = take_required(&mut attrs, SYMLINK_TARGET_KEY, take_string)?;
+                let target_type = match take_string(&mut attrs, SYMLINK_TARGET_TYPE_KEY)? {
+                    Some(t) if t == "file" => SymlinkTargetType::File,
+                    Some(t) if t == "dir" => SymlinkTargetType::Dir,
+                    _ => SymlinkTargetType::Unknown,
+                };
+                FileItemType::Symlink {
+                    target,
+                    target_type,
+                }
+            }
+            _ => {
+                let link = take_required(&mut attrs, PREDICATE_LINK, take_string)?;
+                FileItemType::File { media_type, link }
+            }
+        };
+
+        let size = take_required(&mut attrs, PREDICATE_SIZE, take_u64)?;
+        let created = take_required(&mut attrs, PREDICATE_CREATED, take_timestamp)?;
+        let modified = take_required(&mut attrs, PREDICATE_MODIFIED, take_timestamp)?;
+        let original_created = take_timestamp(&mut attrs, PREDICATE_ORIGINAL_CREATED)?;
+        let original_modified = take_timestamp(&mut attrs, PREDICATE_ORIGINAL_MODIFIED)?;
+        let readonly = take_bool(&mut attrs, PREDICATE_READONLY)?;
+        let mode_bits = match take_u64(&mut attrs, PREDICATE_MODE_BITS)? {
+            Some(bits) => Some(
+                u32::try_from(bits)
+                    .map_err(|_| invalid_attr(PREDICATE_MODE_BITS, &Value::from(bits)))?,
+            ),
+            None => None,
+        };
+        let checksum = take_string(&mut attrs, PREDICATE_CHECKSUM)?;
+
+        // Anything else is kept as an extension attribute
+        let mut extensions = BTreeMap::new();
+        if let Some(value) = attrs.remove(EXTENSIONS_KEY) {
+            match value {
+                Value::Object(map) => {
+                    for (key, value) in map {
+                        match value {
+                            Value::String(v) => extensions.insert(key, v),
+                            other => return Err(invalid_attr(&key, &other)),
+                        };
+                    }
+                }
+                Value::Null => {}
+                other => return Err(invalid_attr(EXTENSIONS_KEY, &other)),
+            }
+        }
+        for (key, value) in attrs {
+            match value {
+                Value::String(v) => extensions.insert(key, v),
+                Value::Null => None,
+                other => return Err(invalid_attr(&key, &other)),
+            };
+        }
+
+        Ok(Self {
+            file_type,
+            size,
+            created,
+            modified,
+            original_created,
+            original_modified,
+            readonly,
+            mode_bits,
+            checksum,
+            extensions,
+        })
+    }
+}
+
+// Representation of a FileItem as stored in the current FilesMap schema version
+#[derive(Serialize)]
+struct SerialisedFileItem<'a> {
+    #[serde(rename = "type")]
+    media_type: &'a str,
+    size: u64,
+    created: String,
+    modified: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    link: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    symlink_target: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    symlink_target_type: Option<&'a str>,
+    #[serde(rename = "o_created", skip_serializing_if = "Option::is_none")]
+    original_created: Option<String>,
+    #[serde(rename = "o_modified", skip_serializing_if = "Option::is_none")]
+    original_modified: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    readonly: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mode_bits: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checksum: Option<&'a str>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    extensions: &'a BTreeMap<String, String>,
+}
+
+impl Serialize for FileItem {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let (symlink_target, symlink_target_type) = match &self.file_type {
+            FileItemType::Symlink {
+                target,
+                target_type,
+            } => (Some(target.as_str()), Some(target_type.as_str())),
+            _ => (None, None),
+        };
+
+        SerialisedFileItem {
+            media_type: self.media_type(),
+            size: self.size,
+            created: datetime_to_rfc3339(&self.created),
+            modified: datetime_to_rfc3339(&self.modified),
+            link: self.link(),
+            symlink_target,
+            symlink_target_type,
+            original_created: self.original_created.as_ref().map(datetime_to_rfc3339),
+            original_modified: self.original_modified.as_ref().map(datetime_to_rfc3339),
+            readonly: self.readonly,
+            mode_bits: self.mode_bits,
+            checksum: self.checksum.as_deref(),
+            extensions: &self.extensions,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for FileItem {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let attrs = BTreeMap::<String, Value>::deserialize(deserializer)?;
+        Self::from_attrs(attrs).map_err(de::Error::custom)
+    }
+}
+
+impl ToRdf for FilesMap {
+    fn to_rdf(&self, format: RdfFormat) -> Result<String> {
+        match format {
+            RdfFormat::JsonLd => {
+                let jsonld = files_map_to_jsonld(self, &BTreeMap::new())?;
+                serde_json::to_string_pretty(&jsonld).map_err(|err| {
+                    Error::Serialisation(format!(
+                        "Couldn't serialise the FilesMap as JSON-LD: {:?}",
+                        err
+                    ))
+                })
+            }
+            RdfFormat::Turtle => Ok(files_map_to_turtle(self).to_document(SAFE_FILES_VOCAB)),
+        }
+    }
+}
+
+// Serialise a FilesMap with the encoding provided, using the current schema version
+pub(crate) fn serialise_files_map(
+    files_map: &FilesMap,
+    encoding: FilesMapEncoding,
+) -> Result<Vec<u8>> {
+    serialise_files_and_shards(files_map, &BTreeMap::new(), encoding)
+}
+
+// Serialise a shard of a sharded FilesMap with the encoding provided
+pub(crate) fn serialise_files_map_shard(
+    shard: &FilesMapShard,
+    encoding: FilesMapEncoding,
+) -> Result<Vec<u8>> {
+    serialise_files_and_shards(&shard.files, &shard.shards, encoding)
+}
+
+fn serialise_files_and_shards(
+    files_map: &FilesMap,
+    shards: &BTreeMap<String, String>,
+    encoding: FilesMapEncoding,
+) -> Result<Vec<u8>> {
+    match encoding {
+        FilesMapEncoding::JsonLd => encode_value(&files_map_to_jsonld(files_map, shards)?),
+        FilesMapEncoding::MsgPackZstd => encode_msgpack_zstd(&VersionedFilesMap {
+            schema_version: FILES_MAP_SCHEMA_VERSION,
+            files: files_map,
+            shards,
+        }),
+    }
+}
+
+// Deserialise a FilesMap stored with the encoding provided. When JSON is used, it
+// auto-detects if it's a JSON-LD document, or it uses the legacy format which
+// is just the map of paths to FileItems with string attributes.
+pub(crate) fn deserialise_files_map(
+    serialised: &[u8],
+    encoding: FilesMapEncoding,
+) -> Result<FilesMap> {
+    deserialise_files_map_shard(serialised, encoding).map(|shard| shard.files)
+}
+
+// Deserialise a shard of a sharded FilesMap stored with the encoding provided.
+// A FilesMap stored in a single Blob is a shard without links to other shards.
+pub(crate) fn deserialise_files_map_shard(
+    serialised: &[u8],
+    encoding: FilesMapEncoding,
+) -> Result<FilesMapShard> {
+    match decode_value(serialised, encoding)? {
+        Value::Object(mut map) if map.contains_key(SCHEMA_VERSION_KEY) => {
+            check_schema_version(&mut map)?;
+            let files = take_file_items(&mut map, FILES_KEY)?;
+            let shards = match map.remove(SHARDS_KEY) {
+                Some(links) => serde_json::from_value(links).map_err(deserialisation_error)?,
+                None => BTreeMap::new(),
+            };
+
+            Ok(FilesMapShard { files, shards })
+        }
+        Value::Null => Ok(FilesMapShard::default()),
+        legacy => Ok(FilesMapShard {
+            files: serde_json::from_value(legacy).map_err(deserialisation_error)?,
+            shards: BTreeMap::new(),
+        }),
+    }
+}
+
+// The FilesMap together with the version of the schema used to serialise it,
+// used by the encodings which are not JSON-LD
+#[derive(Serialize)]
+struct VersionedFilesMap<'a> {
+    schema_version: u64,
+    files: &'a FilesMap,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    shards: &'a BTreeMap<String, String>,
+}
+
+// Generate the JSON-LD representation of a FilesMap. FileItems are kept in an
+// index map keyed by their path, which is also set as a property of each of them.
+// The links to other shards, if it's a shard of a sharded FilesMap, are kept in
+// an index map keyed by the path of the directory they contain.
+fn files_map_to_jsonld(files_map: &FilesMap, shards: &BTreeMap<String, String>) -> Result<Value> {
+    let mut jsonld = json!({
+        JSONLD_CONTEXT: files_jsonld_context(),
+        JSONLD_TYPE: "FilesMap",
+        SCHEMA_VERSION_KEY: FILES_MAP_SCHEMA_VERSION,
+        FILES_KEY: file_items_to_jsonld(files_map)?,
+    });
+    if !shards.is_empty() {
+        jsonld[SHARDS_KEY] = json!(shards);
+    }
+
+    Ok(jsonld)
+}
+
+// Serialise a FilesMap delta with the encoding provided, using the current schema version
+pub(crate) fn serialise_files_map_delta(
+    delta: &FilesMapDelta,
+    encoding: FilesMapEncoding,
+) -> Result<Vec<u8>> {
+    match encoding {
+        FilesMapEncoding::JsonLd => encode_value(&json!({
+            JSONLD_CONTEXT: files_jsonld_context(),
+            JSONLD_TYPE: "FilesMapDelta",
+            SCHEMA_VERSION_KEY: FILES_MAP_SCHEMA_VERSION,
+            BASE_VERSION_KEY: delta.base_version,
+            ADDED_KEY: file_items_to_jsonld(&delta.added)?,
+            MODIFIED_KEY: file_items_to_jsonld(&delta.modified)?,
+            REMOVED_KEY: delta.removed,
+        })),
+        FilesMapEncoding::MsgPackZstd => encode_msgpack_zstd(&VersionedFilesMapDelta {
+            schema_version: FILES_MAP_SCHEMA_VERSION,
+            base_version: delta.base_version,
+            added: &delta.added,
+            modified: &delta.modified,
+            removed: &delta.removed,
+        }),
+    }
+}
+
+// Deserialise a FilesMap delta stored with the encoding provided
+pub(crate) fn deserialise_files_map_delta(
+    serialised: &[u8],
+    encoding: FilesMapEncoding,
+) -> Result<FilesMapDelta> {
+    let mut map = match decode_value(serialised, encoding)? {
+        Value::Object(map) => map,
+        other => {
+            return Err(Error::ContentError(format!(
+                "Invalid FilesMap delta found in the FilesContainer: {}",
+                other
+            )))
+        }
+    };
+    check_schema_version(&mut map)?;
+    let base_version = map
+        .remove(BASE_VERSION_KEY)
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| {
+            Error::ContentError(
+                "Invalid base version found in the FilesMap delta stored in the FilesContainer"
+                    .to_string(),
+            )
+        })?;
+    let added = take_file_items(&mut map, ADDED_KEY)?;
+    let modified = take_file_items(&mut map, MODIFIED_KEY)?;
+    let removed = match map.remove(REMOVED_KEY) {
+        Some(paths) => serde_json::from_value(paths).map_err(deserialisation_error)?,
+        None => BTreeSet::new(),
+    };
+
+    Ok(FilesMapDelta {
+        base_version,
+        added,
+        modified,
+        removed,
+    })
+}
+
+// The FilesMap delta together with the version of the schema used to serialise it,
+// used by the encodings which are not JSON-LD
+#[derive(Serialize)]
+struct VersionedFilesMapDelta<'a> {
+    schema_version: u64,
+    base_version: u64,
+    added: &'a FilesMap,
+    modified: &'a FilesMap,
+    removed: &'a BTreeSet<String>,
+}
+
+// JSON-LD context used by the documents describing FilesMaps and their deltas
+fn files_jsonld_context() -> Value {
+    json!({
+        "@vocab": SAFE_FILES_VOCAB,
+        "xsd": XSD_NAMESPACE,
+        FILES_KEY: { "@container": "@index" },
+        SHARDS_KEY: { "@container": "@index", "@type": "@id" },
+        ADDED_KEY: { "@container": "@index" },
+        MODIFIED_KEY: { "@container": "@index" },
+        REMOVED_KEY: { "@container": "@set" },
+        PREDICATE_LINK: { "@type": "@id" },
+        PREDICATE_CREATED: { "@type": "xsd:dateTime" },
+        PREDICATE_MODIFIED: { "@type": "xsd:dateTime" },
+        PREDICATE_ORIGINAL_CREATED: { "@type": "xsd:dateTime" },
+        PREDICATE_ORIGINAL_MODIFIED: { "@type": "xsd:dateTime" },
+    })
+}
+
+// Generate the JSON-LD index map of the FileItems of a FilesMap, keyed by their path
+fn file_items_to_jsonld(files_map: &FilesMap) -> Result<Value> {
+    let mut files = serde_json::Map::new();
+    for (path, file_item) in files_map {
+        let mut item = serde_json::to_value(file_item).map_err(|err| {
+            Error::Serialisation(format!(
+                "Couldn't serialise FileItem at \"{}\": {:?}",
+                path, err
+            ))
+        })?;
+        if let Value::Object(attrs) = &mut item {
+            attrs.insert(JSONLD_TYPE.to_string(), json!("FileItem"));
+            attrs.insert(PATH_KEY.to_string(), json!(path));
+        }
+        files.insert(path.to_string(), item);
+    }
 
-// A trait to get an key attr and return an API Result
-pub trait GetAttr {
-    fn getattr(&self, key: &str) -> Result<&str>;
+    Ok(Value::Object(files))
 }
 
-impl GetAttr for FileItem {
-    // Makes it more readable to conditionally get an attribute from a FileItem
-    // because we can call it in API funcs like fileitem.getattr("key")?;
-    fn getattr(&self, key: &str) -> Result<&str> {
-        match self.get(key) {
-            Some(v) => Ok(v),
-            None => Err(Error::EntryNotFound(format!("key not found: {}", key))),
+// Take the map of FileItems found with the key provided out of a versioned document,
+// dropping the JSON-LD specific properties, they are not part of the FileItems
+fn take_file_items(map: &mut serde_json::Map<String, Value>, key: &str) -> Result<FilesMap> {
+    let mut files = match map.remove(key) {
+        None | Some(Value::Null) => return Ok(FilesMap::default()),
+        Some(files) => files,
+    };
+    if map.contains_key(JSONLD_CONTEXT) {
+        if let Value::Object(items) = &mut files {
+            for item in items.values_mut() {
+                if let Value::Object(attrs) = item {
+                    attrs.remove(JSONLD_TYPE);
+                    attrs.remove(PATH_KEY);
+                }
+            }
         }
     }
+
+    serde_json::from_value(files).map_err(deserialisation_error)
+}
+
+// Take the schema version out of a versioned document, making sure it's supported
+fn check_schema_version(map: &mut serde_json::Map<String, Value>) -> Result<()> {
+    let schema_version = map
+        .remove(SCHEMA_VERSION_KEY)
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| {
+            Error::ContentError(
+                "Invalid schema version found in the FilesMap stored in the FilesContainer"
+                    .to_string(),
+            )
+        })?;
+    if schema_version > FILES_MAP_SCHEMA_VERSION {
+        return Err(Error::ContentError(format!(
+            "The FilesMap stored in the FilesContainer uses schema version {}, which is not supported (latest supported is {})",
+            schema_version, FILES_MAP_SCHEMA_VERSION
+        )));
+    }
+
+    Ok(())
+}
+
+// Encode a JSON document to be stored. The same document always produces the same bytes,
+// and thus the same address, since serde_json's objects are sorted maps as long as its
+// `preserve_order` feature is not enabled, so their keys are always written sorted.
+fn encode_value(value: &Value) -> Result<Vec<u8>> {
+    serde_json::to_vec(value).map_err(serialisation_error)
+}
+
+// Encode a document with MessagePack, compressing it with zstd
+fn encode_msgpack_zstd<T: Serialize>(document: &T) -> Result<Vec<u8>> {
+    let packed = rmp_serde::to_vec_named(document).map_err(serialisation_error)?;
+    zstd::encode_all(packed.as_slice(), ZSTD_COMPRESSION_LEVEL).map_err(serialisation_error)
+}
+
+// Decode a stored document, with the encoding provided, into a JSON value
+fn decode_value(serialised: &[u8], encoding: FilesMapEncoding) -> Result<Value> {
+    match encoding {
+        FilesMapEncoding::JsonLd => {
+            serde_json::from_slice(serialised).map_err(deserialisation_error)
+        }
+        FilesMapEncoding::MsgPackZstd => {
+            let packed = zstd::decode_all(serialised).map_err(deserialisation_error)?;
+            rmp_serde::from_slice(&packed).map_err(deserialisation_error)
+        }
+    }
+}
+
+fn serialisation_error<E: fmt::Debug>(err: E) -> Error {
+    Error::Serialisation(format!(
+        "Couldn't serialise the FilesMap generated: {:?}",
+        err
+    ))
+}
+
+fn deserialisation_error<E: fmt::Debug>(err: E) -> Error {
+    Error::ContentError(format!(
+        "Couldn't deserialise the FilesMap stored in the FilesContainer: {:?}",
+        err
+    ))
+}
+
+// Generate the RDF graph of a FilesMap to be written as a Turtle document
+fn files_map_to_turtle(files_map: &FilesMap) -> TurtleNode {
+    let mut root = TurtleNode::new("FilesMap");
+    root.add(
+        SCHEMA_VERSION_KEY,
+        TurtleObject::Integer(FILES_MAP_SCHEMA_VERSION),
+    );
+
+    for (path, file_item) in files_map {
+        let mut node = TurtleNode::new("FileItem");
+        node.add(PATH_KEY, TurtleObject::Literal(path.to_string()));
+        node.add(
+            PREDICATE_TYPE,
+            TurtleObject::Literal(file_item.media_type().to_string()),
+        );
+        node.add(PREDICATE_SIZE, TurtleObject::Integer(file_item.size));
+        let timestamps = [
+            (PREDICATE_CREATED, Some(&file_item.created)),
+            (PREDICATE_MODIFIED, Some(&file_item.modified)),
+            (
+                PREDICATE_ORIGINAL_CREATED,
+                file_item.original_created.as_ref(),
+            ),
+            (
+                PREDICATE_ORIGINAL_MODIFIED,
+                file_item.original_modified.as_ref(),
+            ),
+        ];
+        for (term, timestamp) in timestamps.iter() {
+            if let Some(timestamp) = timestamp {
+                node.add(
+                    term,
+                    TurtleObject::TypedLiteral(datetime_to_rfc3339(timestamp), "xsd:dateTime"),
+                );
+            }
+        }
+        match &file_item.file_type {
+            FileItemType::File { link, .. } => {
+                node.add(PREDICATE_LINK, TurtleObject::Iri(link.to_string()))
+            }
+            FileItemType::Symlink {
+                target,
+                target_type,
+            } => {
+                node.add(
+                    SYMLINK_TARGET_KEY,
+                    TurtleObject::Literal(target.to_string()),
+                );
+                node.add(
+                    SYMLINK_TARGET_TYPE_KEY,
+                    TurtleObject::Literal(target_type.to_string()),
+                );
+            }
+            FileItemType::Dir => {}
+        }
+        if let Some(readonly) = file_item.readonly {
+            node.add(PREDICATE_READONLY, TurtleObject::Boolean(readonly));
+        }
+        if let Some(mode_bits) = file_item.mode_bits {
+            node.add(
+                PREDICATE_MODE_BITS,
+                TurtleObject::Integer(u64::from(mode_bits)),
+            );
+        }
+        if let Some(checksum) = &file_item.checksum {
+            node.add(
+                PREDICATE_CHECKSUM,
+                TurtleObject::Literal(checksum.to_string()),
+            );
+        }
+        if !file_item.extensions.is_empty() {
+            let mut extensions = TurtleNode::new("Extensions");
+            for (name, value) in &file_item.extensions {
+                extensions.add_with_iri(
+                    SAFE_FILES_VOCAB,
+                    name,
+                    TurtleObject::Literal(value.to_string()),
+                );
+            }
+            node.add(EXTENSIONS_KEY, TurtleObject::Node(extensions));
+        }
+
+        root.add(FILES_KEY, TurtleObject::Node(node));
+    }
+
+    root
+}
+
+// Helpers to take a FileItem attribute out of the serialised attributes,
+// accepting both the typed values and the legacy string values.
+fn take_required<T>(
+    attrs: &mut BTreeMap<String, Value>,
+    key: &str,
+    take: fn(&mut BTreeMap<String, Value>, &str) -> Result<Option<T>>,
+) -> Result<T> {
+    take(attrs, key)?.ok_or_else(|| {
+        Error::ContentError(format!(
+            "FileItem is corrupt. It is missing a \"{}\" property",
+            key
+        ))
+    })
+}
+
+fn take_string(attrs: &mut BTreeMap<String, Value>, key: &str) -> Result<Option<String>> {
+    match attrs.remove(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(v)) => Ok(Some(v)),
+        Some(other) => Err(invalid_attr(key, &other)),
+    }
+}
+
+fn take_u64(attrs: &mut BTreeMap<String, Value>, key: &str) -> Result<Option<u64>> {
+    match attrs.remove(key) {
+        None | Some(Value::Null) => Ok(None),
+        // the legacy format used an empty string for unknown sizes
+        Some(Value::String(v)) if v.is_empty() => Ok(Some(0)),
+        Some(Value::String(v)) => v
+            .parse()
+            .map(Some)
+            .map_err(|_| invalid_attr(key, &Value::String(v))),
+        Some(Value::Number(n)) => n
+            .as_u64()
+            .map(Some)
+            .ok_or_else(|| invalid_attr(key, &Value::Number(n))),
+        Some(other) => Err(invalid_attr(key, &other)),
+    }
+}
+
+fn take_bool(attrs: &mut BTreeMap<String, Value>, key: &str) -> Result<Option<bool>> {
+    match attrs.remove(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Bool(v)) => Ok(Some(v)),
+        Some(Value::String(v)) => v
+            .parse()
+            .map(Some)
+            .map_err(|_| invalid_attr(key, &Value::String(v))),
+        Some(other) => Err(invalid_attr(key, &other)),
+    }
+}
+
+fn take_timestamp(attrs: &mut BTreeMap<String, Value>, key: &str) -> Result<Option<DateTime<Utc>>> {
+    match take_string(attrs, key)? {
+        Some(v) => rfc3339_to_datetime(&v)
+            .map(Some)
+            .map_err(|_| invalid_attr(key, &Value::String(v))),
+        None => Ok(None),
+    }
+}
+
+fn invalid_attr(key: &str, value: &Value) -> Error {
+    Error::ContentError(format!(
+        "FileItem is corrupt. Invalid value for its \"{}\" property: {}",
+        key, value
+    ))
 }
 
 // Helper function to add or update a FileItem in a FilesMap
@@ -48,12 +934,13 @@ pub(crate) async fn add_or_update_file_item(
     file_meta: &FileMeta,
     file_link: Option<&str>,
     name_exists: bool,
+    private: bool,
     dry_run: bool,
     files_map: &mut FilesMap,
     processed_files: &mut ProcessedFiles,
 ) -> bool {
     // We need to add a new FileItem, let's generate the FileItem first
-    match gen_new_file_item(safe, file_path, file_meta, file_link, dry_run).await {
+    match gen_new_file_item(safe, file_path, file_meta, file_link, private, dry_run).await {
         Ok(new_file_item) => {
             let content_added_sign = if name_exists {
                 CONTENT_UPDATED_SIGN.to_string()
@@ -71,10 +958,7 @@ pub(crate) async fn add_or_update_file_item(
                     content_added_sign,
                     // note: files have link property,
                     //       dirs and symlinks do not
-                    new_file_item
-                        .get(PREDICATE_LINK)
-                        .unwrap_or(&String::default())
-                        .to_string(),
+                    new_file_item.link().unwrap_or_default().to_string(),
                 ),
             );
 
@@ -99,30 +983,40 @@ async fn gen_new_file_item(
     file_path: &Path,
     file_meta: &FileMeta,
     link: Option<&str>, // must be symlink target or None if FileMeta::is_symlink() is true.
+    private: bool,
     dry_run: bool,
 ) -> Result<FileItem> {
-    let mut file_item = file_meta.to_file_item();
-    if file_meta.is_file() {
+    let mut checksum = None;
+    let file_type = if file_meta.is_file() {
+        // Links to content already on the network have no content to compute a checksum from
         let xorurl = match link {
-            None => upload_file_to_net(safe, file_path, dry_run).await?,
+            None => {
+                let (xorurl, uploaded_checksum) =
+                    upload_file_to_net(safe, file_path, private, dry_run).await?;
+                checksum = uploaded_checksum;
+                xorurl
+            }
             Some(link) => link.to_string(),
         };
-        file_item.insert(PREDICATE_LINK.to_string(), xorurl);
+        FileItemType::File {
+            media_type: file_meta.file_type.clone(),
+            link: xorurl,
+        }
     } else if file_meta.is_symlink() {
         // get metadata, with any symlinks resolved.
         let result = fs::metadata(&file_path);
-        let symlink_target_type = match result {
+        let target_type = match result {
             Ok(meta) => {
                 if meta.is_dir() {
-                    "dir"
+                    SymlinkTargetType::Dir
                 } else {
-                    "file"
+                    SymlinkTargetType::File
                 }
             }
-            Err(_) => "unknown", // this occurs for a broken link.  on windows, this would be fixed by: https://github.com/rust-lang/rust/pull/47956
-                                 // on unix, there is no way to know if broken link points to file or dir, though we could guess, based on if it has an extension or not.
+            Err(_) => SymlinkTargetType::Unknown, // this occurs for a broken link.  on windows, this would be fixed by: https://github.com/rust-lang/rust/pull/47956
+                                                  // on unix, there is no way to know if broken link points to file or dir, though we could guess, based on if it has an extension or not.
         };
-        let target_path = match link {
+        let target = match link {
             Some(target) => target.to_string(),
             None => {
                 let target_path = fs::read_link(&file_path).map_err(|e| {
@@ -135,14 +1029,16 @@ async fn gen_new_file_item(
                 normalise_path_separator(&target_path.display().to_string())
             }
         };
-        file_item.insert("symlink_target".to_string(), target_path);
-        // This is a hint for windows-platform clients to be able to call
-        //   symlink_dir() or symlink_file().  on unix, there's no need.
-        file_item.insert(
-            "symlink_target_type".to_string(),
-            symlink_target_type.to_string(),
-        );
-    }
+        FileItemType::Symlink {
+            target,
+            target_type,
+        }
+    } else {
+        FileItemType::Dir
+    };
+
+    let mut file_item = file_meta.to_file_item(file_type);
+    file_item.checksum = checksum;
 
     Ok(file_item)
 }
@@ -0,0 +1,336 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{normalise_files_map_path, FileItem, FileItemType, FilesMap};
+use crate::{
+    app::{fetch::gen_filtered_filesmap, helpers::systemtime_to_datetime_secs},
+    Error, Result, Safe,
+};
+use chrono::{DateTime, Utc};
+use log::debug;
+use std::{cmp::Ordering, collections::BTreeMap, time::UNIX_EPOCH};
+
+/// Attribute the entries of a FilesContainer listing are sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LsSortBy {
+    Name,
+    /// Size of files, or the aggregated size of directories
+    Size,
+    Modified,
+}
+
+impl Default for LsSortBy {
+    fn default() -> Self {
+        Self::Name
+    }
+}
+
+/// Options for listing a directory of a FilesContainer
+#[derive(Debug, Clone, Default)]
+pub struct LsOptions {
+    /// List the content of the subdirectories too, at any depth
+    pub recursive: bool,
+    pub sort_by: LsSortBy,
+    /// Sort the entries in descending order
+    pub reverse: bool,
+    /// Maximum number of entries returned, which cannot be zero,
+    /// all of them are returned if not set
+    pub limit: Option<usize>,
+    /// Cursor returned with the previous page of the listing, to get the next one
+    pub cursor: Option<String>,
+}
+
+/// Entry of a directory listed from a FilesContainer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LsEntry {
+    /// Path of the entry relative to the directory listed
+    pub path: String,
+    pub file_item: FileItem,
+    /// Size of the file, or total size of the files a directory contains at any depth
+    pub size: u64,
+    /// 1 for files, or total number of files a directory contains at any depth
+    pub files_count: u64,
+}
+
+/// Page of the entries of a directory listed from a FilesContainer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LsPage {
+    /// Version of the FilesContainer listed
+    pub version: u64,
+    pub entries: Vec<LsEntry>,
+    /// Total size of the files the directory listed contains at any depth
+    pub size: u64,
+    /// Total number of files the directory listed contains at any depth
+    pub files_count: u64,
+    /// Cursor to get the next page of the listing, if there are more entries
+    pub next_cursor: Option<String>,
+}
+
+impl Safe {
+    /// # List a directory of a FilesContainer.
+    ///
+    /// The directory is the one at the path of the URL, after resolving the symlinks on it.
+    /// Directories are listed with the total size and number of the files they contain, and
+    /// all the pages of a listing are taken from the same version of the FilesContainer.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::{Safe, files::{CreateOptions, LsOptions, LsSortBy}};
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, &CreateOptions::default()).await.unwrap();
+    ///     let mut options = LsOptions { sort_by: LsSortBy::Size, limit: Some(10), ..LsOptions::default() };
+    ///     loop {
+    ///         let page = safe.files_container_ls(&xorurl, &options).await.unwrap();
+    ///         for entry in page.entries {
+    ///             println!("{}: {} bytes in {} file(s)", entry.path, entry.size, entry.files_count);
+    ///         }
+    ///         match page.next_cursor {
+    ///             Some(cursor) => options.cursor = Some(cursor),
+    ///             None => break,
+    ///         }
+    ///     }
+    /// # });
+    /// ```
+    pub async fn files_container_ls(&mut self, url: &str, options: &LsOptions) -> Result<LsPage> {
+        // A page without entries would never get to the end of the listing
+        if options.limit == Some(0) {
+            return Err(Error::InvalidInput(
+                "The limit of entries listed cannot be zero".to_string(),
+            ));
+        }
+
+        let (mut safe_url, _) = self.parse_and_resolve_url(url).await?;
+        let path = normalise_files_map_path(&safe_url.path_decoded()?);
+
+        let offset = match &options.cursor {
+            Some(cursor) => {
+                let (version, offset) = parse_cursor(cursor)?;
+                safe_url.set_content_version(Some(version));
+                offset
+            }
+            None => 0,
+        };
+
+        // Only the shards needed are fetched if the FilesMap is sharded
+        let (version, files_map, realpath) = if path == "/" {
+            let (version, files_map) = self.fetch_files_container(&safe_url).await?;
+            (version, files_map, path)
+        } else {
+            self.fetch_files_container_path(&safe_url, &path).await?
+        };
+        debug!(
+            "Listing '{}' from version {} of FilesContainer at \"{}\"",
+            realpath, version, safe_url
+        );
+
+        let files_map = match files_map.get(&realpath) {
+            Some(file_item) if !file_item.is_dir() => {
+                let name = realpath.rsplit('/').next().unwrap_or_default();
+                let mut files_map = FilesMap::default();
+                files_map.insert(name.to_string(), file_item.clone());
+                files_map
+            }
+            // An existing directory, or the root, may be empty
+            Some(_) => gen_filtered_filesmap(&realpath, &files_map, url).unwrap_or_default(),
+            None if realpath == "/" => {
+                gen_filtered_filesmap(&realpath, &files_map, url).unwrap_or_default()
+            }
+            None => gen_filtered_filesmap(&realpath, &files_map, url)?,
+        };
+
+        let (size, files_count) = files_map
+            .values()
+            .filter(|file_item| file_item.is_file())
+            .fold((0, 0), |(size, count), file_item| {
+                (size + file_item.size, count + 1)
+            });
+
+        let mut entries = ls_entries(&files_map, options.recursive);
+        entries.sort_by(|a, b| {
+            let ordering = match options.sort_by {
+                LsSortBy::Name => Ordering::Equal,
+                LsSortBy::Size => a.size.cmp(&b.size),
+                LsSortBy::Modified => a.file_item.modified.cmp(&b.file_item.modified),
+            };
+            ordering.then_with(|| a.path.cmp(&b.path))
+        });
+        if options.reverse {
+            entries.reverse();
+        }
+
+        let mut entries: Vec<LsEntry> = entries.into_iter().skip(offset).collect();
+        let next_cursor = match options.limit {
+            Some(limit) if entries.len() > limit => {
+                entries.truncate(limit);
+                Some(format!("{}:{}", version, offset + limit))
+            }
+            _ => None,
+        };
+
+        Ok(LsPage {
+            version,
+            entries,
+            size,
+            files_count,
+            next_cursor,
+        })
+    }
+}
+
+// Parse a listing cursor into the version of the FilesContainer
+// listed and the number of entries already returned
+fn parse_cursor(cursor: &str) -> Result<(u64, usize)> {
+    let invalid = || Error::InvalidInput(format!("Invalid listing cursor: '{}'", cursor));
+    let mut parts = cursor.splitn(2, ':');
+    let version = parts
+        .next()
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(invalid)?;
+    let offset = parts
+        .next()
+        .and_then(|o| o.parse().ok())
+        .ok_or_else(invalid)?;
+    Ok((version, offset))
+}
+
+// Generate the entries of a FilesMap filtered by the directory listed, i.e. with paths
+// relative to it, aggregating the size and number of files of each directory. Directories
+// without a FileItem, which are found in FilesContainers created before empty directories
+// were stored, are listed with the latest modification time of their content.
+fn ls_entries(files_map: &FilesMap, recursive: bool) -> Vec<LsEntry> {
+    let mut dirs = BTreeMap::<String, (u64, u64, Option<DateTime<Utc>>)>::new();
+    for (path, file_item) in files_map.iter().filter(|(path, _)| !path.is_empty()) {
+        let parts: Vec<&str> = path.split('/').collect();
+        for depth in 1..parts.len() {
+            let (size, count, modified) = dirs.entry(parts[..depth].join("/")).or_default();
+            if file_item.is_file() {
+                *size += file_item.size;
+                *count += 1;
+            }
+            if modified.map_or(true, |modified| modified < file_item.modified) {
+                *modified = Some(file_item.modified);
+            }
+        }
+    }
+
+    let mut entries = BTreeMap::new();
+    for (path, file_item) in files_map.iter() {
+        if path.is_empty() || (!recursive && path.contains('/')) {
+            continue;
+        }
+        let (size, files_count) = match &file_item.file_type {
+            FileItemType::File { .. } => (file_item.size, 1),
+            FileItemType::Dir => dirs
+                .get(path)
+                .map_or((0, 0), |(size, count, _)| (*size, *count)),
+            FileItemType::Symlink { .. } => (0, 0),
+        };
+        let entry = LsEntry {
+            path: path.clone(),
+            file_item: file_item.clone(),
+            size,
+            files_count,
+        };
+        entries.insert(path.clone(), entry);
+    }
+
+    for (path, (size, files_count, modified)) in dirs {
+        if entries.contains_key(&path) || (!recursive && path.contains('/')) {
+            continue;
+        }
+        let modified = modified.unwrap_or_else(|| systemtime_to_datetime_secs(UNIX_EPOCH));
+        let file_item = FileItem {
+            file_type: FileItemType::Dir,
+            size: 0,
+            created: modified,
+            modified,
+            original_created: None,
+            original_modified: None,
+            readonly: None,
+            mode_bits: None,
+            checksum: None,
+            extensions: BTreeMap::new(),
+        };
+        let entry = LsEntry {
+            path: path.clone(),
+            file_item,
+            size,
+            files_count,
+        };
+        entries.insert(path, entry);
+    }
+
+    entries.into_iter().map(|(_, entry)| entry).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{consts::MIMETYPE_FILESYSTEM_DIR, files::FileMeta};
+
+    fn file_item(size: u64) -> FileItem {
+        FileMeta::from_type_and_size("text/plain", size).to_file_item(FileItemType::File {
+            media_type: "text/plain".to_string(),
+            link: "safe://link".to_string(),
+        })
+    }
+
+    #[test]
+    fn test_ls_entries() {
+        let mut files_map = FilesMap::default();
+        files_map.insert("a.txt".to_string(), file_item(5));
+        files_map.insert(
+            "docs".to_string(),
+            FileMeta::from_type_and_size(MIMETYPE_FILESYSTEM_DIR, 0)
+                .to_file_item(FileItemType::Dir),
+        );
+        files_map.insert("docs/b.txt".to_string(), file_item(7));
+        files_map.insert("docs/sub/c.txt".to_string(), file_item(11));
+        // Directory without a FileItem from an old FilesContainer
+        files_map.insert("old/d.txt".to_string(), file_item(13));
+
+        let entries = ls_entries(&files_map, false);
+        let listed: Vec<(&str, u64, u64)> = entries
+            .iter()
+            .map(|entry| (entry.path.as_str(), entry.size, entry.files_count))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![("a.txt", 5, 1), ("docs", 18, 2), ("old", 13, 1)]
+        );
+        assert!(entries[2].file_item.is_dir());
+
+        let entries = ls_entries(&files_map, true);
+        let paths: Vec<&str> = entries.iter().map(|entry| entry.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "a.txt",
+                "docs",
+                "docs/b.txt",
+                "docs/sub",
+                "docs/sub/c.txt",
+                "old",
+                "old/d.txt"
+            ]
+        );
+        assert_eq!(entries[3].size, 11);
+    }
+
+    #[test]
+    fn test_parse_cursor() {
+        assert_eq!(parse_cursor("3:20").ok(), Some((3, 20)));
+        assert!(parse_cursor("3").is_err());
+        assert!(parse_cursor("a:b").is_err());
+    }
+}
@@ -0,0 +1,203 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::SafeUrl;
+use std::{fs::File, io::Read, path::Path};
+
+// Media type of the content which type couldn't be detected
+pub(crate) const RAW_MEDIA_TYPE: &str = "Raw";
+
+// Number of bytes of the content looked at to detect its media type
+const SNIFF_LEN: usize = 512;
+
+// Magic bytes found at a given offset of the content, and the media type they identify
+const MAGIC_BYTES: &[(usize, &[u8], &str)] = &[
+    (0, b"\x89PNG\r\n\x1a\n", "image/png"),
+    (0, b"\xff\xd8\xff", "image/jpeg"),
+    (0, b"GIF87a", "image/gif"),
+    (0, b"GIF89a", "image/gif"),
+    (0, b"II*\x00", "image/tiff"),
+    (0, b"MM\x00*", "image/tiff"),
+    (0, b"\x00\x00\x01\x00", "image/x-icon"),
+    (0, b"%PDF-", "application/pdf"),
+    (0, b"%!PS", "application/postscript"),
+    (0, b"PK\x03\x04", "application/zip"),
+    (0, b"\x1f\x8b", "application/gzip"),
+    (0, b"BZh", "application/x-bzip2"),
+    (0, b"\xfd7zXZ\x00", "application/x-xz"),
+    (0, b"\x28\xb5\x2f\xfd", "application/zstd"),
+    (0, b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    (0, b"Rar!\x1a\x07", "application/vnd.rar"),
+    (257, b"ustar", "application/x-tar"),
+    (0, b"\x00asm", "application/wasm"),
+    (0, b"\x7fELF", "application/x-executable"),
+    (0, b"OggS", "audio/ogg"),
+    (0, b"fLaC", "audio/flac"),
+    (0, b"ID3", "audio/mpeg"),
+    (0, b"\x1a\x45\xdf\xa3", "video/webm"),
+    (4, b"ftypqt", "video/quicktime"),
+    (4, b"ftyp", "video/mp4"),
+    (0, b"wOFF", "font/woff"),
+    (0, b"wOF2", "font/woff2"),
+    (0, b"\x00\x01\x00\x00\x00", "font/ttf"),
+    (0, b"OTTO", "font/otf"),
+    (0, b"SQLite format 3\x00", "application/vnd.sqlite3"),
+];
+
+// Media types of RIFF containers, identified by the format found after the chunk size
+const RIFF_FORMATS: &[(&[u8], &str)] = &[
+    (b"WEBP", "image/webp"),
+    (b"WAVE", "audio/wav"),
+    (b"AVI ", "video/x-msvideo"),
+];
+
+// Leading markup of text documents, matched case-insensitively after any whitespace
+const TEXT_MARKUP: &[(&str, &str)] = &[
+    ("<!doctype html", "text/html"),
+    ("<html", "text/html"),
+    ("<svg", "image/svg+xml"),
+    ("<?xml", "text/xml"),
+    ("%yaml", "text/yaml"),
+];
+
+// Detect the media type of a file from the extension of its path,
+// or from its content if the extension is missing or unknown
+pub(crate) fn detect_media_type(path: &Path, data: &[u8]) -> Option<String> {
+    mime_guess::from_path(path)
+        .first_raw()
+        .or_else(|| sniff_media_type(data))
+        .map(|media_type| media_type.to_string())
+}
+
+// Detect the media type of a local file, reading the beginning
+// of its content only if it cannot be guessed from its path
+pub(crate) fn detect_file_media_type(path: &Path) -> Option<String> {
+    if let Some(media_type) = mime_guess::from_path(path).first_raw() {
+        return Some(media_type.to_string());
+    }
+
+    let mut data = Vec::with_capacity(SNIFF_LEN);
+    let file = File::open(path).ok()?;
+    file.take(SNIFF_LEN as u64).read_to_end(&mut data).ok()?;
+    sniff_media_type(&data).map(|media_type| media_type.to_string())
+}
+
+// The media type to encode in the XOR-URL of the content, which is only possible if it's
+// supported by the XOR-URL encoding. Otherwise it's only kept in the content's FileItem.
+pub(crate) fn xorurl_media_type(media_type: Option<&str>) -> Option<&str> {
+    media_type.filter(|media_type| SafeUrl::is_media_type_supported(media_type))
+}
+
+// Detect the media type of the content from its magic bytes, or as plain text if it's UTF-8
+fn sniff_media_type(data: &[u8]) -> Option<&'static str> {
+    let data = &data[..data.len().min(SNIFF_LEN)];
+    if data.is_empty() {
+        return None;
+    }
+
+    let magic = MAGIC_BYTES.iter().find(|(offset, magic, _)| {
+        data.get(*offset..offset + magic.len())
+            .map_or(false, |bytes| bytes == *magic)
+    });
+    if let Some((_, _, media_type)) = magic {
+        return Some(*media_type);
+    }
+
+    if data.starts_with(b"RIFF") {
+        let format = data.get(8..12)?;
+        return RIFF_FORMATS
+            .iter()
+            .find(|(riff_format, _)| *riff_format == format)
+            .map(|(_, media_type)| *media_type);
+    }
+
+    // The content may have been cut in the middle of a multi-byte char
+    let text = match std::str::from_utf8(data) {
+        Ok(text) => text,
+        Err(err) if err.error_len().is_none() => {
+            std::str::from_utf8(&data[..err.valid_up_to()]).ok()?
+        }
+        Err(_) => return None,
+    };
+    if text.contains('\0') {
+        return None;
+    }
+
+    let lowercase = text.trim_start().to_lowercase();
+    let markup = TEXT_MARKUP
+        .iter()
+        .find(|(markup, _)| lowercase.starts_with(markup))
+        .map(|(_, media_type)| *media_type);
+    if markup.is_some() {
+        return markup;
+    }
+
+    let trimmed = lowercase.trim_end();
+    let is_json = ((trimmed.starts_with('{') && trimmed.ends_with('}'))
+        || (trimmed.starts_with('[') && trimmed.ends_with(']')))
+        && serde_json::from_str::<serde_json::Value>(trimmed).is_ok();
+    if is_json {
+        return Some("application/json");
+    }
+
+    Some("text/plain")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_media_type() {
+        let png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR";
+        assert_eq!(
+            detect_media_type(Path::new("/image"), png),
+            Some("image/png".to_string())
+        );
+        // The extension takes precedence over the content
+        assert_eq!(
+            detect_media_type(Path::new("/image.txt"), png),
+            Some("text/plain".to_string())
+        );
+
+        let webp = b"RIFF\x24\x00\x00\x00WEBPVP8 ";
+        assert_eq!(sniff_media_type(webp), Some("image/webp"));
+        assert_eq!(sniff_media_type(b"RIFF\x24\x00\x00\x00XXXX"), None);
+
+        let mut tar = vec![0; 512];
+        tar[257..262].copy_from_slice(b"ustar");
+        tar[0] = b'a';
+        assert_eq!(sniff_media_type(&tar), Some("application/x-tar"));
+
+        assert_eq!(
+            sniff_media_type(b"  <!DOCTYPE html><html></html>"),
+            Some("text/html")
+        );
+        assert_eq!(
+            sniff_media_type(b"{\"key\": [1, 2]}\n"),
+            Some("application/json")
+        );
+        assert_eq!(sniff_media_type(b"{ not json }"), Some("text/plain"));
+        assert_eq!(sniff_media_type("héllo".as_bytes()), Some("text/plain"));
+        // Text cut in the middle of a multi-byte char
+        assert_eq!(
+            sniff_media_type(&"héllo".as_bytes()[..2]),
+            Some("text/plain")
+        );
+        assert_eq!(sniff_media_type(b"\x01\x02\x00\xff\xfe"), None);
+        assert_eq!(sniff_media_type(b""), None);
+    }
+
+    #[test]
+    fn test_xorurl_media_type() {
+        assert_eq!(xorurl_media_type(Some("text/plain")), Some("text/plain"));
+        assert_eq!(xorurl_media_type(Some("application/x-unknown")), None);
+        assert_eq!(xorurl_media_type(None), None);
+    }
+}
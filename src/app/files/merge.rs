@@ -0,0 +1,216 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{is_same_or_subpath, FilesMap};
+use crate::{Error, Result, Safe, SafeUrl};
+use log::{debug, info};
+use std::collections::BTreeSet;
+
+/// Precondition on the version of a FilesContainer for a change to be committed on it.
+///
+/// The precondition is checked when the change is applied, and checked again after the new
+/// FilesMap is stored, right before the new version is appended. Since the network doesn't
+/// support conditional appends, only a concurrent update landing within that final append
+/// can go undetected. Multi-writer FilesContainers keep concurrent updates as separate
+/// versions which are merged when fetched, thus they never overwrite each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpectedVersion {
+    /// Commit the change on top of the latest version, whatever it is
+    Any,
+    /// Fail with a `VersionConflict` error unless the latest version is the one expected
+    Version(u64),
+    /// Apply the change to the version expected, and if the FilesContainer was updated
+    /// since then, merge it with those updates as long as they didn't change the same
+    /// paths, failing with a `VersionConflict` error otherwise
+    VersionOrMerge(u64),
+}
+
+impl Default for ExpectedVersion {
+    fn default() -> Self {
+        Self::Any
+    }
+}
+
+impl Safe {
+    // Private helper to fetch the version of a FilesContainer a change is to be applied to,
+    // according to the version expected, together with its FilesMap.
+    // The URL is expected to be without a version, i.e. targeting the latest version.
+    pub(super) async fn fetch_files_container_for_update(
+        &self,
+        safe_url: &SafeUrl,
+        expected_version: ExpectedVersion,
+    ) -> Result<(u64, FilesMap)> {
+        let (current_version, current_files_map) = self.fetch_files_container(safe_url).await?;
+        match expected_version {
+            ExpectedVersion::Any => Ok((current_version, current_files_map)),
+            ExpectedVersion::Version(expected) | ExpectedVersion::VersionOrMerge(expected)
+                if expected == current_version =>
+            {
+                Ok((current_version, current_files_map))
+            }
+            ExpectedVersion::VersionOrMerge(expected) if expected < current_version => {
+                info!(
+                    "FilesContainer is at version {}, applying changes to version {} to merge them",
+                    current_version, expected
+                );
+                let mut base_url = safe_url.clone();
+                base_url.set_content_version(Some(expected));
+                self.fetch_files_container(&base_url).await
+            }
+            ExpectedVersion::Version(expected) | ExpectedVersion::VersionOrMerge(expected) => {
+                Err(Error::VersionConflict {
+                    expected,
+                    current: current_version,
+                })
+            }
+        }
+    }
+
+    // Private helper to reconcile the FilesMap of a new version with the latest version of the
+    // FilesContainer, given the version the changes were applied to. It returns the version the
+    // new one will be appended to, and the FilesMap to store, merged with the changes made
+    // since the version the changes were applied to if needed and allowed.
+    pub(super) async fn reconcile_files_map(
+        &self,
+        safe_url: &SafeUrl,
+        base_version: u64,
+        latest_version: u64,
+        new_files_map: FilesMap,
+        expected_version: ExpectedVersion,
+    ) -> Result<(u64, FilesMap)> {
+        if latest_version == base_version {
+            return Ok((latest_version, new_files_map));
+        }
+
+        match expected_version {
+            ExpectedVersion::Any => Ok((latest_version, new_files_map)),
+            ExpectedVersion::Version(expected) => Err(Error::VersionConflict {
+                expected,
+                current: latest_version,
+            }),
+            ExpectedVersion::VersionOrMerge(expected) => {
+                let mut base_url = safe_url.clone();
+                base_url.set_content_version(Some(base_version));
+                let (_, base_files_map) = self.fetch_files_container(&base_url).await?;
+                let mut latest_url = safe_url.clone();
+                latest_url.set_content_version(Some(latest_version));
+                let (_, latest_files_map) = self.fetch_files_container(&latest_url).await?;
+
+                match merge_files_maps(&base_files_map, &new_files_map, &latest_files_map) {
+                    Ok(merged) => Ok((latest_version, merged)),
+                    Err(paths) => {
+                        debug!("Changes cannot be merged, conflicting paths: {:?}", paths);
+                        Err(Error::VersionConflict {
+                            expected,
+                            current: latest_version,
+                        })
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Three-way merge of FilesMaps: the changes made from the base FilesMap to ours are applied
+// on top of theirs. If both changed the same paths differently, or a path and one of its
+// ancestors or descendants, the conflicting paths are returned instead.
+fn merge_files_maps(
+    base: &FilesMap,
+    ours: &FilesMap,
+    theirs: &FilesMap,
+) -> std::result::Result<FilesMap, BTreeSet<String>> {
+    let our_changes = changed_paths(base, ours);
+    let their_changes = changed_paths(base, theirs);
+
+    let conflicts: BTreeSet<String> = our_changes
+        .iter()
+        .filter(|path| {
+            their_changes.iter().any(|their_path| {
+                (is_same_or_subpath(path, their_path) || is_same_or_subpath(their_path, path))
+                    && !(ours.get(*path) == theirs.get(*path)
+                        && ours.get(their_path) == theirs.get(their_path))
+            })
+        })
+        .cloned()
+        .collect();
+    if !conflicts.is_empty() {
+        return Err(conflicts);
+    }
+
+    let mut merged = theirs.clone();
+    for path in our_changes {
+        match ours.get(&path) {
+            Some(file_item) => {
+                merged.insert(path, file_item.clone());
+            }
+            None => {
+                merged.remove(&path);
+            }
+        }
+    }
+
+    Ok(merged)
+}
+
+// Paths which were added, removed or updated from one FilesMap to another
+pub(super) fn changed_paths(from: &FilesMap, to: &FilesMap) -> BTreeSet<String> {
+    from.keys()
+        .chain(to.keys())
+        .filter(|path| from.get(*path) != to.get(*path))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::files::{FileItem, FileItemType, FileMeta};
+
+    fn file_item(link: &str) -> FileItem {
+        FileMeta::from_type_and_size("text/plain", 1).to_file_item(FileItemType::File {
+            media_type: "text/plain".to_string(),
+            link: link.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_merge_files_maps() {
+        let mut base = FilesMap::default();
+        base.insert("/a.txt".to_string(), file_item("safe://a"));
+        base.insert("/dir/b.txt".to_string(), file_item("safe://b"));
+
+        let mut ours = base.clone();
+        ours.insert("/c.txt".to_string(), file_item("safe://c"));
+        ours.remove("/a.txt");
+
+        let mut theirs = base.clone();
+        theirs.insert("/dir/b.txt".to_string(), file_item("safe://b2"));
+        theirs.insert("/d.txt".to_string(), file_item("safe://d"));
+
+        let merged = merge_files_maps(&base, &ours, &theirs)
+            .unwrap_or_else(|paths| panic!("unexpected conflicts: {:?}", paths));
+        let paths: Vec<&str> = merged.keys().map(String::as_str).collect();
+        assert_eq!(paths, vec!["/c.txt", "/d.txt", "/dir/b.txt"]);
+        assert_eq!(merged["/dir/b.txt"].link(), Some("safe://b2"));
+
+        // Removing a directory conflicts with changes made within it
+        let mut ours = base.clone();
+        ours.remove("/dir/b.txt");
+        ours.insert("/dir".to_string(), file_item("safe://dir"));
+        let conflicts = merge_files_maps(&base, &ours, &theirs).err();
+        let expected: BTreeSet<String> = vec!["/dir".to_string(), "/dir/b.txt".to_string()]
+            .into_iter()
+            .collect();
+        assert_eq!(conflicts, Some(expected));
+
+        // The same change made by both is not a conflict
+        let merged = merge_files_maps(&base, &theirs, &theirs);
+        assert_eq!(merged, Ok(theirs));
+    }
+}
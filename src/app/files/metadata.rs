@@ -7,14 +7,19 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-use super::files_map::FileItem;
+use super::{
+    builder::FileAttributes,
+    files_map::{FileItem, FileItemType},
+    media_type::{detect_file_media_type, RAW_MEDIA_TYPE},
+};
 use crate::{
     app::{
         consts::*,
-        helpers::{gen_timestamp_secs, systemtime_to_rfc3339},
+        helpers::{gen_datetime_secs, systemtime_to_datetime_secs},
     },
     Error, Result,
 };
+use chrono::{DateTime, Utc};
 use log::debug;
 use std::{fs, path::Path};
 
@@ -22,17 +27,15 @@ use std::{fs, path::Path};
 use std::os::unix::fs::PermissionsExt;
 
 // Represents file metadata.  Simplifies passing it around.
-// note: all values are String or Option<String>
-// to facilitate use with FileItem.
 pub(crate) struct FileMeta {
-    created: String,
-    modified: String,
-    pub(crate) file_size: String,
+    created: DateTime<Utc>,
+    modified: DateTime<Utc>,
+    pub(crate) file_size: u64,
     pub(crate) file_type: String,
-    readonly: Option<String>,
-    mode_bits: Option<String>,
-    original_created: Option<String>,
-    original_modified: Option<String>,
+    readonly: Option<bool>,
+    mode_bits: Option<u32>,
+    original_created: Option<DateTime<Utc>>,
+    original_modified: Option<DateTime<Utc>>,
 }
 
 impl FileMeta {
@@ -41,34 +44,26 @@ impl FileMeta {
         let (metadata, file_type) = get_metadata(Path::new(path), follow_links)?;
 
         // created and modified may not be available on all platforms/filesystems.
-        let original_created = if let Ok(time) = metadata.created() {
-            Some(systemtime_to_rfc3339(time))
-        } else {
-            None
-        };
-        let original_modified = if let Ok(time) = metadata.modified() {
-            Some(systemtime_to_rfc3339(time))
-        } else {
-            None
-        };
-        let readonly = Some(metadata.permissions().readonly().to_string());
+        let original_created = metadata.created().ok().map(systemtime_to_datetime_secs);
+        let original_modified = metadata.modified().ok().map(systemtime_to_datetime_secs);
+        let readonly = Some(metadata.permissions().readonly());
 
         // We use 0 as file_size for metadata such as directories, symlinks.
         let file_size = if metadata.file_type().is_file() {
-            metadata.len().to_string()
+            metadata.len()
         } else {
-            "0".to_string()
+            0
         };
 
         #[cfg(windows)]
         let mode_bits = None; // Todo:  what does git do for windows?
 
         #[cfg(not(windows))]
-        let mode_bits = Some(metadata.permissions().mode().to_string());
+        let mode_bits = Some(metadata.permissions().mode());
 
         let s = Self {
-            created: gen_timestamp_secs(),
-            modified: gen_timestamp_secs(),
+            created: gen_datetime_secs(),
+            modified: gen_datetime_secs(),
             file_size,
             file_type,
             readonly,
@@ -81,42 +76,24 @@ impl FileMeta {
 
     // Instantiates FileMeta from a FileItem
     pub(crate) fn from_file_item(file_item: &FileItem) -> Self {
-        // The first 4 must be present, else a crash.
-        // lots of other code relies on this, so big refactor
-        // would be needed to change it.
-        let created = file_item[PREDICATE_CREATED].to_string();
-        let modified = file_item[PREDICATE_MODIFIED].to_string();
-        let file_size = file_item[PREDICATE_SIZE].to_string();
-        let file_type = file_item[PREDICATE_TYPE].to_string();
-
-        // These are all Option<String>
-        let original_created = file_item
-            .get(PREDICATE_ORIGINAL_CREATED)
-            .map(ToOwned::to_owned);
-        let original_modified = file_item
-            .get(PREDICATE_ORIGINAL_MODIFIED)
-            .map(ToOwned::to_owned);
-        let readonly = file_item.get(PREDICATE_READONLY).map(ToOwned::to_owned);
-        let mode_bits = file_item.get(PREDICATE_MODE_BITS).map(ToOwned::to_owned);
-
         Self {
-            created,
-            modified,
-            file_size,
-            file_type,
-            readonly,
-            mode_bits,
-            original_created,
-            original_modified,
+            created: file_item.created,
+            modified: file_item.modified,
+            file_size: file_item.size,
+            file_type: file_item.media_type().to_string(),
+            readonly: file_item.readonly,
+            mode_bits: file_item.mode_bits,
+            original_created: file_item.original_created,
+            original_modified: file_item.original_modified,
         }
     }
 
     // Instantiates FileMeta from just type and size properties.
-    pub(crate) fn from_type_and_size(file_type: &str, file_size: &str) -> Self {
+    pub(crate) fn from_type_and_size(file_type: &str, file_size: u64) -> Self {
         Self {
-            created: gen_timestamp_secs(),
-            modified: gen_timestamp_secs(),
-            file_size: file_size.to_string(),
+            created: gen_datetime_secs(),
+            modified: gen_datetime_secs(),
+            file_size,
             file_type: file_type.to_string(),
             readonly: None,
             mode_bits: None,
@@ -125,35 +102,42 @@ impl FileMeta {
         }
     }
 
-    // converts Self to FileItem
-    pub(crate) fn to_file_item(&self) -> FileItem {
-        let mut file_item = FileItem::new();
-        Self::add_to_fileitem(
-            &mut file_item,
-            PREDICATE_CREATED,
-            Some(self.created.clone()),
-        );
-        Self::add_to_fileitem(
-            &mut file_item,
-            PREDICATE_MODIFIED,
-            Some(self.modified.clone()),
-        );
-        Self::add_to_fileitem(&mut file_item, PREDICATE_SIZE, Some(self.file_size.clone()));
-        Self::add_to_fileitem(&mut file_item, PREDICATE_TYPE, Some(self.file_type.clone()));
-        Self::add_to_fileitem(&mut file_item, PREDICATE_READONLY, self.readonly.clone());
-        Self::add_to_fileitem(&mut file_item, PREDICATE_MODE_BITS, self.mode_bits.clone());
-        Self::add_to_fileitem(
-            &mut file_item,
-            PREDICATE_ORIGINAL_CREATED,
-            self.original_created.clone(),
-        );
-        Self::add_to_fileitem(
-            &mut file_item,
-            PREDICATE_ORIGINAL_MODIFIED,
-            self.original_modified.clone(),
-        );
-
-        file_item
+    // Instantiates FileMeta from the attributes provided for content not read from
+    // the local filesystem. The readonly flag is derived from the mode bits if not set.
+    pub(crate) fn from_attributes(
+        file_type: &str,
+        file_size: u64,
+        attributes: &FileAttributes,
+    ) -> Self {
+        let readonly = attributes
+            .readonly
+            .or_else(|| attributes.mode_bits.map(|mode| mode & 0o222 == 0));
+        Self {
+            created: gen_datetime_secs(),
+            modified: gen_datetime_secs(),
+            file_size,
+            file_type: file_type.to_string(),
+            readonly,
+            mode_bits: attributes.mode_bits,
+            original_created: attributes.created,
+            original_modified: attributes.modified,
+        }
+    }
+
+    // converts Self to a FileItem of the type provided
+    pub(crate) fn to_file_item(&self, file_type: FileItemType) -> FileItem {
+        FileItem {
+            file_type,
+            size: self.file_size,
+            created: self.created,
+            modified: self.modified,
+            original_created: self.original_created,
+            original_modified: self.original_modified,
+            readonly: self.readonly,
+            mode_bits: self.mode_bits,
+            checksum: None,
+            extensions: Default::default(),
+        }
     }
 
     // returns false if a directory or symlink, true if anything else (a file).
@@ -187,10 +171,29 @@ impl FileMeta {
         Self::filetype_is_dir(&self.file_type)
     }
 
-    // helper: adds property to FileItem if val.is_some()
-    fn add_to_fileitem(file_item: &mut FileItem, key: &str, val: Option<String>) {
-        if let Some(v) = val {
-            file_item.insert(key.to_string(), v);
+    // returns true if the original modification timestamp and size are the same in both,
+    // which is the heuristic used to skip comparing files content when syncing by mtime.
+    pub(crate) fn same_size_and_mtime(&self, other: &Self) -> bool {
+        self.file_size == other.file_size
+            && self.original_modified.is_some()
+            && self.original_modified == other.original_modified
+    }
+
+    // returns true if permissions metadata differs, optionally also comparing
+    // the original creation and modification timestamps.
+    pub(crate) fn metadata_differs(&self, other: &Self, compare_timestamps: bool) -> bool {
+        // mode_bits are not available on all platforms, so only compare them when both have it
+        let mode_bits_differ = match (&self.mode_bits, &other.mode_bits) {
+            (Some(mine), Some(theirs)) => mine != theirs,
+            _ => false,
+        };
+        let perms_differ = self.readonly != other.readonly || mode_bits_differ;
+        if compare_timestamps {
+            perms_differ
+                || self.original_created != other.original_created
+                || self.original_modified != other.original_modified
+        } else {
+            perms_differ
         }
     }
 }
@@ -224,7 +227,5 @@ fn get_media_type(path: &Path, meta: &fs::Metadata) -> String {
     } else if meta.file_type().is_symlink() {
         return MIMETYPE_FILESYSTEM_SYMLINK.to_string();
     }
-    let mime_type = mime_guess::from_path(&path);
-    let media_type = mime_type.first_raw().unwrap_or("Raw");
-    media_type.to_string()
+    detect_file_media_type(path).unwrap_or_else(|| RAW_MEDIA_TYPE.to_string())
 }
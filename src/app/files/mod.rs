@@ -7,29 +7,129 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
+mod archive;
+mod attributes;
+mod builder;
+mod checksums;
+mod config;
+mod entries;
 mod file_system;
 mod files_map;
+mod ls;
+mod media_type;
+mod merge;
 mod metadata;
+mod multi_writer;
+mod plan;
 mod realpath;
+mod safeguards;
+mod sharding;
+mod timestamps;
+mod verify;
+mod versions;
+mod vfs;
+mod watch;
 
 use crate::{
     app::consts::*, fetch::Range, Error, Result, Safe, SafeContentType, SafeDataType, SafeUrl,
     XorUrl,
 };
-use file_system::{file_system_dir_walk, file_system_single_file, normalise_path_separator};
-use files_map::add_or_update_file_item;
+use builder::ancestors;
+use file_system::{
+    file_system_dir_walk, file_system_dir_walk_with_checksums, file_system_single_file,
+    follow_symlink, normalise_path_separator, upload_file_to_net, FileChecksums,
+};
+use files_map::{
+    add_or_update_file_item, deserialise_files_map, serialise_files_map, FilesMapFormat,
+};
 use log::{debug, info, warn};
+use media_type::{detect_media_type, xorurl_media_type};
+use multi_writer::is_register_files_container;
 use relative_path::RelativePath;
+use safeguards::{local_paths_count, sync_deletions};
+use sharding::{store_sharded_files_map, ShardsLoader};
 use std::{collections::BTreeMap, fs, path::Path};
+use versions::{fetch_files_map_version, store_files_map_version};
 
+pub(crate) use checksums::verify_file_item_checksum;
+pub(crate) use media_type::RAW_MEDIA_TYPE;
 pub(crate) use metadata::FileMeta;
 pub(crate) use realpath::RealPath;
-
-pub use files_map::{FileItem, FilesMap, GetAttr};
+pub(crate) use versions::FilesMapsCache;
+
+pub use archive::ArchiveFormat;
+pub use builder::{FileAttributes, FilesContainerBuilder};
+pub use checksums::ChecksumAlgorithm;
+pub use config::FilesConfig;
+pub use files_map::{
+    FileItem, FileItemType, FilesMap, FilesMapEncoding, FilesMapLayout, SymlinkTargetType,
+    FILES_MAP_SCHEMA_VERSION,
+};
+pub use ls::{LsEntry, LsOptions, LsPage, LsSortBy};
+pub use merge::ExpectedVersion;
+pub use plan::UploadPlan;
+pub use safeguards::DeletionSafeguards;
+pub use timestamps::FilesTimestamps;
+pub use verify::VerifyReport;
+pub use vfs::{FilesContainerVfs, Vfs, VfsAttr, VfsDirEntry, VfsFileType};
+pub use watch::{WatchCancellation, WatchOptions};
 
 // List of files uploaded with details if they were added, updated or deleted from FilesContainer
 pub type ProcessedFiles = BTreeMap<String, (String, String)>;
 
+/// Policy used by sync operations to decide if a local file differs from its FileItem
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilesComparison {
+    /// Compare files content and symlinks targets only
+    ContentOnly,
+    /// Compare content as well as permissions metadata (mode bits and readonly flag)
+    ContentAndMetadata,
+    /// Compare metadata including original timestamps, and only compare the content
+    /// of files which size or modification time differ from the FileItem's
+    ModifiedTime,
+}
+
+impl Default for FilesComparison {
+    fn default() -> Self {
+        Self::ContentOnly
+    }
+}
+
+/// Policy used when reading local folders to decide how the symlinks found are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Keep symlinks as such in the FilesContainer, storing only their targets
+    Preserve,
+    /// Follow the symlinks which target is within the folder being read,
+    /// and preserve those pointing outside of it
+    FollowWithinRoot,
+    /// Follow all symlinks, storing the content they point to
+    FollowAll,
+    /// Leave symlinks out of the FilesContainer
+    Skip,
+}
+
+impl Default for SymlinkPolicy {
+    fn default() -> Self {
+        Self::Preserve
+    }
+}
+
+// Number of times the changes of a new version are merged with the updates made to the
+// FilesContainer while it was being stored, before giving up with a version conflict
+const MAX_MERGE_ATTEMPTS: usize = 3;
+
+/// Default maximum number of nested symlinks followed, see `FilesConfig::symlinks_max_depth`
+pub const DEFAULT_SYMLINKS_MAX_DEPTH: usize = 16;
+
+// Kind of change found when comparing a local file with its FileItem
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileItemChange {
+    Unchanged,
+    Metadata,
+    Content,
+}
+
 // Type tag to use for the FilesContainer stored on Sequence
 const FILES_CONTAINER_TYPE_TAG: u64 = 1_100;
 
@@ -38,15 +138,18 @@ const ERROR_MSG_NO_FILES_CONTAINER_FOUND: &str = "No FilesContainer found at thi
 impl Safe {
     /// # Create a FilesContainer.
     ///
+    /// If `options.private` is set, the FilesContainer is stored on a Private Sequence, and
+    /// both the files and the FilesMap are stored as Private Blobs.
+    ///
     /// ## Example
     ///
     /// ```no_run
-    /// # use sn_api::Safe;
+    /// # use sn_api::{Safe, files::{CreateOptions, SymlinkPolicy}};
     /// # let mut safe = Safe::default();
     /// # let rt = tokio::runtime::Runtime::new().unwrap();
     /// # rt.block_on(async {
     ///     safe.connect(None, None, None).await.unwrap();
-    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata"), None, true, true, false).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata"), None, &CreateOptions { symlinks: SymlinkPolicy::FollowAll, ..CreateOptions::default() }).await.unwrap();
     ///     assert!(xorurl.contains("safe://"))
     /// # });
     /// ```
@@ -54,65 +157,65 @@ impl Safe {
         &mut self,
         location: Option<&str>,
         dest: Option<&str>,
-        recursive: bool,
-        follow_links: bool,
-        dry_run: bool,
+        options: &CreateOptions,
     ) -> Result<(XorUrl, ProcessedFiles, FilesMap)> {
         // TODO: Enable source for funds / ownership
         // Warn about ownership?
+        let (processed_files, files_map) = self
+            .files_map_from_location(location, dest, options)
+            .await?;
+
+        let xorurl = if options.dry_run {
+            "".to_string()
+        } else {
+            self.store_new_files_container(&files_map, options.private)
+                .await?
+        };
+
+        Ok((xorurl, processed_files, files_map))
+    }
+
+    // Private helper to upload the files found at a local location, if any, and
+    // generate the FilesMap with them for the first version of a new FilesContainer
+    async fn files_map_from_location(
+        &mut self,
+        location: Option<&str>,
+        dest: Option<&str>,
+        options: &CreateOptions,
+    ) -> Result<(ProcessedFiles, FilesMap)> {
+        let CreateOptions {
+            recursive,
+            symlinks,
+            private,
+            dry_run,
+        } = *options;
 
         // Let's upload the files and generate the list of local files paths
-        let (processed_files, files_map) = match location {
+        match location {
             Some(path) => {
-                let mut processed_files =
-                    file_system_dir_walk(self, path, recursive, follow_links, dry_run).await?;
+                let (mut processed_files, checksums) = file_system_dir_walk_with_checksums(
+                    self, path, recursive, symlinks, private, dry_run,
+                )
+                .await?;
 
                 // The FilesContainer is stored on a Sequence
                 // and the link to the serialised FilesMap as the entry's value
-                // TODO: use RDF format
-                let files_map = files_map_create(
+                let mut files_map = files_map_create(
                     self,
                     &mut processed_files,
+                    checksums,
                     path,
                     dest,
-                    follow_links,
+                    symlinks,
+                    private,
                     dry_run,
                 )
                 .await?;
-                (processed_files, files_map)
+                self.apply_files_timestamps(&mut files_map);
+                Ok((processed_files, files_map))
             }
-            None => (ProcessedFiles::default(), FilesMap::default()),
-        };
-
-        let xorurl = if dry_run {
-            "".to_string()
-        } else {
-            // Store the serialised FilesMap in a Public Blob
-            let files_map_xorurl = self.store_files_map(&files_map).await?;
-
-            // Store the FilesContainer in a Public Sequence, putting the
-            // serialised FilesMap XOR-URL as the first entry value
-            let xorname = self
-                .safe_client
-                .store_sequence(
-                    files_map_xorurl.as_bytes(),
-                    None,
-                    FILES_CONTAINER_TYPE_TAG,
-                    None,
-                    false,
-                )
-                .await?;
-
-            SafeUrl::encode_sequence_data(
-                xorname,
-                FILES_CONTAINER_TYPE_TAG,
-                SafeContentType::FilesContainer,
-                self.xorurl_base,
-                false,
-            )?
-        };
-
-        Ok((xorurl, processed_files, files_map))
+            None => Ok((ProcessedFiles::default(), FilesMap::default())),
+        }
     }
 
     /// # Fetch an existing FilesContainer.
@@ -120,12 +223,12 @@ impl Safe {
     /// ## Example
     ///
     /// ```no_run
-    /// # use sn_api::Safe;
+    /// # use sn_api::{Safe, files::{CreateOptions, SymlinkPolicy}};
     /// # let mut safe = Safe::default();
     /// # let rt = tokio::runtime::Runtime::new().unwrap();
     /// # rt.block_on(async {
     /// #   safe.connect(None, None, None).await.unwrap();
-    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata"), None, true, true, false).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata"), None, &CreateOptions { symlinks: SymlinkPolicy::FollowAll, ..CreateOptions::default() }).await.unwrap();
     ///     let (version, files_map) = safe.files_container_get(&xorurl).await.unwrap();
     ///     println!("FilesContainer fetched is at version: {}", version);
     ///     println!("FilesMap of fetched version is: {:?}", files_map);
@@ -143,36 +246,75 @@ impl Safe {
         &self,
         safe_url: &SafeUrl,
     ) -> Result<(u64, FilesMap)> {
-        // Check if the URL specifies a specific version of the content or simply the latest available
-        match self.fetch_sequence(safe_url).await {
-            Ok((version, serialised_files_map)) => {
-                debug!("Files map retrieved.... v{:?}", &version);
-                // TODO: use RDF format and deserialise it
-                // We first obtain the FilesMap XOR-URL from the Sequence
-                let files_map_xorurl = SafeUrl::from_url(
-                    &String::from_utf8(serialised_files_map).map_err(|err| {
-                        Error::ContentError(format!(
-                            "Couldn't parse the FilesMap link stored in the FilesContainer: {:?}",
-                            err
-                        ))
-                    })?,
-                )?;
-
-                // Using the FilesMap XOR-URL we can now fetch the FilesMap and deserialise it
-                let serialised_files_map = self.fetch_public_blob(&files_map_xorurl, None).await?;
+        // Concurrent versions of multi-writer FilesContainers need to be merged
+        if is_register_files_container(safe_url) {
+            return self.fetch_register_files_container(safe_url).await;
+        }
+
+        match self.fetch_files_map_link(safe_url).await? {
+            Some((version, files_map_xorurl, format)) => {
+                // Using the FilesMap XOR-URL we can now fetch the FilesMap and deserialise it,
+                // rebuilding it from the deltas stored since the last snapshot if needed
                 let files_map =
-                    serde_json::from_slice(serialised_files_map.as_slice()).map_err(|err| {
-                        Error::ContentError(format!(
-                            "Couldn't deserialise the FilesMap stored in the FilesContainer: {:?}",
-                            err
-                        ))
-                    })?;
+                    fetch_files_map_version(self, safe_url, version, files_map_xorurl, format)
+                        .await?;
 
                 Ok((version, files_map))
             }
+            None => Ok((0, FilesMap::default())),
+        }
+    }
+
+    /// Fetch a FilesContainer from a SafeUrl without performing any type of URL resolution,
+    /// resolving the real path of a path within it. When the FilesMap is sharded, only the
+    /// shards needed to resolve the path are fetched, plus those of its subtree if it's a
+    /// directory, thus the FilesMap returned may not contain all the FileItems.
+    pub(crate) async fn fetch_files_container_path(
+        &self,
+        safe_url: &SafeUrl,
+        path: &str,
+    ) -> Result<(u64, FilesMap, String)> {
+        match self.fetch_files_map_link(safe_url).await? {
+            Some((version, files_map_xorurl, format))
+                if format.layout == FilesMapLayout::Sharded
+                    && format.delta_base.is_none()
+                    && !is_register_files_container(safe_url) =>
+            {
+                let (realpath, files_map) = ShardsLoader::new(files_map_xorurl, format.encoding)
+                    .resolve_path(self, path)
+                    .await?;
+                Ok((version, files_map, realpath))
+            }
+            _ => {
+                let (version, files_map) = self.fetch_files_container(safe_url).await?;
+                let realpath = files_map.realpath(path)?;
+                Ok((version, files_map, realpath))
+            }
+        }
+    }
+
+    // Private helper to fetch the link to the FilesMap of a FilesContainer's version,
+    // together with the format it was stored with, or None if the FilesContainer is empty
+    async fn fetch_files_map_link(
+        &self,
+        safe_url: &SafeUrl,
+    ) -> Result<Option<(u64, SafeUrl, FilesMapFormat)>> {
+        if is_register_files_container(safe_url) {
+            return self.fetch_register_files_map_link(safe_url).await;
+        }
+
+        // Check if the URL specifies a specific version of the content or simply the latest available
+        match self.fetch_sequence(safe_url).await {
+            Ok((version, files_map_link)) => {
+                debug!("Files map retrieved.... v{:?}", &version);
+                // We obtain the FilesMap XOR-URL from the Sequence,
+                // together with the format the FilesMap was stored with
+                let (files_map_xorurl, format) = parse_files_map_link(files_map_link)?;
+                Ok(Some((version, files_map_xorurl, format)))
+            }
             Err(Error::EmptyContent(_)) => {
                 warn!("FilesContainer found at \"{:?}\" was empty", safe_url);
-                Ok((0, FilesMap::default()))
+                Ok(None)
             }
             Err(Error::ContentNotFound(_)) => Err(Error::ContentNotFound(
                 ERROR_MSG_NO_FILES_CONTAINER_FOUND.to_string(),
@@ -191,32 +333,47 @@ impl Safe {
 
     /// # Sync up local folder with the content on a FilesContainer.
     ///
+    /// The `options.expected_version` is the precondition on the version of the FilesContainer
+    /// for the changes to be committed, the same as for all the APIs which update it.
+    ///
+    /// If `options.delete` is set, the paths not found locally are removed from the FilesContainer
+    /// as long as the deletions don't break any of the `FilesConfig::deletion_safeguards`, otherwise
+    /// an `Error::DeletionsNotConfirmed` listing them is returned. The deletions can then be
+    /// reviewed and made by setting `options.confirm_deletions`, as long as none of them is of
+    /// any of the protected paths, which are never deleted.
+    ///
     /// ## Example
     ///
     /// ```no_run
-    /// # use sn_api::Safe;
+    /// # use sn_api::{Safe, files::{CreateOptions, SymlinkPolicy, SyncOptions}};
     /// # let mut safe = Safe::default();
     /// # let rt = tokio::runtime::Runtime::new().unwrap();
     /// # rt.block_on(async {
     /// #   safe.connect(None, None, None).await.unwrap();
-    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata"), None, true, false, false).await.unwrap();
-    ///     let (version, new_processed_files, new_files_map) = safe.files_container_sync("../testdata", &xorurl, true, true, false, false, false).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata"), None, &CreateOptions::default()).await.unwrap();
+    ///     let (version, new_processed_files, new_files_map) = safe.files_container_sync("../testdata", &xorurl, &SyncOptions { symlinks: SymlinkPolicy::FollowAll, ..SyncOptions::default() }).await.unwrap();
     ///     println!("FilesContainer synced up is at version: {}", version);
     ///     println!("The local files that were synced up are: {:?}", new_processed_files);
     ///     println!("The FilesMap of the updated FilesContainer now is: {:?}", new_files_map);
     /// # });
     /// ```
-    #[allow(clippy::too_many_arguments)]
     pub async fn files_container_sync(
         &mut self,
         location: &str,
         url: &str,
-        recursive: bool,
-        follow_links: bool,
-        delete: bool,
-        update_nrs: bool,
-        dry_run: bool,
+        options: &SyncOptions,
     ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        let SyncOptions {
+            recursive,
+            symlinks,
+            comparison,
+            delete,
+            confirm_deletions,
+            update_nrs,
+            expected_version,
+            dry_run,
+        } = *options;
+
         if delete && !recursive {
             return Err(Error::InvalidInput(
                 "'delete' is not allowed if 'recursive' is not set".to_string(),
@@ -244,15 +401,32 @@ impl Safe {
         // the version from it so we can fetch latest version of it for sync-ing
         safe_url.set_content_version(None);
 
-        let (current_version, current_files_map): (u64, FilesMap) =
-            self.fetch_files_container(&safe_url).await?;
+        let (current_version, current_files_map): (u64, FilesMap) = self
+            .fetch_files_container_for_update(&safe_url, expected_version)
+            .await?;
+
+        // Files are stored as Private Blobs if the FilesContainer is private
+        let private = is_private_files_container(&safe_url);
 
         // Let's generate the list of local files paths, without uploading any new file yet
         let processed_files =
-            file_system_dir_walk(self, location, recursive, follow_links, true).await?;
+            file_system_dir_walk(self, location, recursive, symlinks, private, true).await?;
 
         let dest_path = Some(safe_url.path());
 
+        // Check the paths which would be deleted before uploading anything
+        if delete {
+            let deletions =
+                sync_deletions(&current_files_map, location, &processed_files, dest_path);
+            let local_count = local_paths_count(&processed_files, location);
+            self.files_config.deletion_safeguards.check(
+                &deletions,
+                current_files_map.len(),
+                local_count,
+                confirm_deletions,
+            )?;
+        }
+
         let (processed_files, new_files_map, success_count): (ProcessedFiles, FilesMap, u64) =
             files_map_sync(
                 self,
@@ -261,20 +435,22 @@ impl Safe {
                 processed_files,
                 dest_path,
                 delete,
+                private,
                 dry_run,
                 false,
-                true,
-                follow_links,
+                Some(comparison),
+                symlinks,
             )
             .await?;
 
-        let version = self
+        let (version, new_files_map) = self
             .append_version_to_files_container(
                 success_count,
                 current_version,
-                &new_files_map,
+                new_files_map,
                 url,
                 safe_url,
+                expected_version,
                 dry_run,
                 update_nrs,
             )
@@ -288,14 +464,14 @@ impl Safe {
     /// ## Example
     ///
     /// ```no_run
-    /// # use sn_api::Safe;
+    /// # use sn_api::{Safe, files::{AddOptions, CreateOptions, SymlinkPolicy}};
     /// # let mut safe = Safe::default();
     /// # let rt = tokio::runtime::Runtime::new().unwrap();
     /// # rt.block_on(async {
     /// #   safe.connect(None, None, None).await.unwrap();
-    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata"), None, true, true, false).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata"), None, &CreateOptions { symlinks: SymlinkPolicy::FollowAll, ..CreateOptions::default() }).await.unwrap();
     ///     let new_file_name = format!("{}/new_name_test.md", xorurl);
-    ///     let (version, new_processed_files, new_files_map) = safe.files_container_add("../testdata/test.md", &new_file_name, false, false, true, false).await.unwrap();
+    ///     let (version, new_processed_files, new_files_map) = safe.files_container_add("../testdata/test.md", &new_file_name, &AddOptions { symlinks: SymlinkPolicy::FollowAll, ..AddOptions::default() }).await.unwrap();
     ///     println!("FilesContainer is now at version: {}", version);
     ///     println!("The local files that were synced up are: {:?}", new_processed_files);
     ///     println!("The FilesMap of the updated FilesContainer now is: {:?}", new_files_map);
@@ -305,23 +481,28 @@ impl Safe {
         &mut self,
         source_file: &str,
         url: &str,
-        force: bool,
-        update_nrs: bool,
-        follow_links: bool,
-        dry_run: bool,
+        options: &AddOptions,
     ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        let AddOptions {
+            force,
+            symlinks,
+            update_nrs,
+            expected_version,
+            dry_run,
+        } = *options;
         let (safe_url, current_version, current_files_map) =
-            validate_files_add_params(self, source_file, url, update_nrs).await?;
+            validate_files_add_params(self, source_file, url, update_nrs, expected_version).await?;
 
         let dest_path = safe_url.path();
+        let private = is_private_files_container(&safe_url);
 
         // Let's act according to if it's a local file path or a safe:// location
         let (processed_files, new_files_map, success_count) = if source_file.starts_with("safe://")
         {
-            files_map_add_link(self, current_files_map, source_file, dest_path, force).await?
+            files_map_add_link(self, current_files_map, source_file, dest_path, None, force).await?
         } else {
             // Let's generate the list of local files paths, without uploading any new file yet
-            let processed_files = file_system_single_file(self, source_file, true).await?;
+            let processed_files = file_system_single_file(self, source_file, private, true).await?;
 
             files_map_sync(
                 self,
@@ -330,21 +511,23 @@ impl Safe {
                 processed_files,
                 Some(dest_path),
                 false,
+                private,
                 dry_run,
                 force,
-                false,
-                follow_links,
+                None,
+                symlinks,
             )
             .await?
         };
 
-        let version = self
+        let (version, new_files_map) = self
             .append_version_to_files_container(
                 success_count,
                 current_version,
-                &new_files_map,
+                new_files_map,
                 url,
                 safe_url,
+                expected_version,
                 dry_run,
                 update_nrs,
             )
@@ -358,14 +541,14 @@ impl Safe {
     /// ## Example
     ///
     /// ```no_run
-    /// # use sn_api::Safe;
+    /// # use sn_api::{Safe, files::{AddOptions, CreateOptions, SymlinkPolicy}};
     /// # let mut safe = Safe::default();
     /// # let rt = tokio::runtime::Runtime::new().unwrap();
     /// # rt.block_on(async {
     /// #   safe.connect(None, None, None).await.unwrap();
-    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata"), None, true, true, false).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata"), None, &CreateOptions { symlinks: SymlinkPolicy::FollowAll, ..CreateOptions::default() }).await.unwrap();
     ///     let new_file_name = format!("{}/new_name_test.md", xorurl);
-    ///     let (version, new_processed_files, new_files_map) = safe.files_container_add_from_raw(b"0123456789", &new_file_name, false, false, false).await.unwrap();
+    ///     let (version, new_processed_files, new_files_map) = safe.files_container_add_from_raw(b"0123456789", &new_file_name, &AddOptions::default()).await.unwrap();
     ///     println!("FilesContainer is now at version: {}", version);
     ///     println!("The local files that were synced up are: {:?}", new_processed_files);
     ///     println!("The FilesMap of the updated FilesContainer now is: {:?}", new_files_map);
@@ -375,26 +558,48 @@ impl Safe {
         &mut self,
         data: &[u8],
         url: &str,
-        force: bool,
-        update_nrs: bool,
-        dry_run: bool,
+        options: &AddOptions,
     ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        let AddOptions {
+            force,
+            update_nrs,
+            expected_version,
+            dry_run,
+            ..
+        } = *options;
         let (safe_url, current_version, current_files_map) =
-            validate_files_add_params(self, "", url, update_nrs).await?;
+            validate_files_add_params(self, "", url, update_nrs, expected_version).await?;
 
         let dest_path = safe_url.path();
-        let new_file_xorurl = self.files_store_public_blob(data, None, false).await?;
+        let private = is_private_files_container(&safe_url);
+        let media_type = detect_media_type(Path::new(dest_path), data);
+        let new_file_xorurl = self
+            .files_store_blob(
+                data,
+                xorurl_media_type(media_type.as_deref()),
+                private,
+                false,
+            )
+            .await?;
 
         // Let's act according to if it's a local file path or a safe:// location
-        let (processed_files, new_files_map, success_count) =
-            files_map_add_link(self, current_files_map, &new_file_xorurl, dest_path, force).await?;
-        let version = self
+        let (processed_files, new_files_map, success_count) = files_map_add_link(
+            self,
+            current_files_map,
+            &new_file_xorurl,
+            dest_path,
+            media_type.as_deref(),
+            force,
+        )
+        .await?;
+        let (version, new_files_map) = self
             .append_version_to_files_container(
                 success_count,
                 current_version,
-                &new_files_map,
+                new_files_map,
                 url,
                 safe_url,
+                expected_version,
                 dry_run,
                 update_nrs,
             )
@@ -408,14 +613,14 @@ impl Safe {
     /// ## Example
     ///
     /// ```no_run
-    /// # use sn_api::Safe;
+    /// # use sn_api::{Safe, files::{CreateOptions, SymlinkPolicy, UpdateOptions}};
     /// # let mut safe = Safe::default();
     /// # let rt = tokio::runtime::Runtime::new().unwrap();
     /// # rt.block_on(async {
     /// #   safe.connect(None, None, None).await.unwrap();
-    ///     let (xorurl, processed_files, files_map) = safe.files_container_create(Some("../testdata/"), None, true, true, false).await.unwrap();
+    ///     let (xorurl, processed_files, files_map) = safe.files_container_create(Some("../testdata/"), None, &CreateOptions { symlinks: SymlinkPolicy::FollowAll, ..CreateOptions::default() }).await.unwrap();
     ///     let remote_file_path = format!("{}/test.md", xorurl);
-    ///     let (version, new_processed_files, new_files_map) = safe.files_container_remove_path(&remote_file_path, false, false, false).await.unwrap();
+    ///     let (version, new_processed_files, new_files_map) = safe.files_container_remove_path(&remote_file_path, false, &UpdateOptions::default()).await.unwrap();
     ///     println!("FilesContainer is now at version: {}", version);
     ///     println!("The files that were removed: {:?}", new_processed_files);
     ///     println!("The FilesMap of the updated FilesContainer now is: {:?}", new_files_map);
@@ -425,9 +630,13 @@ impl Safe {
         &mut self,
         url: &str,
         recursive: bool,
-        update_nrs: bool,
-        dry_run: bool,
+        options: &UpdateOptions,
     ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        let UpdateOptions {
+            update_nrs,
+            expected_version,
+            dry_run,
+        } = *options;
         let safe_url = Safe::parse_url(url)?;
         if safe_url.content_version().is_some() {
             return Err(Error::InvalidInput(format!(
@@ -456,19 +665,197 @@ impl Safe {
         // the version from it so we can fetch latest version of it
         safe_url.set_content_version(None);
 
-        let (current_version, files_map): (u64, FilesMap) =
-            self.fetch_files_container(&safe_url).await?;
+        let (current_version, files_map): (u64, FilesMap) = self
+            .fetch_files_container_for_update(&safe_url, expected_version)
+            .await?;
 
         let (processed_files, new_files_map, success_count) =
             files_map_remove_path(dest_path, files_map, recursive)?;
 
-        let version = self
+        let (version, new_files_map) = self
+            .append_version_to_files_container(
+                success_count,
+                current_version,
+                new_files_map,
+                url,
+                safe_url,
+                expected_version,
+                dry_run,
+                update_nrs,
+            )
+            .await?;
+
+        Ok((version, processed_files, new_files_map))
+    }
+
+    /// # Move or rename a path within an existing FilesContainer.
+    ///
+    /// All the entries found under the source path are moved to the destination path keeping
+    /// their links and metadata, thus no content is uploaded again. Relative symlinks
+    /// are updated, when possible, so they keep pointing to the same targets.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::{Safe, files::{CreateOptions, SymlinkPolicy, UpdateOptions}};
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, &CreateOptions { symlinks: SymlinkPolicy::FollowAll, ..CreateOptions::default() }).await.unwrap();
+    ///     let (version, new_processed_files, new_files_map) = safe.files_container_move(&xorurl, "/test.md", "/renamed.md", false, &UpdateOptions::default()).await.unwrap();
+    ///     println!("FilesContainer is now at version: {}", version);
+    ///     println!("The files that were moved: {:?}", new_processed_files);
+    ///     println!("The FilesMap of the updated FilesContainer now is: {:?}", new_files_map);
+    /// # });
+    /// ```
+    pub async fn files_container_move(
+        &mut self,
+        url: &str,
+        from_path: &str,
+        to_path: &str,
+        force: bool,
+        options: &UpdateOptions,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        let UpdateOptions {
+            update_nrs,
+            expected_version,
+            dry_run,
+        } = *options;
+        let safe_url = Safe::parse_url(url)?;
+        if safe_url.content_version().is_some() {
+            return Err(Error::InvalidInput(format!(
+                "The target URL cannot contain a version: {}",
+                url
+            )));
+        };
+
+        // If NRS name shall be updated then the URL has to be an NRS-URL
+        if update_nrs && safe_url.content_type() != SafeContentType::NrsMapContainer {
+            return Err(Error::InvalidInput(
+                "'update-nrs' is not allowed since the URL provided is not an NRS URL".to_string(),
+            ));
+        }
+
+        let (mut safe_url, _) = self.parse_and_resolve_url(url).await?;
+
+        // If the FilesContainer URL was resolved from an NRS name we need to remove
+        // the version from it so we can fetch latest version of it
+        safe_url.set_content_version(None);
+
+        let (current_version, files_map): (u64, FilesMap) = self
+            .fetch_files_container_for_update(&safe_url, expected_version)
+            .await?;
+
+        let (processed_files, new_files_map, success_count) =
+            files_map_move_path(from_path, to_path, files_map, force)?;
+
+        let (version, new_files_map) = self
             .append_version_to_files_container(
                 success_count,
                 current_version,
-                &new_files_map,
+                new_files_map,
                 url,
                 safe_url,
+                expected_version,
+                dry_run,
+                update_nrs,
+            )
+            .await?;
+
+        Ok((version, processed_files, new_files_map))
+    }
+
+    /// # Copy paths from a FilesContainer into another one, by reference.
+    ///
+    /// The FileItems found at the source URL, which can target any version of a FilesContainer,
+    /// are added to the destination FilesContainer keeping their links, thus no content is
+    /// uploaded. All the copied entries are committed in a single new version.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::{Safe, files::{CreateOptions, SymlinkPolicy, UpdateOptions}};
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let (src_xorurl, _, _) = safe.files_container_create(Some("../testdata/"), None, &CreateOptions { symlinks: SymlinkPolicy::FollowAll, ..CreateOptions::default() }).await.unwrap();
+    ///     let (dest_xorurl, _, _) = safe.files_container_create(None, None, &CreateOptions { symlinks: SymlinkPolicy::FollowAll, ..CreateOptions::default() }).await.unwrap();
+    ///     let src_url = format!("{}/subfolder", src_xorurl);
+    ///     let dest_url = format!("{}/assets/", dest_xorurl);
+    ///     let (version, new_processed_files, new_files_map) = safe.files_container_copy(&src_url, &dest_url, true, false, &UpdateOptions::default()).await.unwrap();
+    ///     println!("FilesContainer is now at version: {}", version);
+    ///     println!("The files that were copied: {:?}", new_processed_files);
+    ///     println!("The FilesMap of the updated FilesContainer now is: {:?}", new_files_map);
+    /// # });
+    /// ```
+    pub async fn files_container_copy(
+        &mut self,
+        src_url: &str,
+        dest_url: &str,
+        recursive: bool,
+        force: bool,
+        options: &UpdateOptions,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        let UpdateOptions {
+            update_nrs,
+            expected_version,
+            dry_run,
+        } = *options;
+        let (src_safe_url, _) = self.parse_and_resolve_url(src_url).await?;
+        if src_safe_url.content_type() != SafeContentType::FilesContainer {
+            return Err(Error::InvalidInput(format!(
+                "The source URL should target a FilesContainer, but the URL provided targets a '{}'",
+                src_safe_url.content_type()
+            )));
+        }
+        let src_path = src_safe_url.path_decoded()?;
+        let (_, src_files_map) = self.fetch_files_container(&src_safe_url).await?;
+
+        let dest_safe_url = Safe::parse_url(dest_url)?;
+        if dest_safe_url.content_version().is_some() {
+            return Err(Error::InvalidInput(format!(
+                "The target URL cannot contain a version: {}",
+                dest_url
+            )));
+        };
+
+        // If NRS name shall be updated then the URL has to be an NRS-URL
+        if update_nrs && dest_safe_url.content_type() != SafeContentType::NrsMapContainer {
+            return Err(Error::InvalidInput(
+                "'update-nrs' is not allowed since the URL provided is not an NRS URL".to_string(),
+            ));
+        }
+
+        let (mut dest_safe_url, _) = self.parse_and_resolve_url(dest_url).await?;
+
+        // If the FilesContainer URL was resolved from an NRS name we need to remove
+        // the version from it so we can fetch latest version of it
+        dest_safe_url.set_content_version(None);
+        let dest_path = dest_safe_url.path_decoded()?;
+
+        let (current_version, current_files_map): (u64, FilesMap) = self
+            .fetch_files_container_for_update(&dest_safe_url, expected_version)
+            .await?;
+
+        let (processed_files, new_files_map, success_count) = files_map_copy(
+            &src_files_map,
+            &src_path,
+            current_files_map,
+            &dest_path,
+            recursive,
+            force,
+        )?;
+
+        let (version, new_files_map) = self
+            .append_version_to_files_container(
+                success_count,
+                current_version,
+                new_files_map,
+                dest_url,
+                dest_safe_url,
+                expected_version,
                 dry_run,
                 update_nrs,
             )
@@ -478,35 +865,106 @@ impl Safe {
     }
 
     // Private helper function to append new version of the FilesMap to the Files Container
-    // It flagged with `update_nrs`, it will also update the link in the corresponding NRS Map Container
+    // It flagged with `update_nrs`, it will also update the link in the corresponding NRS Map Container.
+    // The version expected is checked against the latest version before storing the new FilesMap,
+    // merging the changes with those made since `current_version` if allowed, and checked again
+    // right before appending it, in case the FilesContainer was updated while it was being stored.
+    // It returns the new version together with the FilesMap stored with it.
     #[allow(clippy::too_many_arguments)]
     async fn append_version_to_files_container(
         &mut self,
         success_count: u64,
         current_version: u64,
-        new_files_map: &FilesMap,
+        mut new_files_map: FilesMap,
         url: &str,
         mut safe_url: SafeUrl,
+        expected_version: ExpectedVersion,
         dry_run: bool,
         update_nrs: bool,
-    ) -> Result<u64> {
-        let version = if success_count == 0 {
-            current_version
-        } else if dry_run {
-            current_version + 1
-        } else {
+    ) -> Result<(u64, FilesMap)> {
+        if success_count == 0 {
+            return Ok((current_version, new_files_map));
+        }
+
+        self.apply_files_timestamps_to_changes(&safe_url, current_version, &mut new_files_map)
+            .await?;
+
+        let private = is_private_files_container(&safe_url);
+        let mut attempts = 0;
+        loop {
+            let current = self.fetch_files_map_link(&safe_url).await?;
+            let latest_version = current.as_ref().map_or(0, |(version, _, _)| *version);
+            let (latest_version, files_map) = self
+                .reconcile_files_map(
+                    &safe_url,
+                    current_version,
+                    latest_version,
+                    new_files_map.clone(),
+                    expected_version,
+                )
+                .await?;
+
+            let new_version = latest_version + 1;
+            if dry_run {
+                return Ok((new_version, files_map));
+            }
+
+            // Concurrent versions of multi-writer FilesContainers are kept as separate
+            // heads which are merged when fetched, thus they don't overwrite each other
+            if is_register_files_container(&safe_url) {
+                let new_version = self
+                    .append_to_register_files_container(&safe_url, &files_map)
+                    .await?;
+                if update_nrs {
+                    // Only the latest version of multi-writer FilesContainers can be
+                    // fetched, thus the NRS name is linked to it without a version
+                    let new_link_for_nrs = safe_url.to_string();
+                    let _ = self
+                        .nrs_map_container_add(url, &new_link_for_nrs, false, true, false)
+                        .await?;
+                }
+                return Ok((new_version, files_map));
+            }
+
             // The FilesContainer is updated by adding an entry containing the link to
             // the Blob with the serialised new version of the FilesMap.
-            let files_map_xorurl = self.store_files_map(new_files_map).await?;
+            // New versions keep the format the FilesContainer was created with,
+            // and they are stored as deltas against the current version if enabled
+            let files_map_xorurl =
+                store_files_map_version(self, &safe_url, current, &files_map, private).await?;
+
+            // The FilesContainer may have been updated while the FilesMap was being stored
+            if let ExpectedVersion::Version(expected) | ExpectedVersion::VersionOrMerge(expected) =
+                expected_version
+            {
+                let head_version = self
+                    .fetch_files_map_link(&safe_url)
+                    .await?
+                    .map_or(0, |(version, _, _)| version);
+                if head_version != latest_version {
+                    attempts += 1;
+                    if matches!(expected_version, ExpectedVersion::VersionOrMerge(_))
+                        && attempts < MAX_MERGE_ATTEMPTS
+                    {
+                        debug!(
+                            "FilesContainer was updated to version {} while storing version {}, merging again",
+                            head_version, new_version
+                        );
+                        continue;
+                    }
+                    return Err(Error::VersionConflict {
+                        expected,
+                        current: head_version,
+                    });
+                }
+            }
 
             let xorname = safe_url.xorname();
             let type_tag = safe_url.type_tag();
             self.safe_client
-                .append_to_sequence(files_map_xorurl.as_bytes(), xorname, type_tag, false)
+                .append_to_sequence(files_map_xorurl.as_bytes(), xorname, type_tag, private)
                 .await?;
 
-            let new_version = current_version + 1;
-
             if update_nrs {
                 // We need to update the link in the NRS container as well,
                 // to link it to the new new_version of the FilesContainer we just generated
@@ -517,10 +975,8 @@ impl Safe {
                     .await?;
             }
 
-            new_version
-        };
-
-        Ok(version)
+            return Ok((new_version, files_map));
+        }
     }
 
     /// # Put a Public Blob
@@ -545,19 +1001,7 @@ impl Safe {
         media_type: Option<&str>,
         dry_run: bool,
     ) -> Result<XorUrl> {
-        let content_type = media_type.map_or_else(
-            || Ok(SafeContentType::Raw),
-            |media_type_str| {
-                if SafeUrl::is_media_type_supported(media_type_str) {
-                    Ok(SafeContentType::MediaType(media_type_str.to_string()))
-                } else {
-                    Err(Error::InvalidMediaType(format!(
-                        "Media-type '{}' not supported. You can pass 'None' as the 'media_type' for this content to be treated as raw",
-                        media_type_str
-                    )))
-                }
-            },
-        )?;
+        let content_type = blob_content_type(media_type)?;
 
         // TODO: do we want ownership from other PKs yet?
         let xorname = self.safe_client.store_public_blob(data, dry_run).await?;
@@ -567,8 +1011,8 @@ impl Safe {
         Ok(xorurl)
     }
 
-    /// # Get a Public Blob
-    /// Get blob from the network.
+    /// # Put a Private Blob
+    /// Put data blobs onto the network, encrypted and owned by this client.
     ///
     /// ## Example
     /// ```no_run
@@ -577,18 +1021,106 @@ impl Safe {
     /// # let rt = tokio::runtime::Runtime::new().unwrap();
     /// # rt.block_on(async {
     /// #   safe.connect(None, None, None).await.unwrap();
-    ///     let data = b"Something super good";
-    ///     let xorurl = safe.files_store_public_blob(data, None, false).await.unwrap();
-    ///     let received_data = safe.files_get_public_blob(&xorurl, None).await.unwrap();
+    ///     let data = b"Something super secret";
+    ///     let xorurl = safe.files_store_private_blob(data, Some("text/plain"), false).await.unwrap();
+    ///     let received_data = safe.files_get_private_blob(&xorurl, None).await.unwrap();
     ///     assert_eq!(received_data, data);
     /// # });
     /// ```
-    pub async fn files_get_public_blob(&mut self, url: &str, range: Range) -> Result<Vec<u8>> {
-        // TODO: do we want ownership from other PKs yet?
+    pub async fn files_store_private_blob(
+        &self,
+        data: &[u8],
+        media_type: Option<&str>,
+        dry_run: bool,
+    ) -> Result<XorUrl> {
+        let content_type = blob_content_type(media_type)?;
+
+        let xorname = self.safe_client.store_private_blob(data, dry_run).await?;
+
+        let xorurl = SafeUrl::encode(
+            xorname,
+            None,
+            0,
+            SafeDataType::PrivateBlob,
+            content_type,
+            None,
+            None,
+            None,
+            None,
+            None,
+            self.xorurl_base,
+        )?;
+
+        Ok(xorurl)
+    }
+
+    // Private helper to store either a Public or Private Blob
+    pub(crate) async fn files_store_blob(
+        &self,
+        data: &[u8],
+        media_type: Option<&str>,
+        private: bool,
+        dry_run: bool,
+    ) -> Result<XorUrl> {
+        if private {
+            self.files_store_private_blob(data, media_type, dry_run)
+                .await
+        } else {
+            self.files_store_public_blob(data, media_type, dry_run)
+                .await
+        }
+    }
+
+    /// # Get a Public Blob
+    /// Get blob from the network.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let data = b"Something super good";
+    ///     let xorurl = safe.files_store_public_blob(data, None, false).await.unwrap();
+    ///     let received_data = safe.files_get_public_blob(&xorurl, None).await.unwrap();
+    ///     assert_eq!(received_data, data);
+    /// # });
+    /// ```
+    pub async fn files_get_public_blob(&mut self, url: &str, range: Range) -> Result<Vec<u8>> {
+        // TODO: do we want ownership from other PKs yet?
         let (safe_url, _) = self.parse_and_resolve_url(url).await?;
         self.fetch_public_blob(&safe_url, range).await
     }
 
+    /// # Get a Private Blob
+    /// Get a private blob, owned by this client, from the network.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let data = b"Something super secret";
+    ///     let xorurl = safe.files_store_private_blob(data, None, false).await.unwrap();
+    ///     let received_data = safe.files_get_private_blob(&xorurl, None).await.unwrap();
+    ///     assert_eq!(received_data, data);
+    /// # });
+    /// ```
+    pub async fn files_get_private_blob(&mut self, url: &str, range: Range) -> Result<Vec<u8>> {
+        let (safe_url, _) = self.parse_and_resolve_url(url).await?;
+        if safe_url.data_type() != SafeDataType::PrivateBlob {
+            return Err(Error::InvalidInput(format!(
+                "The URL should target a '{}', but the URL provided targets a '{}'",
+                SafeDataType::PrivateBlob,
+                safe_url.data_type()
+            )));
+        }
+        self.fetch_blob(&safe_url, range).await
+    }
+
     /// Fetch an Blob from a SafeUrl without performing any type of URL resolution
     pub(crate) async fn fetch_public_blob(
         &self,
@@ -600,33 +1132,172 @@ impl Safe {
             .await
     }
 
-    // Private helper to serialise a FilesMap and store it in a Public Blob
-    async fn store_files_map(&mut self, files_map: &FilesMap) -> Result<String> {
+    /// Fetch a Blob, either public or private according to the
+    /// SafeUrl's data type, without performing any type of URL resolution
+    pub(crate) async fn fetch_blob(&self, safe_url: &SafeUrl, range: Range) -> Result<Vec<u8>> {
+        if safe_url.data_type() == SafeDataType::PrivateBlob {
+            self.safe_client
+                .get_private_blob(safe_url.xorname(), range)
+                .await
+        } else {
+            self.fetch_public_blob(safe_url, range).await
+        }
+    }
+
+    // Private helper to serialise a FilesMap and store it in a Blob, or in a tree of
+    // Blobs if it's sharded, which are private if the FilesContainer is private.
+    // For sharded FilesMaps, only the shards which changed since the previous
+    // version, if it's provided, are stored.
+    async fn store_files_map(
+        &mut self,
+        files_map: &FilesMap,
+        private: bool,
+        format: FilesMapFormat,
+        previous_files_map: Option<SafeUrl>,
+    ) -> Result<String> {
         // The FilesMapContainer is a Sequence where each NRS Map version is
         // an entry containing the XOR-URL of the Blob that contains the serialised NrsMap.
-        // TODO: use RDF format
-        let serialised_files_map = serde_json::to_string(&files_map).map_err(|err| {
-            Error::Serialisation(format!(
-                "Couldn't serialise the FilesMap generated: {:?}",
-                err
-            ))
-        })?;
+        // The format is recorded in the XOR-URL so readers know how to deserialise it.
+        let files_map_xorurl = match format.layout {
+            FilesMapLayout::Single => {
+                let serialised_files_map = serialise_files_map(files_map, format.encoding)?;
+                self.files_store_blob(&serialised_files_map, None, private, false)
+                    .await?
+            }
+            FilesMapLayout::Sharded => {
+                store_sharded_files_map(
+                    self,
+                    files_map,
+                    private,
+                    format.encoding,
+                    previous_files_map,
+                )
+                .await?
+            }
+        };
+
+        let mut files_map_url = SafeUrl::from_url(&files_map_xorurl)?;
+        format.set_in_files_map_link(&mut files_map_url)?;
+
+        Ok(files_map_url.to_string())
+    }
+
+    // Private helper to store a new FilesContainer with the FilesMap provided as its first version
+    async fn store_new_files_container(
+        &mut self,
+        files_map: &FilesMap,
+        private: bool,
+    ) -> Result<XorUrl> {
+        // Store the serialised FilesMap in a Public (or Private) Blob,
+        // using the encoding and layout currently set for new FilesContainers
+        let format = self.default_files_map_format();
         let files_map_xorurl = self
-            .files_store_public_blob(serialised_files_map.as_bytes(), None, false)
+            .store_files_map(files_map, private, format, None)
+            .await?;
+
+        // Store the FilesContainer in a Public (or Private) Sequence, putting the
+        // serialised FilesMap XOR-URL as the first entry value
+        let xorname = self
+            .safe_client
+            .store_sequence(
+                files_map_xorurl.as_bytes(),
+                None,
+                FILES_CONTAINER_TYPE_TAG,
+                None,
+                private,
+            )
             .await?;
 
-        Ok(files_map_xorurl)
+        let xorurl = SafeUrl::encode_sequence_data(
+            xorname,
+            FILES_CONTAINER_TYPE_TAG,
+            SafeContentType::FilesContainer,
+            self.xorurl_base,
+            private,
+        )?;
+
+        Ok(xorurl)
+    }
+
+    // Private helper to fetch a FilesMap stored as a complete snapshot, in a
+    // single Blob or in a tree of shards, and deserialise it
+    async fn fetch_files_map_snapshot(
+        &self,
+        files_map_xorurl: &SafeUrl,
+        format: FilesMapFormat,
+    ) -> Result<FilesMap> {
+        match format.layout {
+            FilesMapLayout::Single => {
+                let serialised_files_map = self.fetch_blob(files_map_xorurl, None).await?;
+                deserialise_files_map(&serialised_files_map, format.encoding)
+            }
+            FilesMapLayout::Sharded => {
+                ShardsLoader::new(files_map_xorurl.clone(), format.encoding)
+                    .load_all(self)
+                    .await
+            }
+        }
+    }
+
+    // Format currently set for the FilesMaps of new FilesContainers
+    fn default_files_map_format(&self) -> FilesMapFormat {
+        FilesMapFormat {
+            encoding: self.files_config.files_map_encoding,
+            layout: self.files_config.files_map_layout,
+            delta_base: None,
+        }
     }
 }
 
 // Helper functions
 
+// Parse the link to a FilesMap stored as an entry of the FilesContainer's Sequence,
+// returning its XOR-URL and the format it was stored with
+fn parse_files_map_link(entry: Vec<u8>) -> Result<(SafeUrl, FilesMapFormat)> {
+    let files_map_xorurl = SafeUrl::from_url(&String::from_utf8(entry).map_err(|err| {
+        Error::ContentError(format!(
+            "Couldn't parse the FilesMap link stored in the FilesContainer: {:?}",
+            err
+        ))
+    })?)?;
+    let format = FilesMapFormat::from_files_map_link(&files_map_xorurl)?;
+
+    Ok((files_map_xorurl, format))
+}
+
+// Returns true if the FilesContainer is stored on a Private Sequence or Register,
+// in which case its files and FilesMaps are all stored as Private Blobs
+fn is_private_files_container(safe_url: &SafeUrl) -> bool {
+    matches!(
+        safe_url.data_type(),
+        SafeDataType::PrivateSequence | SafeDataType::PrivateRegister
+    )
+}
+
+// Obtain the content type to encode in a Blob XOR-URL from the media type provided
+fn blob_content_type(media_type: Option<&str>) -> Result<SafeContentType> {
+    media_type.map_or_else(
+        || Ok(SafeContentType::Raw),
+        |media_type_str| {
+            if SafeUrl::is_media_type_supported(media_type_str) {
+                Ok(SafeContentType::MediaType(media_type_str.to_string()))
+            } else {
+                Err(Error::InvalidMediaType(format!(
+                    "Media-type '{}' not supported. You can pass 'None' as the 'media_type' for this content to be treated as raw",
+                    media_type_str
+                )))
+            }
+        },
+    )
+}
+
 // Make sure the input params are valid for a files_container_add operation
 async fn validate_files_add_params(
     safe: &mut Safe,
     source_file: &str,
     url: &str,
     update_nrs: bool,
+    expected_version: ExpectedVersion,
 ) -> Result<(SafeUrl, u64, FilesMap)> {
     let safe_url = Safe::parse_url(url)?;
     if safe_url.content_version().is_some() {
@@ -649,18 +1320,22 @@ async fn validate_files_add_params(
     // the version from it so we can fetch latest version of it for sync-ing
     safe_url.set_content_version(None);
 
-    let (current_version, current_files_map): (u64, FilesMap) =
-        safe.fetch_files_container(&safe_url).await?;
+    let (current_version, current_files_map): (u64, FilesMap) = safe
+        .fetch_files_container_for_update(&safe_url, expected_version)
+        .await?;
 
     let dest_path = safe_url.path().to_string();
 
     // Let's act according to if it's a local file path or a safe:// location
     if source_file.starts_with("safe://") {
         let source_safe_url = Safe::parse_url(source_file)?;
-        if source_safe_url.data_type() != SafeDataType::PublicBlob {
+        if source_safe_url.data_type() != SafeDataType::PublicBlob
+            && source_safe_url.data_type() != SafeDataType::PrivateBlob
+        {
             return Err(Error::InvalidInput(format!(
-                "The source URL should target a file ('{}'), but the URL provided targets a '{}'",
+                "The source URL should target a file ('{}' or '{}'), but the URL provided targets a '{}'",
                 SafeDataType::PublicBlob,
+                SafeDataType::PrivateBlob,
                 source_safe_url.content_type()
             )));
         }
@@ -714,6 +1389,27 @@ fn get_base_paths(location: &str, dest_path: Option<&str>) -> (String, String) {
     (location_base_path, dest_base_path)
 }
 
+// Path within the FilesContainer a local file path is mapped to, given the base paths
+fn files_map_path_from_local(
+    local_file_name: &str,
+    location_base_path: &str,
+    dest_base_path: &str,
+) -> String {
+    let file_name =
+        RelativePath::new(&local_file_name.replace(location_base_path, dest_base_path)).normalize();
+    // Above normalize removes initial slash, and uses '\' if it's on Windows
+    // here, we trim any trailing '/', as it could be a filename.
+    let normalised_file_name = format!("/{}", normalise_path_separator(file_name.as_str()))
+        .trim_end_matches('/')
+        .to_string();
+
+    if normalised_file_name.is_empty() {
+        "/".to_string()
+    } else {
+        normalised_file_name
+    }
+}
+
 // From the provided list of local files paths, find the local changes made in comparison with the
 // target FilesContainer, uploading new files as necessary, and creating a new FilesMap with file's
 // metadata and their corresponding links, as well as generating the report of processed files
@@ -725,10 +1421,11 @@ async fn files_map_sync(
     new_content: ProcessedFiles,
     dest_path: Option<&str>,
     delete: bool,
+    private: bool,
     dry_run: bool,
     force: bool,
-    compare_file_content: bool,
-    follow_links: bool,
+    comparison: Option<FilesComparison>,
+    symlinks: SymlinkPolicy,
 ) -> Result<(ProcessedFiles, FilesMap, u64)> {
     let (location_base_path, dest_base_path) = get_base_paths(location, dest_path);
     let mut updated_files_map = FilesMap::new();
@@ -740,22 +1437,8 @@ async fn files_map_sync(
         .filter(|(_, (change, _))| change != CONTENT_ERROR_SIGN)
     {
         let file_path = Path::new(&local_file_name);
-
-        let file_name = RelativePath::new(
-            &local_file_name
-                .to_string()
-                .replace(&location_base_path, &dest_base_path),
-        )
-        .normalize();
-        // Above normalize removes initial slash, and uses '\' if it's on Windows
-        // here, we trim any trailing '/', as it could be a filename.
-        let mut normalised_file_name = format!("/{}", normalise_path_separator(file_name.as_str()))
-            .trim_end_matches('/')
-            .to_string();
-
-        if normalised_file_name.is_empty() {
-            normalised_file_name = "/".to_string();
-        }
+        let normalised_file_name =
+            files_map_path_from_local(local_file_name, &location_base_path, &dest_base_path);
 
         // Let's update FileItem if there is a change or it doesn't exist in current_files_map
         match current_files_map.get(&normalised_file_name) {
@@ -766,9 +1449,13 @@ async fn files_map_sync(
                     local_file_name,
                     &normalised_file_name,
                     file_path,
-                    &FileMeta::from_path(local_file_name, follow_links)?,
+                    &FileMeta::from_path(
+                        local_file_name,
+                        follow_symlink(symlinks, file_path, location),
+                    )?,
                     None, // no xorurl link
                     false,
+                    private,
                     dry_run,
                     &mut updated_files_map,
                     &mut processed_files,
@@ -797,18 +1484,56 @@ async fn files_map_sync(
                 }
             }
             Some(file_item) => {
-                let is_modified =
-                    is_file_item_modified(safe, Path::new(local_file_name), file_item).await;
-                if force || (compare_file_content && is_modified) {
-                    // We need to update the current FileItem
+                let change = match file_item_change(
+                    safe,
+                    Path::new(local_file_name),
+                    file_item,
+                    follow_symlink(symlinks, file_path, location),
+                    private,
+                    comparison.unwrap_or_default(),
+                )
+                .await
+                {
+                    Ok(change) => change,
+                    Err(err) => {
+                        // The local file couldn't be compared with its FileItem,
+                        // thus the current one is kept as it is
+                        processed_files.insert(
+                            local_file_name.to_string(),
+                            (CONTENT_ERROR_SIGN.to_string(), format!("<{}>", err)),
+                        );
+                        info!("Skipping file \"{}\". {}", local_file_name, err);
+                        updated_files_map
+                            .insert(normalised_file_name.to_string(), file_item.clone());
+                        keep_processed_path(
+                            &mut current_files_map,
+                            &mut updated_files_map,
+                            &normalised_file_name,
+                        );
+                        continue;
+                    }
+                };
+                let is_modified = change != FileItemChange::Unchanged;
+                if force || (comparison.is_some() && is_modified) {
+                    // We need to update the current FileItem. If only its metadata changed
+                    // we keep the current link so the content is not uploaded again
+                    let file_link = if !force && change == FileItemChange::Metadata {
+                        file_item.link()
+                    } else {
+                        None
+                    };
                     if add_or_update_file_item(
                         safe,
                         local_file_name,
                         &normalised_file_name,
                         file_path,
-                        &FileMeta::from_path(local_file_name, follow_links)?,
-                        None, // no xorurl link
+                        &FileMeta::from_path(
+                            local_file_name,
+                            follow_symlink(symlinks, file_path, location),
+                        )?,
+                        file_link,
                         true,
+                        private,
                         dry_run,
                         &mut updated_files_map,
                         &mut processed_files,
@@ -816,12 +1541,21 @@ async fn files_map_sync(
                     .await
                     {
                         success_count += 1;
+
+                        // The content is the same, and so is its checksum
+                        if file_link.is_some() {
+                            if let Some(new_file_item) =
+                                updated_files_map.get_mut(&normalised_file_name)
+                            {
+                                new_file_item.checksum = file_item.checksum.clone();
+                            }
+                        }
                     }
                 } else {
                     // No need to update FileItem just copy the existing one
                     updated_files_map.insert(normalised_file_name.to_string(), file_item.clone());
 
-                    if !force && !compare_file_content {
+                    if !force && comparison.is_none() {
                         let comp_str = if is_modified { "different" } else { "same" };
                         processed_files.insert(
                             local_file_name.to_string(),
@@ -837,26 +1571,11 @@ async fn files_map_sync(
                     }
                 }
 
-                // let's now remove it from the current list so we now it has been processed
-                current_files_map.remove(&normalised_file_name);
-
-                // We also remove any parent directories
-                // from the current list, so they will not be deleted.
-                let mut trail = Vec::<&str>::new();
-                for part in normalised_file_name.split('/') {
-                    trail.push(part);
-                    let ancestor = if trail.len() > 1 {
-                        trail.join("/")
-                    } else {
-                        "/".to_string()
-                    };
-                    if ancestor != normalised_file_name {
-                        if let Some(fi) = current_files_map.get(&ancestor) {
-                            updated_files_map.insert(ancestor.clone(), fi.clone());
-                            current_files_map.remove(&ancestor);
-                        }
-                    }
-                }
+                keep_processed_path(
+                    &mut current_files_map,
+                    &mut updated_files_map,
+                    &normalised_file_name,
+                );
             }
         }
     }
@@ -873,10 +1592,7 @@ async fn files_map_sync(
                     CONTENT_DELETED_SIGN.to_string(),
                     // note: files have link property,
                     //       dirs and symlinks do not
-                    file_item
-                        .get(PREDICATE_LINK)
-                        .unwrap_or(&String::default())
-                        .to_string(),
+                    file_item.link().unwrap_or_default().to_string(),
                 ),
             );
             success_count += 1;
@@ -886,32 +1602,98 @@ async fn files_map_sync(
     Ok((processed_files, updated_files_map, success_count))
 }
 
-async fn is_file_item_modified(
+// Remove a path processed from the current list so we know it has been processed,
+// as well as any parent directories, keeping them so they will not be deleted.
+fn keep_processed_path(
+    current_files_map: &mut FilesMap,
+    updated_files_map: &mut FilesMap,
+    file_name: &str,
+) {
+    current_files_map.remove(file_name);
+
+    let mut trail = Vec::<&str>::new();
+    for part in file_name.split('/') {
+        trail.push(part);
+        let ancestor = if trail.len() > 1 {
+            trail.join("/")
+        } else {
+            "/".to_string()
+        };
+        if ancestor != file_name {
+            if let Some(fi) = current_files_map.remove(&ancestor) {
+                updated_files_map.insert(ancestor, fi);
+            }
+        }
+    }
+}
+
+// Compare a local file with its FileItem according to the comparison policy provided,
+// returning whether the content or only the metadata has changed. It fails if the
+// local file cannot be read, rather than reporting it as unchanged.
+async fn file_item_change(
     safe: &mut Safe,
     local_filename: &Path,
     file_item: &FileItem,
-) -> bool {
-    if FileMeta::filetype_is_file(&file_item[PREDICATE_TYPE]) {
-        match upload_file_to_net(safe, local_filename, true /* dry-run */).await {
-            Ok(local_xorurl) => file_item[PREDICATE_LINK] != local_xorurl,
-            Err(_err) => false,
+    follow_links: bool,
+    private: bool,
+    comparison: FilesComparison,
+) -> Result<FileItemChange> {
+    let local_meta = FileMeta::from_path(&local_filename.display().to_string(), follow_links)?;
+    let current_meta = FileMeta::from_file_item(file_item);
+
+    // a file replaced by a directory or symlink, or vice versa, is always a change
+    if local_meta.file_type != current_meta.file_type
+        && !(local_meta.is_file() && current_meta.is_file())
+    {
+        return Ok(FileItemChange::Content);
+    }
+
+    let content_changed = if local_meta.is_file() {
+        if comparison == FilesComparison::ModifiedTime
+            && local_meta.same_size_and_mtime(&current_meta)
+        {
+            false
+        } else {
+            let (local_xorurl, _) =
+                upload_file_to_net(safe, local_filename, private, true /* dry-run */).await?;
+            file_item.link() != Some(local_xorurl.as_str())
         }
+    } else if local_meta.is_symlink() {
+        let target = fs::read_link(local_filename).map_err(|err| {
+            Error::InvalidInput(format!(
+                "Failed to read the target of the symlink '{}': {}",
+                local_filename.display(),
+                err
+            ))
+        })?;
+        let local_target = normalise_path_separator(&target.display().to_string());
+        file_item.symlink_target() != Some(local_target.as_str())
     } else {
-        // for now, we just return false if a symlink or directory.
-        // In the future, should check if symlink has been modified.
-        // Also, could check if ctime or mtime is different, though that
-        // could apply to files as well, and some use-cases would not want to
-        // sync remotely if actual content has not changed.  So it should
-        // probably be a user flag to enable.
+        // directories have no content to compare
         false
-    }
+    };
+
+    let change = if content_changed {
+        FileItemChange::Content
+    } else if comparison != FilesComparison::ContentOnly
+        && local_meta.metadata_differs(&current_meta, comparison == FilesComparison::ModifiedTime)
+    {
+        FileItemChange::Metadata
+    } else {
+        FileItemChange::Unchanged
+    };
+
+    Ok(change)
 }
 
+// Add a link to a file to the FilesMap. The media type of the FileItem is the one encoded
+// in the link unless it's provided, e.g. if it's not supported by the XOR-URL encoding.
 async fn files_map_add_link(
     safe: &mut Safe,
     mut files_map: FilesMap,
     file_link: &str,
     file_name: &str,
+    media_type: Option<&str>,
     force: bool,
 ) -> Result<(ProcessedFiles, FilesMap, u64)> {
     let mut processed_files = ProcessedFiles::new();
@@ -927,25 +1709,30 @@ async fn files_map_add_link(
         }
         Ok(safe_url) => {
             let file_path = Path::new("");
-            let file_type = match safe_url.content_type() {
-                SafeContentType::MediaType(media_type) => media_type,
-                other => format!("{}", other),
+            let file_type = match (media_type, safe_url.content_type()) {
+                (Some(media_type), _) => media_type.to_string(),
+                (None, SafeContentType::MediaType(media_type)) => media_type,
+                (None, other) => format!("{}", other),
             };
-            let file_size = ""; // unknown
+            let file_size = 0; // unknown
 
             // Let's update FileItem if the link is different or it doesn't exist in the files_map
             match files_map.get(file_name) {
                 Some(current_file_item) => {
-                    let mut file_meta = FileMeta::from_file_item(current_file_item);
-                    file_meta.file_type = file_type;
-                    file_meta.file_size = file_size.to_string();
-
-                    let is_modified = if file_meta.is_file() {
-                        current_file_item[PREDICATE_LINK] != file_link
+                    let current_meta = FileMeta::from_file_item(current_file_item);
+
+                    // A directory or symlink being replaced by a file link is always a change,
+                    // and in such case we don't keep its metadata for the new FileItem
+                    let (is_modified, file_meta) = if current_meta.is_file() {
+                        let mut file_meta = current_meta;
+                        file_meta.file_type = file_type;
+                        file_meta.file_size = file_size;
+                        let is_modified = current_file_item
+                            .link()
+                            .map_or(true, |current_link| current_link != file_link);
+                        (is_modified, file_meta)
                     } else {
-                        // directory: nothing to check.
-                        // symlink: TODO: check if sym-link path has changed.
-                        false
+                        (true, FileMeta::from_type_and_size(&file_type, file_size))
                     };
 
                     if is_modified {
@@ -958,6 +1745,7 @@ async fn files_map_add_link(
                                 &file_meta,
                                 Some(file_link),
                                 true,
+                                false,
                                 true,
                                 &mut files_map,
                                 &mut processed_files,
@@ -993,6 +1781,7 @@ async fn files_map_add_link(
                         &FileMeta::from_type_and_size(&file_type, file_size),
                         Some(file_link),
                         false,
+                        false,
                         true,
                         &mut files_map,
                         &mut processed_files,
@@ -1034,10 +1823,7 @@ fn files_map_remove_path(
                         CONTENT_DELETED_SIGN.to_string(),
                         // note: files have link property,
                         //       dirs and symlinks do not
-                        file_item
-                            .get(PREDICATE_LINK)
-                            .unwrap_or(&String::default())
-                            .to_string(),
+                        file_item.link().unwrap_or_default().to_string(),
                     ),
                 );
                 success_count += 1;
@@ -1059,10 +1845,7 @@ fn files_map_remove_path(
                 CONTENT_DELETED_SIGN.to_string(),
                 // note: files have link property,
                 //       dirs and symlinks do not
-                file_item
-                    .get(PREDICATE_LINK)
-                    .unwrap_or(&String::default())
-                    .to_string(),
+                file_item.link().unwrap_or_default().to_string(),
             ),
         );
         (1, files_map)
@@ -1071,64 +1854,397 @@ fn files_map_remove_path(
     Ok((processed_files, new_files_map, success_count))
 }
 
-// Upload a files to the Network as a Public Blob
-async fn upload_file_to_net(safe: &mut Safe, path: &Path, dry_run: bool) -> Result<XorUrl> {
-    let data = fs::read(path).map_err(|err| {
-        Error::InvalidInput(format!("Failed to read file from local location: {}", err))
-    })?;
+// Copy the FileItems found at a path of a FilesMap into another FilesMap, keeping their links
+fn files_map_copy(
+    src_files_map: &FilesMap,
+    src_path: &str,
+    mut files_map: FilesMap,
+    dest_path: &str,
+    recursive: bool,
+    force: bool,
+) -> Result<(ProcessedFiles, FilesMap, u64)> {
+    let src = if src_path.is_empty() || src_path == "/" {
+        "/".to_string()
+    } else {
+        src_files_map.realpath(&normalise_files_map_path(src_path))?
+    };
+    let src = normalise_files_map_path(&src);
 
-    let mime_type = mime_guess::from_path(&path);
-    match safe
-        .files_store_public_blob(&data, mime_type.first_raw(), dry_run)
-        .await
-    {
-        Ok(xorurl) => Ok(xorurl),
-        Err(err) => {
-            // Let's then upload it and set media-type to be simply raw content
-            if let Error::InvalidMediaType(_) = err {
-                safe.files_store_public_blob(&data, None, dry_run).await
-            } else {
-                Err(err)
-            }
-        }
+    let src_is_file = src_files_map
+        .get(&src)
+        .map_or(false, |file_item| !file_item.is_dir());
+    if !src_is_file && !recursive {
+        return Err(Error::InvalidInput(format!(
+            "\"{}\" is a directory, the 'recursive' flag is needed to copy it",
+            src
+        )));
     }
-}
 
-// From the provided list of local files paths and corresponding files XOR-URLs,
-// create a FilesMap with file's metadata and their corresponding links
-async fn files_map_create(
-    safe: &mut Safe,
-    mut content: &mut ProcessedFiles,
-    location: &str,
-    dest_path: Option<&str>,
-    follow_links: bool,
-    dry_run: bool,
-) -> Result<FilesMap> {
-    let mut files_map = FilesMap::default();
+    // If the destination ends with '/' we copy the source into it,
+    // otherwise the destination is the new name for the source
+    let dest = if dest_path.is_empty() || dest_path.ends_with('/') {
+        let src_name = src.rsplit('/').next().unwrap_or("");
+        normalise_files_map_path(&format!("{}/{}", dest_path, src_name))
+    } else {
+        normalise_files_map_path(dest_path)
+    };
 
-    let (location_base_path, dest_base_path) = get_base_paths(location, dest_path);
+    let items_to_copy = src_files_map
+        .iter()
+        .filter(|(path, _)| src == "/" || is_same_or_subpath(path, &src))
+        .map(|(path, file_item)| {
+            let new_path = if src == "/" {
+                format!("{}{}", dest.trim_end_matches('/'), path)
+            } else {
+                format!("{}{}", dest, &path[src.len()..])
+            };
+            (new_path, file_item)
+        })
+        .collect::<Vec<_>>();
+    if items_to_copy.is_empty() {
+        return Err(Error::ContentError(format!(
+            "No content found matching the \"{}\" path on the source FilesContainer",
+            src
+        )));
+    }
 
-    // We want to iterate over the BTreeMap and also modify it.
-    // We DON'T want to clone/dup the whole thing, might be very big.
-    // Rust doesn't allow that exactly, but we can get the keys
-    // to iterate over instead.  Cloning the keys isn't ideal
-    // either, but is much less data.  Is there a more efficient way?
-    let keys = content.keys().cloned().collect::<Vec<_>>();
-    for file_name in keys {
-        let (change, link) = &content[&file_name].clone();
+    let mut processed_files = ProcessedFiles::new();
+    let mut success_count = 0;
+    for (new_path, file_item) in items_to_copy {
+        // note: files have link property,
+        //       dirs and symlinks do not
+        let link = file_item.link().unwrap_or_default().to_string();
+        match files_map.get(&new_path) {
+            None => {
+                processed_files.insert(new_path.clone(), (CONTENT_ADDED_SIGN.to_string(), link));
+                files_map.insert(new_path, file_item.clone());
+                success_count += 1;
+            }
+            Some(current_file_item) => {
+                if is_same_file_item_content(current_file_item, file_item) {
+                    // directories are merged, for anything else there is nothing to copy
+                    if !file_item.is_dir() {
+                        processed_files.insert(
+                            new_path.clone(),
+                            (
+                                CONTENT_ERROR_SIGN.to_string(),
+                                format!(
+                                    "File named \"{}\" already exists on target with same link",
+                                    new_path
+                                ),
+                            ),
+                        );
+                        info!("Skipping file \"{}\" since it already exists on target with the same link", new_path);
+                    }
+                } else if force {
+                    processed_files
+                        .insert(new_path.clone(), (CONTENT_UPDATED_SIGN.to_string(), link));
+                    files_map.insert(new_path, file_item.clone());
+                    success_count += 1;
+                } else {
+                    processed_files.insert(new_path.clone(), (CONTENT_ERROR_SIGN.to_string(), format!("File named \"{}\" already exists on target. Use the 'force' flag to replace it", new_path)));
+                    info!("Skipping file \"{}\" since it already exists on target. You can use the 'force' flag to replace the existing file with the new one", new_path);
+                }
+            }
+        }
+    }
 
-        if change == CONTENT_ERROR_SIGN {
-            continue;
+    // Create the parent directories of the content copied which don't exist yet
+    let copied_paths: Vec<String> = processed_files
+        .iter()
+        .filter(|(_, (change, _))| change != CONTENT_ERROR_SIGN)
+        .map(|(path, _)| path.clone())
+        .collect();
+    for dir in copied_paths.iter().flat_map(|path| ancestors(path)) {
+        if !files_map.contains_key(&dir) {
+            let file_meta =
+                FileMeta::from_attributes(MIMETYPE_FILESYSTEM_DIR, 0, &FileAttributes::default());
+            files_map.insert(dir.clone(), file_meta.to_file_item(FileItemType::Dir));
+            processed_files.insert(dir, (CONTENT_ADDED_SIGN.to_string(), String::default()));
+            success_count += 1;
         }
+    }
 
-        let new_file_name = RelativePath::new(
-            &file_name
-                .to_string()
-                .replace(&location_base_path, &dest_base_path),
-        )
-        .normalize();
+    Ok((processed_files, files_map, success_count))
+}
 
-        // Above normalize removes initial slash, and uses '\' if it's on Windows
+// Returns true if both FileItems are of the same type and refer to the same content,
+// i.e. same link for files, same target for symlinks, or are both directories
+fn is_same_file_item_content(file_item: &FileItem, other: &FileItem) -> bool {
+    file_item.file_type == other.file_type
+}
+
+// Move a path, and everything under it if it's a directory, to a new location in the FilesMap
+fn files_map_move_path(
+    from_path: &str,
+    to_path: &str,
+    mut files_map: FilesMap,
+    force: bool,
+) -> Result<(ProcessedFiles, FilesMap, u64)> {
+    let from = normalise_files_map_path(from_path);
+    let to = normalise_files_map_path(to_path);
+    if from == "/" || to == "/" {
+        return Err(Error::InvalidInput(
+            "The root of a FilesContainer cannot be moved, nor be replaced".to_string(),
+        ));
+    }
+    if from == to {
+        return Err(Error::InvalidInput(format!(
+            "The source and destination paths are the same: \"{}\"",
+            from
+        )));
+    }
+    if is_same_or_subpath(&to, &from) {
+        return Err(Error::InvalidInput(format!(
+            "Cannot move \"{}\" into a subpath of itself: \"{}\"",
+            from, to
+        )));
+    }
+    if is_same_or_subpath(&from, &to) {
+        return Err(Error::InvalidInput(format!(
+            "Cannot move \"{}\" to replace one of its parent paths: \"{}\"",
+            from, to
+        )));
+    }
+
+    let paths_to_move = files_map
+        .keys()
+        .filter(|path| is_same_or_subpath(path, &from))
+        .cloned()
+        .collect::<Vec<_>>();
+    if paths_to_move.is_empty() {
+        return Err(Error::ContentError(format!(
+            "No content found matching the \"{}\" path on the target FilesContainer",
+            from
+        )));
+    }
+
+    let mut processed_files = ProcessedFiles::default();
+    let mut success_count = 0;
+
+    // Anything found at the destination is replaced only if we were asked to
+    let paths_to_replace = files_map
+        .keys()
+        .filter(|path| is_same_or_subpath(path, &to))
+        .cloned()
+        .collect::<Vec<_>>();
+    if !paths_to_replace.is_empty() {
+        if !force {
+            return Err(Error::EntryExists(format!(
+                "Content already exists at the \"{}\" path on the target FilesContainer. Use the 'force' flag to replace it",
+                to
+            )));
+        }
+        for path in paths_to_replace {
+            if let Some(file_item) = files_map.remove(&path) {
+                processed_files.insert(
+                    path,
+                    (
+                        CONTENT_DELETED_SIGN.to_string(),
+                        file_item.link().unwrap_or_default().to_string(),
+                    ),
+                );
+                success_count += 1;
+            }
+        }
+    }
+
+    // Keep track of the original location of each moved entry so
+    // relative symlinks can be resolved from where they were
+    let mut original_paths = BTreeMap::new();
+    for old_path in paths_to_move {
+        if let Some(file_item) = files_map.remove(&old_path) {
+            let new_path = format!("{}{}", to, &old_path[from.len()..]);
+            // note: files have link property,
+            //       dirs and symlinks do not
+            let link = file_item.link().unwrap_or_default().to_string();
+            processed_files.insert(
+                old_path.clone(),
+                (CONTENT_DELETED_SIGN.to_string(), link.clone()),
+            );
+            processed_files.insert(new_path.clone(), (CONTENT_ADDED_SIGN.to_string(), link));
+            files_map.insert(new_path.clone(), file_item);
+            original_paths.insert(new_path, old_path);
+            success_count += 1;
+        }
+    }
+
+    // Fix up any relative symlink which target was moved, or which was moved itself
+    for (path, file_item) in files_map.iter_mut() {
+        let target = match file_item.symlink_target() {
+            Some(target) if !target.is_empty() && !target.starts_with('/') => target.to_string(),
+            _ => continue,
+        };
+        let original_path = original_paths.get(path).unwrap_or(path);
+        let abs_target = match symlink_target_abs_path(original_path, &target) {
+            Some(abs_target) => abs_target,
+            // it points beyond the root of the FilesContainer so we cannot fix it
+            None => continue,
+        };
+        // Symlinks not moved and pointing to something not moved are left untouched,
+        // even if their target could be written in a simpler way
+        let target_moved = is_same_or_subpath(&abs_target, &from);
+        if !target_moved && !original_paths.contains_key(path) {
+            continue;
+        }
+        let new_abs_target = if target_moved {
+            format!("{}{}", to, &abs_target[from.len()..])
+        } else {
+            abs_target
+        };
+        // The target is kept as it is if it still resolves to the right path,
+        // e.g. when the symlink was moved together with its target
+        if symlink_target_abs_path(path, &target).as_ref() == Some(&new_abs_target) {
+            continue;
+        }
+        let new_target = relative_symlink_target(path, &new_abs_target);
+        if new_target != target {
+            debug!(
+                "Symlink at \"{}\" updated to target \"{}\" instead of \"{}\"",
+                path, new_target, target
+            );
+            if let FileItemType::Symlink { target, .. } = &mut file_item.file_type {
+                *target = new_target;
+            }
+            if !processed_files.contains_key(path) {
+                processed_files.insert(
+                    path.to_string(),
+                    (CONTENT_UPDATED_SIGN.to_string(), String::default()),
+                );
+                success_count += 1;
+            }
+        }
+    }
+
+    Ok((processed_files, files_map, success_count))
+}
+
+// Make sure a path within a FilesContainer starts with '/' and doesn't end with one
+fn normalise_files_map_path(path: &str) -> String {
+    let path = normalise_path_separator(path);
+    let trimmed = path.trim_matches('/');
+    format!("/{}", trimmed)
+}
+
+// Returns true if the path is the same as the base path or it's found under it
+fn is_same_or_subpath(path: &str, base_path: &str) -> bool {
+    path == base_path || path.starts_with(&format!("{}/", base_path.trim_end_matches('/')))
+}
+
+// Set the type of the target of the symlinks which point to FileItems found in the FilesMap
+fn resolve_symlinks_target_type(files_map: &mut FilesMap) {
+    let targets: Vec<(String, SymlinkTargetType)> = files_map
+        .iter()
+        .filter_map(|(path, file_item)| {
+            let target = file_item.symlink_target()?;
+            let target_type = symlink_target_abs_path(path, target)
+                .and_then(|abs_target| files_map.get(&abs_target))
+                .map_or(SymlinkTargetType::Unknown, |target_item| {
+                    if target_item.is_dir() {
+                        SymlinkTargetType::Dir
+                    } else {
+                        SymlinkTargetType::File
+                    }
+                });
+            Some((path.clone(), target_type))
+        })
+        .collect();
+
+    for (path, new_target_type) in targets {
+        if let Some(FileItemType::Symlink { target_type, .. }) = files_map
+            .get_mut(&path)
+            .map(|file_item| &mut file_item.file_type)
+        {
+            *target_type = new_target_type;
+        }
+    }
+}
+
+// Resolve a relative symlink target from the symlink's path, returning the absolute
+// path within the FilesContainer, or None if it ascends beyond the root
+fn symlink_target_abs_path(symlink_path: &str, target: &str) -> Option<String> {
+    let mut parts = symlink_path
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+    // the target is relative to the folder containing the symlink
+    parts.pop();
+    for part in target.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            other => parts.push(other),
+        }
+    }
+    Some(format!("/{}", parts.join("/")))
+}
+
+// Generate the relative path to reach an absolute target path from a symlink's path
+fn relative_symlink_target(symlink_path: &str, abs_target: &str) -> String {
+    let mut base = symlink_path
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+    base.pop();
+    let target = abs_target
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+
+    let common = base
+        .iter()
+        .zip(target.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut relative = vec![".."; base.len() - common];
+    relative.extend_from_slice(&target[common..]);
+
+    if relative.is_empty() {
+        ".".to_string()
+    } else {
+        relative.join("/")
+    }
+}
+
+// From the provided list of local files paths and corresponding files XOR-URLs,
+// create a FilesMap with file's metadata, their corresponding links and checksums
+async fn files_map_create(
+    safe: &mut Safe,
+    mut content: &mut ProcessedFiles,
+    mut checksums: FileChecksums,
+    location: &str,
+    dest_path: Option<&str>,
+    symlinks: SymlinkPolicy,
+    private: bool,
+    dry_run: bool,
+) -> Result<FilesMap> {
+    let mut files_map = FilesMap::default();
+
+    let (location_base_path, dest_base_path) = get_base_paths(location, dest_path);
+
+    // We want to iterate over the BTreeMap and also modify it.
+    // We DON'T want to clone/dup the whole thing, might be very big.
+    // Rust doesn't allow that exactly, but we can get the keys
+    // to iterate over instead.  Cloning the keys isn't ideal
+    // either, but is much less data.  Is there a more efficient way?
+    let keys = content.keys().cloned().collect::<Vec<_>>();
+    for file_name in keys {
+        let (change, link) = &content[&file_name].clone();
+
+        if change == CONTENT_ERROR_SIGN {
+            continue;
+        }
+
+        let new_file_name = RelativePath::new(
+            &file_name
+                .to_string()
+                .replace(&location_base_path, &dest_base_path),
+        )
+        .normalize();
+
+        // Above normalize removes initial slash, and uses '\' if it's on Windows
         // here, we trim any trailing '/', as it could be a filename.
         let final_name = format!("/{}", normalise_path_separator(new_file_name.as_str()))
             .trim_end_matches('/')
@@ -1136,32 +2252,52 @@ async fn files_map_create(
 
         debug!("FileItem item name: {:?}", &file_name);
 
-        add_or_update_file_item(
+        let added = add_or_update_file_item(
             safe,
             &file_name,
             &final_name,
             Path::new(&file_name),
-            &FileMeta::from_path(&file_name, follow_links)?,
+            &FileMeta::from_path(
+                &file_name,
+                follow_symlink(symlinks, Path::new(&file_name), location),
+            )?,
             if link.is_empty() { None } else { Some(link) },
             false,
+            private,
             dry_run,
             &mut files_map,
             &mut content,
         )
         .await;
+
+        // The checksums were computed when the files were uploaded
+        if let (true, Some(checksum)) = (added, checksums.remove(&file_name)) {
+            if let Some(file_item) = files_map.get_mut(&final_name) {
+                file_item.checksum = Some(checksum);
+            }
+        }
     }
     Ok(files_map)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use super::{
+        files_map::{deserialise_files_map_delta, serialise_files_map_delta, FilesMapDelta},
+        *,
+    };
     use crate::{
-        app::test_helpers::{new_safe_instance, random_nrs_name},
+        app::{
+            helpers::gen_datetime_secs,
+            test_helpers::{new_safe_instance, random_nrs_name},
+            RdfFormat, ToRdf,
+        },
+        fetch::SafeData,
         retry_loop, retry_loop_for_pattern,
     };
     use anyhow::{anyhow, bail, Result};
     use rand::{distributions::Alphanumeric, thread_rng, Rng};
+    use std::collections::BTreeSet;
 
     // make some constants for these, in case entries in the
     // testdata folder change.
@@ -1188,22 +2324,329 @@ mod tests {
         let files_map = files_map_create(
             &mut safe,
             &mut processed_files,
+            FileChecksums::new(),
             "../testdata",
             Some(""),
-            true,
+            SymlinkPolicy::FollowAll,
+            false,
             false,
         )
         .await?;
         assert_eq!(files_map.len(), 2);
         let file_item1 = &files_map["/testdata/test.md"];
-        assert_eq!(file_item1[PREDICATE_LINK], first_xorurl);
-        assert_eq!(file_item1[PREDICATE_TYPE], "text/markdown");
-        assert_eq!(file_item1[PREDICATE_SIZE], "12");
+        assert_eq!(file_item1.link(), Some(first_xorurl.as_str()));
+        assert_eq!(file_item1.media_type(), "text/markdown");
+        assert_eq!(file_item1.size, 12);
 
         let file_item2 = &files_map["/testdata/subfolder/subexists.md"];
-        assert_eq!(file_item2[PREDICATE_LINK], second_xorurl);
-        assert_eq!(file_item2[PREDICATE_TYPE], "text/markdown");
-        assert_eq!(file_item2[PREDICATE_SIZE], "23");
+        assert_eq!(file_item2.link(), Some(second_xorurl.as_str()));
+        assert_eq!(file_item2.media_type(), "text/markdown");
+        assert_eq!(file_item2.size, 23);
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_map_copy_ancestors() -> Result<()> {
+        let dir = || {
+            FileMeta::from_type_and_size(MIMETYPE_FILESYSTEM_DIR, 0).to_file_item(FileItemType::Dir)
+        };
+        let file = FileMeta::from_type_and_size("text/plain", 5).to_file_item(FileItemType::File {
+            media_type: "text/plain".to_string(),
+            link: "safe://link".to_string(),
+        });
+
+        let mut src_files_map = FilesMap::default();
+        src_files_map.insert("/docs".to_string(), dir());
+        src_files_map.insert("/docs/a.md".to_string(), file);
+        let mut files_map = FilesMap::default();
+        files_map.insert("/backup".to_string(), dir());
+
+        let (processed_files, files_map, success_count) = files_map_copy(
+            &src_files_map,
+            "/docs",
+            files_map,
+            "/backup/2021/03/",
+            true,
+            false,
+        )?;
+
+        assert!(files_map["/backup/2021"].is_dir());
+        assert!(files_map["/backup/2021/03"].is_dir());
+        assert!(files_map["/backup/2021/03/docs"].is_dir());
+        assert!(files_map["/backup/2021/03/docs/a.md"].is_file());
+        assert_eq!(processed_files["/backup/2021"].0, CONTENT_ADDED_SIGN);
+        assert!(!processed_files.contains_key("/backup"));
+        assert_eq!(success_count, 4);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_map_sync_read_error() -> Result<()> {
+        let mut safe = Safe::default();
+        let file = || {
+            FileMeta::from_type_and_size("text/plain", 5).to_file_item(FileItemType::File {
+                media_type: "text/plain".to_string(),
+                link: "safe://link".to_string(),
+            })
+        };
+
+        let mut current_files_map = FilesMap::default();
+        current_files_map.insert("/missing.md".to_string(), file());
+        current_files_map.insert("/old.md".to_string(), file());
+        let mut new_content = ProcessedFiles::new();
+        new_content.insert(
+            "../testdata/missing.md".to_string(),
+            (CONTENT_ADDED_SIGN.to_string(), String::new()),
+        );
+
+        let (processed_files, files_map, success_count) = files_map_sync(
+            &mut safe,
+            current_files_map,
+            "../testdata/",
+            new_content,
+            None,
+            true,
+            false,
+            true,
+            false,
+            Some(FilesComparison::ContentOnly),
+            SymlinkPolicy::Preserve,
+        )
+        .await?;
+
+        // The file which cannot be read is reported, and neither updated nor deleted
+        assert_eq!(
+            processed_files["../testdata/missing.md"].0,
+            CONTENT_ERROR_SIGN
+        );
+        assert!(files_map.contains_key("/missing.md"));
+        assert_eq!(processed_files["/old.md"].0, CONTENT_DELETED_SIGN);
+        assert!(!files_map.contains_key("/old.md"));
+        assert_eq!(success_count, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_map_move_path_symlinks() -> Result<()> {
+        let symlink = |target: &str| {
+            FileMeta::from_type_and_size(MIMETYPE_FILESYSTEM_SYMLINK, 0).to_file_item(
+                FileItemType::Symlink {
+                    target: target.to_string(),
+                    target_type: SymlinkTargetType::Unknown,
+                },
+            )
+        };
+        let dir = || {
+            FileMeta::from_type_and_size(MIMETYPE_FILESYSTEM_DIR, 0).to_file_item(FileItemType::Dir)
+        };
+
+        let mut files_map = FilesMap::default();
+        files_map.insert("/docs".to_string(), dir());
+        files_map.insert("/docs/a.md".to_string(), symlink("../x"));
+        files_map.insert("/docs/b.md".to_string(), symlink("./c.md"));
+        files_map.insert("/docs/c.md".to_string(), dir());
+        files_map.insert("/x".to_string(), dir());
+        // Unrelated symlinks, which could be written in a simpler way
+        files_map.insert("/other".to_string(), symlink("./x"));
+        files_map.insert("/other2".to_string(), symlink("x/../x/"));
+        files_map.insert("/to-docs".to_string(), symlink("docs/c.md"));
+
+        let (processed_files, files_map, _) =
+            files_map_move_path("/docs", "/sub/docs", files_map, false)?;
+
+        assert_eq!(files_map["/other"].symlink_target(), Some("./x"));
+        assert_eq!(files_map["/other2"].symlink_target(), Some("x/../x/"));
+        assert!(!processed_files.contains_key("/other"));
+        assert!(!processed_files.contains_key("/other2"));
+
+        // Moved together with its target, thus still valid as it is
+        assert_eq!(files_map["/sub/docs/b.md"].symlink_target(), Some("./c.md"));
+        // Moved without its target, or its target moved without it
+        assert_eq!(
+            files_map["/sub/docs/a.md"].symlink_target(),
+            Some("../../x")
+        );
+        assert_eq!(
+            files_map["/to-docs"].symlink_target(),
+            Some("sub/docs/c.md")
+        );
+        assert_eq!(processed_files["/to-docs"].0, CONTENT_UPDATED_SIGN);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_map_deserialise_legacy_format() -> Result<()> {
+        let legacy = r#"{
+            "/test.md": {
+                "created": "2021-06-01T10:00:00Z",
+                "modified": "2021-06-01T10:00:00Z",
+                "link": "safe://top_xorurl",
+                "mode_bits": "33188",
+                "o_modified": "2021-05-30T08:00:00Z",
+                "readonly": "false",
+                "size": "12",
+                "type": "text/markdown"
+            },
+            "/emptyfolder": {
+                "created": "2021-06-01T10:00:00Z",
+                "modified": "2021-06-01T10:00:00Z",
+                "size": "0",
+                "type": "inode/directory"
+            },
+            "/link.md": {
+                "created": "2021-06-01T10:00:00Z",
+                "modified": "2021-06-01T10:00:00Z",
+                "size": "0",
+                "symlink_target": "test.md",
+                "symlink_target_type": "file",
+                "type": "inode/symlink"
+            }
+        }"#;
+        let files_map = deserialise_files_map(legacy.as_bytes(), FilesMapEncoding::JsonLd)?;
+        assert_eq!(files_map.len(), 3);
+
+        let file_item = &files_map["/test.md"];
+        assert_eq!(file_item.link(), Some("safe://top_xorurl"));
+        assert_eq!(file_item.size, 12);
+        assert_eq!(file_item.readonly, Some(false));
+        assert_eq!(file_item.mode_bits, Some(33188));
+        assert!(file_item.original_modified.is_some());
+        assert!(files_map["/emptyfolder"].is_dir());
+        assert_eq!(files_map["/link.md"].symlink_target(), Some("test.md"));
+
+        // once serialised again it's stored with the current schema version
+        let serialised = serialise_files_map(&files_map, FilesMapEncoding::JsonLd)?;
+        let value: serde_json::Value = serde_json::from_slice(&serialised)?;
+        assert_eq!(value["schema_version"], FILES_MAP_SCHEMA_VERSION);
+        assert_eq!(
+            deserialise_files_map(&serialised, FilesMapEncoding::JsonLd)?,
+            files_map
+        );
+
+        // and it can also be stored with the compact encoding
+        let compact = serialise_files_map(&files_map, FilesMapEncoding::MsgPackZstd)?;
+        assert_eq!(
+            deserialise_files_map(&compact, FilesMapEncoding::MsgPackZstd)?,
+            files_map
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_map_to_rdf() -> Result<()> {
+        let legacy = r#"{
+            "/test.md": {
+                "created": "2021-06-01T10:00:00Z",
+                "modified": "2021-06-01T10:00:00Z",
+                "link": "safe://top_xorurl",
+                "size": "12",
+                "type": "text/markdown"
+            }
+        }"#;
+        let files_map = deserialise_files_map(legacy.as_bytes(), FilesMapEncoding::JsonLd)?;
+
+        let jsonld = files_map.to_rdf(RdfFormat::JsonLd)?;
+        let value: serde_json::Value = serde_json::from_str(&jsonld)?;
+        assert_eq!(value["@type"], "FilesMap");
+        assert_eq!(value["files"]["/test.md"]["path"], "/test.md");
+        assert_eq!(value["files"]["/test.md"]["link"], "safe://top_xorurl");
+        assert_eq!(
+            deserialise_files_map(jsonld.as_bytes(), FilesMapEncoding::JsonLd)?,
+            files_map
+        );
+
+        let turtle = files_map.to_rdf(RdfFormat::Turtle)?;
+        assert!(turtle.starts_with("@prefix : <https://safenetwork.tech/ns/files#> ."));
+        assert!(turtle.contains(":path \"/test.md\""));
+        assert!(turtle.contains(":link <safe://top_xorurl>"));
+        assert!(turtle.contains(":size 12"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_map_deserialise_malformed() -> Result<()> {
+        let missing_link = r#"{
+            "/test.md": {
+                "created": "2021-06-01T10:00:00Z",
+                "modified": "2021-06-01T10:00:00Z",
+                "size": "12",
+                "type": "text/markdown"
+            }
+        }"#;
+        match deserialise_files_map(missing_link.as_bytes(), FilesMapEncoding::JsonLd) {
+            Err(Error::ContentError(_)) => {}
+            other => bail!("Unexpected result for FileItem without link: {:?}", other),
+        }
+
+        let invalid_size = r#"{
+            "schema_version": 1,
+            "files": {
+                "/emptyfolder": {
+                    "created": "2021-06-01T10:00:00Z",
+                    "modified": "2021-06-01T10:00:00Z",
+                    "size": "big",
+                    "type": "inode/directory"
+                }
+            }
+        }"#;
+        match deserialise_files_map(invalid_size.as_bytes(), FilesMapEncoding::JsonLd) {
+            Err(Error::ContentError(_)) => {}
+            other => bail!(
+                "Unexpected result for FileItem with invalid size: {:?}",
+                other
+            ),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_map_delta() -> Result<()> {
+        let legacy = r#"{
+            "/a.md": {
+                "created": "2021-06-01T10:00:00Z",
+                "link": "safe://hbyyyyncj8rrtdsp7u6ctqsrdspch3np8bs4ptzrnazqjj5o7ugbwjshfh",
+                "modified": "2021-06-01T10:00:00Z",
+                "size": "12",
+                "type": "text/markdown"
+            },
+            "/b.md": {
+                "created": "2021-06-01T10:00:00Z",
+                "link": "safe://hbyyyyncj8rrtdsp7u6ctqsrdspch3np8bs4ptzrnazqjj5o7ugbwjshfh",
+                "modified": "2021-06-01T10:00:00Z",
+                "size": "12",
+                "type": "text/markdown"
+            }
+        }"#;
+        let base = deserialise_files_map(legacy.as_bytes(), FilesMapEncoding::JsonLd)?;
+
+        let mut target = base.clone();
+        target.remove("/a.md");
+        let mut modified = target["/b.md"].clone();
+        modified.size = 20;
+        target.insert("/b.md".to_string(), modified.clone());
+        target.insert("/c.md".to_string(), modified);
+
+        let delta = FilesMapDelta::new(3, &base, &target);
+        assert_eq!(delta.added.keys().collect::<Vec<_>>(), vec!["/c.md"]);
+        assert_eq!(delta.modified.keys().collect::<Vec<_>>(), vec!["/b.md"]);
+        assert_eq!(delta.removed.iter().collect::<Vec<_>>(), vec!["/a.md"]);
+
+        for encoding in &[FilesMapEncoding::JsonLd, FilesMapEncoding::MsgPackZstd] {
+            let serialised = serialise_files_map_delta(&delta, *encoding)?;
+            let deserialised = deserialise_files_map_delta(&serialised, *encoding)?;
+            assert_eq!(deserialised, delta);
+
+            let mut files_map = base.clone();
+            deserialised.apply(&mut files_map);
+            assert_eq!(files_map, target);
+        }
+
         Ok(())
     }
 
@@ -1211,7 +2654,14 @@ mod tests {
     async fn test_files_container_create_empty() -> Result<()> {
         let mut safe = new_safe_instance().await?;
         let (xorurl, processed_files, files_map) = safe
-            .files_container_create(None, None, false, false, false)
+            .files_container_create(
+                None,
+                None,
+                &CreateOptions {
+                    recursive: false,
+                    ..CreateOptions::default()
+                },
+            )
             .await?;
 
         assert!(xorurl.starts_with("safe://"));
@@ -1222,7 +2672,7 @@ mod tests {
 
         // let's add a file
         let (version, new_processed_files, new_files_map) = safe
-            .files_container_add("../testdata/test.md", &xorurl, false, false, false, false)
+            .files_container_add("../testdata/test.md", &xorurl, &AddOptions::default())
             .await?;
 
         assert_eq!(version, 1);
@@ -1233,7 +2683,7 @@ mod tests {
         assert_eq!(new_processed_files[filename].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             new_processed_files[filename].1,
-            new_files_map["/test.md"][PREDICATE_LINK]
+            new_files_map["/test.md"].link().unwrap_or_default()
         );
 
         Ok(())
@@ -1260,7 +2710,14 @@ mod tests {
         let mut safe = new_safe_instance().await?;
         let filename = "../testdata/test.md";
         let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some(filename), None, false, false, false)
+            .files_container_create(
+                Some(filename),
+                None,
+                &CreateOptions {
+                    recursive: false,
+                    ..CreateOptions::default()
+                },
+            )
             .await?;
 
         assert!(xorurl.starts_with("safe://"));
@@ -1270,7 +2727,7 @@ mod tests {
         assert_eq!(processed_files[filename].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename].1,
-            files_map[file_path][PREDICATE_LINK]
+            files_map[file_path].link().unwrap_or_default()
         );
 
         Ok(())
@@ -1281,7 +2738,14 @@ mod tests {
         let mut safe = new_safe_instance().await?;
         let filename = "../testdata/";
         let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some(filename), None, true, false, true)
+            .files_container_create(
+                Some(filename),
+                None,
+                &CreateOptions {
+                    dry_run: true,
+                    ..CreateOptions::default()
+                },
+            )
             .await?;
 
         assert!(xorurl.is_empty());
@@ -1293,7 +2757,7 @@ mod tests {
         assert!(!processed_files[filename1].1.is_empty());
         assert_eq!(
             processed_files[filename1].1,
-            files_map["/test.md"][PREDICATE_LINK]
+            files_map["/test.md"].link().unwrap_or_default()
         );
 
         let filename2 = "../testdata/another.md";
@@ -1301,7 +2765,7 @@ mod tests {
         assert!(!processed_files[filename2].1.is_empty());
         assert_eq!(
             processed_files[filename2].1,
-            files_map["/another.md"][PREDICATE_LINK]
+            files_map["/another.md"].link().unwrap_or_default()
         );
 
         let filename3 = "../testdata/subfolder/subexists.md";
@@ -1309,7 +2773,9 @@ mod tests {
         assert!(!processed_files[filename3].1.is_empty());
         assert_eq!(
             processed_files[filename3].1,
-            files_map["/subfolder/subexists.md"][PREDICATE_LINK]
+            files_map["/subfolder/subexists.md"]
+                .link()
+                .unwrap_or_default()
         );
 
         let filename4 = "../testdata/noextension";
@@ -1317,7 +2783,7 @@ mod tests {
         assert!(!processed_files[filename4].1.is_empty());
         assert_eq!(
             processed_files[filename4].1,
-            files_map["/noextension"][PREDICATE_LINK]
+            files_map["/noextension"].link().unwrap_or_default()
         );
 
         Ok(())
@@ -1326,8 +2792,14 @@ mod tests {
     #[tokio::test]
     async fn test_files_container_create_folder_without_trailing_slash() -> Result<()> {
         let mut safe = new_safe_instance().await?;
-        let (xorurl, processed_files, files_map) =
-            retry_loop!(safe.files_container_create(Some("../testdata"), None, true, true, false));
+        let (xorurl, processed_files, files_map) = retry_loop!(safe.files_container_create(
+            Some("../testdata"),
+            None,
+            &CreateOptions {
+                symlinks: SymlinkPolicy::FollowAll,
+                ..CreateOptions::default()
+            },
+        ));
 
         assert!(xorurl.starts_with("safe://"));
         assert_eq!(processed_files.len(), TESTDATA_NO_SLASH_PUT_FILEITEM_COUNT);
@@ -1337,28 +2809,32 @@ mod tests {
         assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename1].1,
-            files_map["/testdata/test.md"][PREDICATE_LINK]
+            files_map["/testdata/test.md"].link().unwrap_or_default()
         );
 
         let filename2 = "../testdata/another.md";
         assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename2].1,
-            files_map["/testdata/another.md"][PREDICATE_LINK]
+            files_map["/testdata/another.md"].link().unwrap_or_default()
         );
 
         let filename3 = "../testdata/subfolder/subexists.md";
         assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename3].1,
-            files_map["/testdata/subfolder/subexists.md"][PREDICATE_LINK]
+            files_map["/testdata/subfolder/subexists.md"]
+                .link()
+                .unwrap_or_default()
         );
 
         let filename4 = "../testdata/noextension";
         assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename4].1,
-            files_map["/testdata/noextension"][PREDICATE_LINK]
+            files_map["/testdata/noextension"]
+                .link()
+                .unwrap_or_default()
         );
 
         Ok(())
@@ -1367,8 +2843,14 @@ mod tests {
     #[tokio::test]
     async fn test_files_container_create_folder_with_trailing_slash() -> Result<()> {
         let mut safe = new_safe_instance().await?;
-        let (xorurl, processed_files, files_map) =
-            retry_loop!(safe.files_container_create(Some("../testdata/"), None, true, true, false));
+        let (xorurl, processed_files, files_map) = retry_loop!(safe.files_container_create(
+            Some("../testdata/"),
+            None,
+            &CreateOptions {
+                symlinks: SymlinkPolicy::FollowAll,
+                ..CreateOptions::default()
+            },
+        ));
 
         assert!(xorurl.starts_with("safe://"));
         assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
@@ -1378,28 +2860,30 @@ mod tests {
         assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename1].1,
-            files_map["/test.md"][PREDICATE_LINK]
+            files_map["/test.md"].link().unwrap_or_default()
         );
 
         let filename2 = "../testdata/another.md";
         assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename2].1,
-            files_map["/another.md"][PREDICATE_LINK]
+            files_map["/another.md"].link().unwrap_or_default()
         );
 
         let filename3 = "../testdata/subfolder/subexists.md";
         assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename3].1,
-            files_map["/subfolder/subexists.md"][PREDICATE_LINK]
+            files_map["/subfolder/subexists.md"]
+                .link()
+                .unwrap_or_default()
         );
 
         let filename4 = "../testdata/noextension";
         assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename4].1,
-            files_map["/noextension"][PREDICATE_LINK]
+            files_map["/noextension"].link().unwrap_or_default()
         );
 
         Ok(())
@@ -1409,7 +2893,14 @@ mod tests {
     async fn test_files_container_create_dest_path_without_trailing_slash() -> Result<()> {
         let mut safe = new_safe_instance().await?;
         let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata"), Some("/myroot"), true, true, false)
+            .files_container_create(
+                Some("../testdata"),
+                Some("/myroot"),
+                &CreateOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    ..CreateOptions::default()
+                },
+            )
             .await?;
 
         assert!(xorurl.starts_with("safe://"));
@@ -1420,28 +2911,30 @@ mod tests {
         assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename1].1,
-            files_map["/myroot/test.md"][PREDICATE_LINK]
+            files_map["/myroot/test.md"].link().unwrap_or_default()
         );
 
         let filename2 = "../testdata/another.md";
         assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename2].1,
-            files_map["/myroot/another.md"][PREDICATE_LINK]
+            files_map["/myroot/another.md"].link().unwrap_or_default()
         );
 
         let filename3 = "../testdata/subfolder/subexists.md";
         assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename3].1,
-            files_map["/myroot/subfolder/subexists.md"][PREDICATE_LINK]
+            files_map["/myroot/subfolder/subexists.md"]
+                .link()
+                .unwrap_or_default()
         );
 
         let filename4 = "../testdata/noextension";
         assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename4].1,
-            files_map["/myroot/noextension"][PREDICATE_LINK]
+            files_map["/myroot/noextension"].link().unwrap_or_default()
         );
 
         Ok(())
@@ -1451,7 +2944,14 @@ mod tests {
     async fn test_files_container_create_dest_path_with_trailing_slash() -> Result<()> {
         let mut safe = new_safe_instance().await?;
         let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata"), Some("/myroot/"), true, true, false)
+            .files_container_create(
+                Some("../testdata"),
+                Some("/myroot/"),
+                &CreateOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    ..CreateOptions::default()
+                },
+            )
             .await?;
 
         assert!(xorurl.starts_with("safe://"));
@@ -1462,104 +2962,284 @@ mod tests {
         assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename1].1,
-            files_map["/myroot/testdata/test.md"][PREDICATE_LINK]
+            files_map["/myroot/testdata/test.md"]
+                .link()
+                .unwrap_or_default()
         );
 
         let filename2 = "../testdata/another.md";
         assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename2].1,
-            files_map["/myroot/testdata/another.md"][PREDICATE_LINK]
+            files_map["/myroot/testdata/another.md"]
+                .link()
+                .unwrap_or_default()
         );
 
         let filename3 = "../testdata/subfolder/subexists.md";
         assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename3].1,
-            files_map["/myroot/testdata/subfolder/subexists.md"][PREDICATE_LINK]
+            files_map["/myroot/testdata/subfolder/subexists.md"]
+                .link()
+                .unwrap_or_default()
         );
 
         let filename4 = "../testdata/noextension";
         assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename4].1,
-            files_map["/myroot/testdata/noextension"][PREDICATE_LINK]
+            files_map["/myroot/testdata/noextension"]
+                .link()
+                .unwrap_or_default()
         );
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_files_container_sync() -> Result<()> {
+    async fn test_files_container_compact_encoding() -> Result<()> {
         let mut safe = new_safe_instance().await?;
-        let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata/"), None, true, true, false)
+        safe.files_config.files_map_encoding = FilesMapEncoding::MsgPackZstd;
+        let (xorurl, _, files_map) = safe
+            .files_container_create(
+                Some("../testdata/"),
+                None,
+                &CreateOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    ..CreateOptions::default()
+                },
+            )
             .await?;
 
-        assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
-        assert_eq!(files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
-
-        let _ = retry_loop!(safe.fetch(&xorurl, None));
+        let (version, fetched_files_map) = retry_loop!(safe.files_container_get(&xorurl));
+        assert_eq!(version, 0);
+        assert_eq!(fetched_files_map, files_map);
 
-        let (version, new_processed_files, new_files_map) = safe
+        // new versions keep the encoding the FilesContainer was created with
+        safe.files_config.files_map_encoding = FilesMapEncoding::JsonLd;
+        let (version, _, new_files_map) = safe
             .files_container_sync(
                 "../testdata/subfolder/",
                 &xorurl,
-                true,
-                true,
-                false,
-                false,
-                false,
+                &SyncOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    ..SyncOptions::default()
+                },
             )
             .await?;
+        assert_eq!(version, 1);
 
+        let safe_url = SafeUrl::from_url(&xorurl)?;
+        match safe.fetch_files_map_link(&safe_url).await? {
+            Some((_, _, format)) => assert_eq!(format.encoding, FilesMapEncoding::MsgPackZstd),
+            None => bail!("The FilesContainer shouldn't be empty"),
+        }
+        let (version, fetched_files_map) = retry_loop!(safe.files_container_get(&xorurl));
         assert_eq!(version, 1);
-        assert_eq!(new_processed_files.len(), 2);
+        assert_eq!(fetched_files_map, new_files_map);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_sharded() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        safe.files_config.files_map_layout = FilesMapLayout::Sharded;
+        let (xorurl, _, files_map) = safe
+            .files_container_create(
+                Some("../testdata/"),
+                None,
+                &CreateOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    ..CreateOptions::default()
+                },
+            )
+            .await?;
+
+        let (version, fetched_files_map) = retry_loop!(safe.files_container_get(&xorurl));
+        assert_eq!(version, 0);
+        assert_eq!(fetched_files_map, files_map);
+
+        // resolving a file's path only fetches the shards on the path
+        let safe_url = SafeUrl::from_url(&xorurl)?;
+        let (_, partial_files_map, realpath) = safe
+            .fetch_files_container_path(&safe_url, "/subfolder/subexists.md")
+            .await?;
+        assert_eq!(realpath, "/subfolder/subexists.md");
         assert_eq!(
-            new_files_map.len(),
-            TESTDATA_PUT_FILEITEM_COUNT + SUBFOLDER_PUT_FILEITEM_COUNT
+            partial_files_map["/subfolder/subexists.md"],
+            files_map["/subfolder/subexists.md"]
         );
+        assert!(partial_files_map.len() < files_map.len());
 
-        let filename1 = "../testdata/test.md";
-        assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
+        // new versions keep the layout the FilesContainer was created with
+        safe.files_config.files_map_layout = FilesMapLayout::Single;
+        let (version, _, new_files_map) = safe
+            .files_container_sync(
+                "../testdata/subfolder/",
+                &xorurl,
+                &SyncOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    ..SyncOptions::default()
+                },
+            )
+            .await?;
+        assert_eq!(version, 1);
+
+        match safe.fetch_files_map_link(&safe_url).await? {
+            Some((_, _, format)) => assert_eq!(format.layout, FilesMapLayout::Sharded),
+            None => bail!("The FilesContainer shouldn't be empty"),
+        }
+        let (version, fetched_files_map) = retry_loop!(safe.files_container_get(&xorurl));
+        assert_eq!(version, 1);
+        assert_eq!(fetched_files_map, new_files_map);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_deltas() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        safe.files_config.files_map_snapshot_interval = Some(2);
+        let (xorurl, _, files_map) = safe
+            .files_container_create(
+                Some("../testdata/"),
+                None,
+                &CreateOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    ..CreateOptions::default()
+                },
+            )
+            .await?;
+
+        // version 1 is stored as a delta against version 0
+        let (version, _, files_map_v1) = safe
+            .files_container_sync(
+                "../testdata/subfolder/",
+                &xorurl,
+                &SyncOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    ..SyncOptions::default()
+                },
+            )
+            .await?;
+        assert_eq!(version, 1);
+
+        let safe_url = SafeUrl::from_url(&xorurl)?;
+        match safe.fetch_files_map_link(&safe_url).await? {
+            Some((_, _, format)) => assert_eq!(format.delta_base, Some(0)),
+            None => bail!("The FilesContainer shouldn't be empty"),
+        }
+
+        // version 2 is a snapshot
+        let (version, _, _) = safe
+            .files_container_remove_path(
+                &format!("{}/test.md", xorurl),
+                false,
+                &UpdateOptions::default(),
+            )
+            .await?;
+        assert_eq!(version, 2);
+        match safe.fetch_files_map_link(&safe_url).await? {
+            Some((_, _, format)) => assert_eq!(format.delta_base, None),
+            None => bail!("The FilesContainer shouldn't be empty"),
+        }
+
+        // all versions are rebuilt transparently, also without the cache
+        let safe = new_safe_instance().await?;
+        let v0_url = SafeUrl::from_url(&format!("{}?v=0", xorurl))?;
+        let (_, fetched_files_map) = retry_loop!(safe.fetch_files_container(&v0_url));
+        assert_eq!(fetched_files_map, files_map);
+        let v1_url = SafeUrl::from_url(&format!("{}?v=1", xorurl))?;
+        let (_, fetched_files_map) = retry_loop!(safe.fetch_files_container(&v1_url));
+        assert_eq!(fetched_files_map, files_map_v1);
+        let (version, fetched_files_map) = retry_loop!(safe.fetch_files_container(&safe_url));
+        assert_eq!(version, 2);
+        assert_eq!(fetched_files_map.len(), files_map_v1.len() - 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_sync() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, processed_files, files_map) = safe
+            .files_container_create(
+                Some("../testdata/"),
+                None,
+                &CreateOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    ..CreateOptions::default()
+                },
+            )
+            .await?;
+
+        assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
+        assert_eq!(files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
+
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
+
+        let (version, new_processed_files, new_files_map) = safe
+            .files_container_sync(
+                "../testdata/subfolder/",
+                &xorurl,
+                &SyncOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    ..SyncOptions::default()
+                },
+            )
+            .await?;
+
+        assert_eq!(version, 1);
+        assert_eq!(new_processed_files.len(), 2);
+        assert_eq!(
+            new_files_map.len(),
+            TESTDATA_PUT_FILEITEM_COUNT + SUBFOLDER_PUT_FILEITEM_COUNT
+        );
+
+        let filename1 = "../testdata/test.md";
+        assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
             processed_files[filename1].1,
-            new_files_map["/test.md"][PREDICATE_LINK]
+            new_files_map["/test.md"].link().unwrap_or_default()
         );
 
         let filename2 = "../testdata/another.md";
         assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename2].1,
-            new_files_map["/another.md"][PREDICATE_LINK]
+            new_files_map["/another.md"].link().unwrap_or_default()
         );
 
         let filename3 = "../testdata/subfolder/subexists.md";
         assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename3].1,
-            new_files_map["/subfolder/subexists.md"][PREDICATE_LINK]
+            new_files_map["/subfolder/subexists.md"]
+                .link()
+                .unwrap_or_default()
         );
 
         let filename4 = "../testdata/noextension";
         assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename4].1,
-            new_files_map["/noextension"][PREDICATE_LINK]
+            new_files_map["/noextension"].link().unwrap_or_default()
         );
 
         let filename5 = "../testdata/subfolder/subexists.md";
         assert_eq!(new_processed_files[filename5].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             new_processed_files[filename5].1,
-            new_files_map["/subexists.md"][PREDICATE_LINK]
+            new_files_map["/subexists.md"].link().unwrap_or_default()
         );
 
         let filename6 = "../testdata/subfolder/sub2.md";
         assert_eq!(new_processed_files[filename6].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             new_processed_files[filename6].1,
-            new_files_map["/sub2.md"][PREDICATE_LINK]
+            new_files_map["/sub2.md"].link().unwrap_or_default()
         );
 
         Ok(())
@@ -1569,7 +3249,14 @@ mod tests {
     async fn test_files_container_sync_dry_run() -> Result<()> {
         let mut safe = new_safe_instance().await?;
         let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata/"), None, true, true, false)
+            .files_container_create(
+                Some("../testdata/"),
+                None,
+                &CreateOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    ..CreateOptions::default()
+                },
+            )
             .await?;
 
         assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
@@ -1581,11 +3268,12 @@ mod tests {
             .files_container_sync(
                 "../testdata/subfolder/",
                 &xorurl,
-                true,
-                true,
-                false,
-                false,
-                true, // set dry_run flag on
+                &SyncOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    expected_version: ExpectedVersion::Any, // set dry_run flag on
+                    dry_run: true,
+                    ..SyncOptions::default()
+                },
             )
             .await?;
 
@@ -1600,28 +3288,30 @@ mod tests {
         assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename1].1,
-            new_files_map["/test.md"][PREDICATE_LINK]
+            new_files_map["/test.md"].link().unwrap_or_default()
         );
 
         let filename2 = "../testdata/another.md";
         assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename2].1,
-            new_files_map["/another.md"][PREDICATE_LINK]
+            new_files_map["/another.md"].link().unwrap_or_default()
         );
 
         let filename3 = "../testdata/subfolder/subexists.md";
         assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename3].1,
-            new_files_map["/subfolder/subexists.md"][PREDICATE_LINK]
+            new_files_map["/subfolder/subexists.md"]
+                .link()
+                .unwrap_or_default()
         );
 
         let filename4 = "../testdata/noextension";
         assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename4].1,
-            new_files_map["/noextension"][PREDICATE_LINK]
+            new_files_map["/noextension"].link().unwrap_or_default()
         );
 
         let filename5 = "../testdata/subfolder/subexists.md";
@@ -1629,7 +3319,7 @@ mod tests {
         assert!(!new_processed_files[filename5].1.is_empty());
         assert_eq!(
             new_processed_files[filename5].1,
-            new_files_map["/subexists.md"][PREDICATE_LINK]
+            new_files_map["/subexists.md"].link().unwrap_or_default()
         );
 
         let filename6 = "../testdata/subfolder/sub2.md";
@@ -1637,7 +3327,7 @@ mod tests {
         assert!(!new_processed_files[filename6].1.is_empty());
         assert_eq!(
             new_processed_files[filename6].1,
-            new_files_map["/sub2.md"][PREDICATE_LINK]
+            new_files_map["/sub2.md"].link().unwrap_or_default()
         );
 
         Ok(())
@@ -1647,7 +3337,14 @@ mod tests {
     async fn test_files_container_sync_same_size() -> Result<()> {
         let mut safe = new_safe_instance().await?;
         let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata/test.md"), None, false, false, false)
+            .files_container_create(
+                Some("../testdata/test.md"),
+                None,
+                &CreateOptions {
+                    recursive: false,
+                    ..CreateOptions::default()
+                },
+            )
             .await?;
 
         assert_eq!(processed_files.len(), 1);
@@ -1659,11 +3356,10 @@ mod tests {
             .files_container_sync(
                 "../testdata/.subhidden/test.md",
                 &xorurl,
-                false,
-                false,
-                false,
-                false,
-                false,
+                &SyncOptions {
+                    recursive: false,
+                    ..SyncOptions::default()
+                },
             )
             .await?;
 
@@ -1675,24 +3371,1091 @@ mod tests {
         assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename1].1,
-            files_map["/test.md"][PREDICATE_LINK]
+            files_map["/test.md"].link().unwrap_or_default()
         );
         let filename2 = "../testdata/.subhidden/test.md";
         assert_eq!(new_processed_files[filename2].0, CONTENT_UPDATED_SIGN);
         assert_eq!(
             new_processed_files[filename2].1,
-            new_files_map["/test.md"][PREDICATE_LINK]
+            new_files_map["/test.md"].link().unwrap_or_default()
+        );
+
+        // check sizes are the same but links are different
+        assert_eq!(files_map["/test.md"].size, new_files_map["/test.md"].size);
+        assert_ne!(
+            files_map["/test.md"].link().unwrap_or_default(),
+            new_files_map["/test.md"].link().unwrap_or_default()
+        );
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_files_container_sync_metadata_only() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut safe = new_safe_instance().await?;
+        let tmp_dir = std::env::temp_dir().join(random_nrs_name());
+        fs::create_dir_all(&tmp_dir)?;
+        let tmp_file = tmp_dir.join("test.md");
+        fs::write(&tmp_file, b"hello tests!")?;
+        fs::set_permissions(&tmp_file, fs::Permissions::from_mode(0o644))?;
+        let location = tmp_file.display().to_string();
+
+        let (xorurl, _, files_map) = safe
+            .files_container_create(
+                Some(&location),
+                None,
+                &CreateOptions {
+                    recursive: false,
+                    ..CreateOptions::default()
+                },
+            )
+            .await?;
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
+
+        // a content-only comparison doesn't pick up the permissions change
+        fs::set_permissions(&tmp_file, fs::Permissions::from_mode(0o600))?;
+        let (version, _, _) = safe
+            .files_container_sync(
+                &location,
+                &xorurl,
+                &SyncOptions {
+                    recursive: false,
+                    ..SyncOptions::default()
+                },
+            )
+            .await?;
+        assert_eq!(version, 0);
+
+        let (version, new_processed_files, new_files_map) = safe
+            .files_container_sync(
+                &location,
+                &xorurl,
+                &SyncOptions {
+                    recursive: false,
+                    comparison: FilesComparison::ContentAndMetadata,
+                    ..SyncOptions::default()
+                },
+            )
+            .await?;
+        fs::remove_dir_all(&tmp_dir)?;
+
+        assert_eq!(version, 1);
+        assert_eq!(new_processed_files[&location].0, CONTENT_UPDATED_SIGN);
+        // the link is kept since the content didn't change
+        assert_eq!(
+            files_map["/test.md"].link().unwrap_or_default(),
+            new_files_map["/test.md"].link().unwrap_or_default()
+        );
+        assert_ne!(
+            files_map["/test.md"].mode_bits,
+            new_files_map["/test.md"].mode_bits
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_watch() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let tmp_dir = std::env::temp_dir().join(random_nrs_name());
+        fs::create_dir_all(&tmp_dir)?;
+        fs::write(tmp_dir.join("test.md"), b"hello tests!")?;
+        let location = format!("{}/", tmp_dir.display());
+
+        let (xorurl, _, _) = safe
+            .files_container_create(Some(&location), None, &CreateOptions::default())
+            .await?;
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
+
+        let new_file = tmp_dir.join("new.md");
+        let writer = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_secs(1));
+            fs::write(new_file, b"hello watch!")
+        });
+
+        let cancellation = WatchCancellation::new();
+        let options = WatchOptions {
+            debounce: std::time::Duration::from_millis(500),
+            ..WatchOptions::default()
+        };
+        let mut batches = vec![];
+        safe.files_container_watch(&location, &xorurl, options, cancellation.clone(), |batch| {
+            batches.push(batch);
+            cancellation.cancel();
+        })
+        .await?;
+        writer
+            .join()
+            .map_err(|_| anyhow!("Failed to write the new file"))??;
+        fs::remove_dir_all(&tmp_dir)?;
+
+        assert_eq!(batches.len(), 1);
+        match batches.remove(0) {
+            Ok((version, _, files_map)) => {
+                assert_eq!(version, 1);
+                assert!(files_map.contains_key("/new.md"));
+            }
+            Err(err) => bail!("Failed to sync the batch of changes: {}", err),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_archive() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+
+        let mut builder = tar::Builder::new(vec![]);
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_mode(0o750);
+        header.set_mtime(1_600_000_000);
+        header.set_size(0);
+        builder.append_data(&mut header, "docs/", std::io::empty())?;
+        let content = b"hello archive!";
+        let mut header = tar::Header::new_gnu();
+        header.set_mode(0o600);
+        header.set_mtime(1_600_000_000);
+        header.set_size(content.len() as u64);
+        builder.append_data(&mut header, "docs/readme.md", &content[..])?;
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_size(0);
+        builder.append_link(&mut header, "latest", "docs")?;
+        let archive = builder.into_inner()?;
+
+        let (xorurl, processed_files, files_map) = safe
+            .files_container_create_from_archive(
+                archive.as_slice(),
+                ArchiveFormat::Tar,
+                Some("/release"),
+                false,
+                false,
+            )
+            .await?;
+        assert_eq!(processed_files.len(), 3);
+        assert!(files_map["/release"].is_dir());
+        assert!(files_map["/release/docs"].is_dir());
+        let readme = &files_map["/release/docs/readme.md"];
+        assert_eq!(readme.size, content.len() as u64);
+        assert_eq!(readme.mode_bits, Some(0o600));
+        assert_eq!(
+            readme.original_modified.map(|mtime| mtime.timestamp()),
+            Some(1_600_000_000)
+        );
+        assert_eq!(
+            files_map["/release/latest"].file_type,
+            FileItemType::Symlink {
+                target: "docs".to_string(),
+                target_type: SymlinkTargetType::Dir
+            }
+        );
+
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
+        let mut exported = vec![];
+        safe.files_container_export_archive(&xorurl, &mut exported)
+            .await?;
+
+        let mut exported_archive = tar::Archive::new(exported.as_slice());
+        let mut entries = BTreeMap::new();
+        for entry in exported_archive.entries()? {
+            let mut entry = entry?;
+            let path = entry.path()?.display().to_string();
+            let mode = entry.header().mode()?;
+            let mut data = vec![];
+            std::io::Read::read_to_end(&mut entry, &mut data)?;
+            entries.insert(path, (mode, data));
+        }
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries["release/docs/readme.md"], (0o600, content.to_vec()));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_builder() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+
+        let mut builder = FilesContainerBuilder::new();
+        builder
+            .add_file("/site/index.html", "<h1>Hello!</h1>", None)?
+            .add_dir("/site/empty")?
+            .add_symlink("/home", "site")?
+            .set_attributes(
+                "/site/index.html",
+                FileAttributes {
+                    mode_bits: Some(0o444),
+                    ..FileAttributes::default()
+                },
+            )?;
+        assert!(builder.add_file("/home/other.html", "", None).is_err());
+        assert!(builder.add_file("/site", "", None).is_err());
+        assert!(builder.add_symlink("/site", "home").is_err());
+
+        let (xorurl, processed_files, files_map) = safe
+            .files_container_create_from_builder(builder, false, false)
+            .await?;
+        assert_eq!(processed_files.len(), 4);
+        assert!(files_map["/site"].is_dir());
+        assert!(files_map["/site/empty"].is_dir());
+        let index = &files_map["/site/index.html"];
+        assert_eq!(index.size, 15);
+        assert_eq!(index.mode_bits, Some(0o444));
+        assert_eq!(index.readonly, Some(true));
+        assert_eq!(
+            files_map["/home"].file_type,
+            FileItemType::Symlink {
+                target: "site".to_string(),
+                target_type: SymlinkTargetType::Dir
+            }
+        );
+
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
+        let mut builder = FilesContainerBuilder::new();
+        builder
+            .add_file("/site/index.html", "<h1>Hello!</h1>", None)?
+            .add_file("/site/about.html", "<h1>About</h1>", None)?
+            .set_attributes(
+                "/site/index.html",
+                FileAttributes {
+                    mode_bits: Some(0o444),
+                    ..FileAttributes::default()
+                },
+            )?;
+        let (version, processed_files, files_map) = safe
+            .files_container_update_from_builder(&xorurl, builder, true, &UpdateOptions::default())
+            .await?;
+        assert_eq!(version, 1);
+        assert_eq!(processed_files["/site/about.html"].0, CONTENT_ADDED_SIGN);
+        assert_eq!(processed_files["/site/empty"].0, CONTENT_DELETED_SIGN);
+        assert_eq!(processed_files["/home"].0, CONTENT_DELETED_SIGN);
+        assert!(!processed_files.contains_key("/site/index.html"));
+        assert!(files_map.contains_key("/site"));
+        assert_eq!(files_map.len(), 3);
+
+        // a file replacing a directory replaces all its content
+        let _ = retry_loop_for_pattern!(safe.files_container_get(&xorurl), Ok((version, _)) if *version == 1)?;
+        let mut builder = FilesContainerBuilder::new();
+        builder.add_file("/site", "Not a directory anymore", None)?;
+        let (_, processed_files, files_map) = safe
+            .files_container_update_from_builder(
+                &xorurl,
+                builder,
+                false,
+                &UpdateOptions {
+                    dry_run: true,
+                    ..UpdateOptions::default()
+                },
+            )
+            .await?;
+        assert_eq!(processed_files["/site"].0, CONTENT_UPDATED_SIGN);
+        assert_eq!(processed_files["/site/index.html"].0, CONTENT_DELETED_SIGN);
+        assert_eq!(processed_files["/site/about.html"].0, CONTENT_DELETED_SIGN);
+        assert!(files_map["/site"].is_file());
+        assert_eq!(files_map.len(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_verify() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, _, _) = safe
+            .files_container_create(
+                Some("../testdata/subfolder/"),
+                None,
+                &CreateOptions::default(),
+            )
+            .await?;
+
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
+        let report = safe
+            .files_container_verify(
+                "../testdata/subfolder/",
+                &xorurl,
+                SymlinkPolicy::Preserve,
+                true,
+            )
+            .await?;
+        assert!(report.is_ok());
+        assert_eq!(report.verified, SUBFOLDER_PUT_FILEITEM_COUNT as u64);
+
+        let report = safe
+            .files_container_verify("../testdata/", &xorurl, SymlinkPolicy::Preserve, false)
+            .await?;
+        assert!(!report.is_ok());
+        assert!(report.missing.contains("/test.md"));
+        assert!(report.extra.contains("/subexists.md"));
+        assert!(report.corrupted.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_custom_attributes() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, _, files_map) = safe
+            .files_container_create(Some("../testdata/"), None, &CreateOptions::default())
+            .await?;
+        let link = files_map["/test.md"].link().unwrap_or_default().to_string();
+
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
+        let url = format!("{}/test.md", xorurl);
+        let mut attributes = BTreeMap::new();
+        attributes.insert("cache-control".to_string(), "max-age=3600".to_string());
+        attributes.insert("licence".to_string(), "MIT".to_string());
+        let (version, processed_files, files_map) = safe
+            .files_container_set_attributes(&url, "http", attributes, &UpdateOptions::default())
+            .await?;
+        assert_eq!(version, 1);
+        assert_eq!(
+            processed_files["/test.md"],
+            (CONTENT_UPDATED_SIGN.to_string(), link.clone())
+        );
+        assert_eq!(files_map["/test.md"].link(), Some(link.as_str()));
+        assert_eq!(
+            files_map["/test.md"].custom_attribute("http", "licence"),
+            Some("MIT")
+        );
+        assert!(safe
+            .files_container_set_attributes(
+                &url,
+                "in:valid",
+                BTreeMap::new(),
+                &UpdateOptions::default(),
+            )
+            .await
+            .is_err());
+
+        let versioned_url = format!("{}/test.md?v=1", xorurl);
+        let attributes = retry_loop!(safe.files_container_get_attributes(&versioned_url, "http"));
+        assert_eq!(attributes.len(), 2);
+        assert_eq!(attributes["cache-control"], "max-age=3600");
+
+        match safe.fetch(&versioned_url, None).await? {
+            SafeData::PublicBlob {
+                metadata: Some(file_item),
+                ..
+            } => assert_eq!(
+                file_item.custom_attribute("http", "cache-control"),
+                Some("max-age=3600")
+            ),
+            other => bail!(
+                "Content fetched is not a PublicBlob with metadata: {:?}",
+                other
+            ),
+        }
+
+        let (version, _, files_map) = safe
+            .files_container_remove_attributes(
+                &url,
+                "http",
+                &["licence"],
+                &UpdateOptions::default(),
+            )
+            .await?;
+        assert_eq!(version, 2);
+        let attributes = files_map["/test.md"].custom_attributes("http");
+        assert_eq!(attributes.len(), 1);
+        assert!(attributes.contains_key("cache-control"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_expected_version() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, _, _) = safe
+            .files_container_create(Some("../testdata/"), None, &CreateOptions::default())
+            .await?;
+
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
+        let (version, _, _) = safe
+            .files_container_add_from_raw(
+                b"first",
+                &format!("{}/first.txt", xorurl),
+                &AddOptions {
+                    expected_version: ExpectedVersion::Version(0),
+                    ..AddOptions::default()
+                },
+            )
+            .await?;
+        assert_eq!(version, 1);
+
+        let _ = retry_loop!(safe.fetch(&format!("{}?v=1", xorurl), None));
+        match safe
+            .files_container_add_from_raw(
+                b"second",
+                &format!("{}/second.txt", xorurl),
+                &AddOptions {
+                    expected_version: ExpectedVersion::Version(0),
+                    ..AddOptions::default()
+                },
+            )
+            .await
+        {
+            Err(Error::VersionConflict { expected, current }) => {
+                assert_eq!(expected, 0);
+                assert_eq!(current, 1);
+            }
+            other => bail!("Expected a VersionConflict error, got: {:?}", other),
+        }
+
+        // Changes to different paths are merged
+        let (version, _, files_map) = safe
+            .files_container_add_from_raw(
+                b"second",
+                &format!("{}/second.txt", xorurl),
+                &AddOptions {
+                    expected_version: ExpectedVersion::VersionOrMerge(0),
+                    ..AddOptions::default()
+                },
+            )
+            .await?;
+        assert_eq!(version, 2);
+        assert!(files_map.contains_key("/first.txt"));
+        assert!(files_map.contains_key("/second.txt"));
+
+        // Changes to the same path cannot be merged
+        let _ = retry_loop!(safe.fetch(&format!("{}?v=2", xorurl), None));
+        let result = safe
+            .files_container_add_from_raw(
+                b"other first",
+                &format!("{}/first.txt", xorurl),
+                &AddOptions {
+                    expected_version: ExpectedVersion::VersionOrMerge(0),
+                    ..AddOptions::default()
+                },
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(Error::VersionConflict {
+                expected: 0,
+                current: 2
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_multi_writer() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let writer = new_safe_instance().await?.keypair().public_key();
+        let (xorurl, processed_files, files_map) = safe
+            .files_container_create_multi_writer(
+                Some("../testdata/"),
+                None,
+                &[writer],
+                &CreateOptions::default(),
+            )
+            .await?;
+        assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
+        assert_eq!(files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
+
+        let safe_url = SafeUrl::from_url(&xorurl)?;
+        assert_eq!(safe_url.content_type(), SafeContentType::FilesContainer);
+        assert_eq!(safe_url.data_type(), SafeDataType::PublicRegister);
+
+        let (version, fetched_files_map) = retry_loop!(safe.files_container_get(&xorurl));
+        assert_eq!(version, 0);
+        assert_eq!(fetched_files_map, files_map);
+
+        let (version, _, new_files_map) = safe
+            .files_container_add_from_raw(
+                b"multi-writer",
+                &format!("{}/new.txt", xorurl),
+                &AddOptions {
+                    expected_version: ExpectedVersion::Version(0),
+                    ..AddOptions::default()
+                },
+            )
+            .await?;
+        assert_eq!(version, 1);
+        assert_eq!(new_files_map.len(), TESTDATA_PUT_FILEITEM_COUNT + 1);
+
+        let (version, fetched_files_map) = retry_loop_for_pattern!(safe
+            .files_container_get(&xorurl), Ok((version, _)) if *version == 1)?;
+        assert_eq!(version, 1);
+        assert_eq!(fetched_files_map, new_files_map);
+
+        // Only the latest version can be fetched
+        match safe.files_container_get(&format!("{}?v=0", xorurl)).await {
+            Err(Error::VersionNotFound(_)) => {}
+            other => bail!("Expected a VersionNotFound error, got: {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_vfs() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, _, _) = safe
+            .files_container_create(Some("../testdata/"), None, &CreateOptions::default())
+            .await?;
+
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
+        let mut vfs = safe.files_container_vfs(&xorurl).await?;
+        assert_eq!(vfs.read("/test.md", 0, 6).await?, b"hello ".to_vec());
+
+        vfs.create("/subfolder/new.txt").await?;
+        let _ = vfs.write("/subfolder/new.txt", 0, b"new content").await?;
+        vfs.rename("/test.md", "/renamed.md").await?;
+        assert!(vfs.has_changes());
+        let version = vfs.commit().await?;
+        assert_eq!(version, 1);
+        assert!(!vfs.has_changes());
+
+        let (version, files_map) = retry_loop_for_pattern!(safe
+            .files_container_get(&xorurl), Ok((version, _)) if *version == 1)?;
+        assert_eq!(version, 1);
+        assert!(files_map.contains_key("/renamed.md"));
+        assert!(!files_map.contains_key("/test.md"));
+        let new_file = files_map
+            .get("/subfolder/new.txt")
+            .ok_or_else(|| anyhow!("New file not found in the FilesContainer"))?;
+        assert_eq!(new_file.size, 11);
+
+        // Views of specific versions are read-only
+        let mut vfs = safe.files_container_vfs(&format!("{}?v=0", xorurl)).await?;
+        assert!(vfs.lookup("/test.md").await.is_ok());
+        assert!(vfs.unlink("/test.md").await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_add_from_raw_media_types() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, _, _) = safe
+            .files_container_create(None, None, &CreateOptions::default())
+            .await?;
+
+        // The media type is sniffed from the content of files without extension
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
+        let (_, _, files_map) = safe
+            .files_container_add_from_raw(
+                b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR",
+                &format!("{}/image", xorurl),
+                &AddOptions::default(),
+            )
+            .await?;
+        assert_eq!(files_map["/image"].media_type(), "image/png");
+        let link = SafeUrl::from_url(files_map["/image"].link().unwrap_or_default())?;
+        assert_eq!(
+            link.content_type(),
+            SafeContentType::MediaType("image/png".to_string())
+        );
+
+        // Media types not supported by the XOR-URL encoding are kept in the FileItem
+        let _ = retry_loop!(safe.fetch(&format!("{}?v=1", xorurl), None));
+        let (_, _, files_map) = safe
+            .files_container_add_from_raw(
+                b"fLaC\x00\x00\x00\x22",
+                &format!("{}/song", xorurl),
+                &AddOptions::default(),
+            )
+            .await?;
+        assert_eq!(files_map["/song"].media_type(), "audio/flac");
+        let link = SafeUrl::from_url(files_map["/song"].link().unwrap_or_default())?;
+        assert_eq!(link.content_type(), SafeContentType::Raw);
+
+        let url = format!("{}/song?v=2", xorurl);
+        match retry_loop!(safe.fetch(&url, None)) {
+            SafeData::PublicBlob { media_type, .. } => {
+                assert_eq!(media_type, Some("audio/flac".to_string()))
+            }
+            other => bail!("Content fetched is not a PublicBlob: {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_files_container_create_symlink_policies() -> Result<()> {
+        use std::os::unix::fs::symlink;
+
+        let mut safe = new_safe_instance().await?;
+        let tmp_dir = std::env::temp_dir().join(random_nrs_name());
+        fs::create_dir_all(tmp_dir.join("subfolder"))?;
+        fs::write(tmp_dir.join("test.md"), b"hello tests!")?;
+        symlink("test.md", tmp_dir.join("inside"))?;
+        symlink("missing.md", tmp_dir.join("dangling"))?;
+        symlink(
+            fs::canonicalize("../testdata/test.md")?,
+            tmp_dir.join("outside"),
+        )?;
+        symlink("..", tmp_dir.join("subfolder/loop"))?;
+        let location = format!("{}/", tmp_dir.display());
+        let path = |name: &str| format!("{}{}", location, name);
+
+        let (_, processed_files, _) = safe
+            .files_container_create(
+                Some(&location),
+                None,
+                &CreateOptions {
+                    dry_run: true,
+                    ..CreateOptions::default()
+                },
+            )
+            .await?;
+        for name in &["inside", "dangling", "outside", "subfolder/loop"] {
+            assert_eq!(
+                processed_files[&path(name)],
+                (CONTENT_ADDED_SIGN.to_string(), String::default())
+            );
+        }
+
+        let (_, processed_files, files_map) = safe
+            .files_container_create(
+                Some(&location),
+                None,
+                &CreateOptions {
+                    symlinks: SymlinkPolicy::Skip,
+                    dry_run: true,
+                    ..CreateOptions::default()
+                },
+            )
+            .await?;
+        assert_eq!(processed_files.len(), 2);
+        assert!(processed_files.contains_key(&path("test.md")));
+        assert!(files_map.get("/inside").is_none());
+
+        let (_, processed_files, files_map) = safe
+            .files_container_create(
+                Some(&location),
+                None,
+                &CreateOptions {
+                    symlinks: SymlinkPolicy::FollowWithinRoot,
+                    dry_run: true,
+                    ..CreateOptions::default()
+                },
+            )
+            .await?;
+        assert_eq!(
+            processed_files[&path("inside")],
+            processed_files[&path("test.md")]
+        );
+        assert_eq!(
+            processed_files[&path("outside")],
+            (CONTENT_ADDED_SIGN.to_string(), String::default())
+        );
+        assert!(files_map["/outside"].is_symlink());
+        let (change, msg) = &processed_files[&path("dangling")];
+        assert_eq!(change, CONTENT_ERROR_SIGN);
+        assert!(msg.contains("Dangling symlink"));
+        let (change, msg) = &processed_files[&path("subfolder/loop")];
+        assert_eq!(change, CONTENT_ERROR_SIGN);
+        assert!(msg.contains("Symlink loop"));
+
+        let (_, processed_files, files_map) = safe
+            .files_container_create(
+                Some(&location),
+                None,
+                &CreateOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    dry_run: true,
+                    ..CreateOptions::default()
+                },
+            )
+            .await?;
+        assert!(!processed_files[&path("outside")].1.is_empty());
+        assert!(files_map["/outside"].is_file());
+        assert_eq!(processed_files[&path("dangling")].0, CONTENT_ERROR_SIGN);
+        assert_eq!(
+            processed_files[&path("subfolder/loop")].0,
+            CONTENT_ERROR_SIGN
+        );
+
+        safe.files_config.symlinks_max_depth = 0;
+        let (_, processed_files, _) = safe
+            .files_container_create(
+                Some(&location),
+                None,
+                &CreateOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    dry_run: true,
+                    ..CreateOptions::default()
+                },
+            )
+            .await?;
+        fs::remove_dir_all(&tmp_dir)?;
+
+        let (change, msg) = &processed_files[&path("inside")];
+        assert_eq!(change, CONTENT_ERROR_SIGN);
+        assert!(msg.contains("Too many levels of symbolic links"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_sync_delete_safeguards() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, _, files_map) = safe
+            .files_container_create(
+                Some("../testdata/"),
+                None,
+                &CreateOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    ..CreateOptions::default()
+                },
+            )
+            .await?;
+
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
+
+        // an empty local folder is refused by default
+        let tmp_dir = std::env::temp_dir().join(random_nrs_name());
+        fs::create_dir_all(&tmp_dir)?;
+        let empty_location = format!("{}/", tmp_dir.display());
+        let result = safe
+            .files_container_sync(
+                &empty_location,
+                &xorurl,
+                &SyncOptions {
+                    delete: true, // this sets the delete flag
+                    ..SyncOptions::default()
+                },
+            )
+            .await;
+        fs::remove_dir_all(&tmp_dir)?;
+        match result {
+            Err(Error::DeletionsNotConfirmed { deletions, .. }) => {
+                assert_eq!(deletions.len(), files_map.len())
+            }
+            other => bail!("Unexpected result: {:?}", other),
+        }
+
+        safe.files_config.deletion_safeguards.protected_paths = vec!["/test.md".to_string()];
+        let result = safe
+            .files_container_sync(
+                "../testdata/subfolder/",
+                &xorurl,
+                &SyncOptions {
+                    delete: true,            // this sets the delete flag
+                    confirm_deletions: true, // protected paths are not deleted even if confirmed
+                    ..SyncOptions::default()
+                },
+            )
+            .await;
+        match result {
+            Err(Error::DeletionsNotConfirmed { reason, deletions }) => {
+                assert!(reason.contains("/test.md"));
+                assert!(deletions.contains(&"/test.md".to_string()));
+            }
+            other => bail!("Unexpected result: {:?}", other),
+        }
+
+        safe.files_config.deletion_safeguards.protected_paths = vec![];
+        safe.files_config.deletion_safeguards.max_deletions = Some(1);
+        let (version, new_processed_files, _) = safe
+            .files_container_sync(
+                "../testdata/subfolder/",
+                &xorurl,
+                &SyncOptions {
+                    delete: true,            // this sets the delete flag
+                    confirm_deletions: true, // this confirms the deletions
+                    ..SyncOptions::default()
+                },
+            )
+            .await?;
+
+        assert_eq!(version, 1);
+        assert_eq!(new_processed_files["/test.md"].0, CONTENT_DELETED_SIGN);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_mkdir_symlink_set_metadata() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, _, _) = safe
+            .files_container_create(Some("../testdata/"), None, &CreateOptions::default())
+            .await?;
+
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
+
+        let (version, processed_files, files_map) = safe
+            .files_container_mkdir(
+                &format!("{}/assets/images", xorurl),
+                &UpdateOptions::default(),
+            )
+            .await?;
+        assert_eq!(version, 1);
+        assert_eq!(processed_files.len(), 2);
+        assert!(files_map["/assets"].is_dir());
+        assert!(files_map["/assets/images"].is_dir());
+
+        // creating it again makes no changes
+        let (version, processed_files, _) = safe
+            .files_container_mkdir(
+                &format!("{}/assets/images", xorurl),
+                &UpdateOptions::default(),
+            )
+            .await?;
+        assert_eq!(version, 1);
+        assert!(processed_files.is_empty());
+
+        let symlink_url = format!("{}/assets/latest.md", xorurl);
+        let (version, _, files_map) = safe
+            .files_container_symlink(&symlink_url, "../test.md", false, &UpdateOptions::default())
+            .await?;
+        assert_eq!(version, 2);
+        assert_eq!(
+            files_map["/assets/latest.md"].symlink_target(),
+            Some("../test.md")
         );
+        assert!(matches!(
+            files_map["/assets/latest.md"].file_type,
+            FileItemType::Symlink {
+                target_type: SymlinkTargetType::File,
+                ..
+            }
+        ));
+
+        // an existing entry is only replaced if forced
+        assert!(safe
+            .files_container_symlink(
+                &symlink_url,
+                "../another.md",
+                false,
+                &UpdateOptions::default(),
+            )
+            .await
+            .is_err());
+
+        let metadata = FileAttributes {
+            mode_bits: Some(0o100444),
+            ..FileAttributes::default()
+        };
+        let (version, processed_files, files_map) = safe
+            .files_container_set_metadata(
+                &format!("{}/test.md", xorurl),
+                metadata,
+                &UpdateOptions::default(),
+            )
+            .await?;
+        assert_eq!(version, 3);
+        assert_eq!(processed_files["/test.md"].0, CONTENT_UPDATED_SIGN);
+        assert_eq!(files_map["/test.md"].mode_bits, Some(0o100444));
+        assert_eq!(files_map["/test.md"].readonly, Some(true));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_create_reproducible() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let fixed = gen_datetime_secs() - chrono::Duration::days(1);
+        safe.files_config.timestamps = FilesTimestamps::Fixed(fixed);
+
+        let (_, _, files_map) = safe
+            .files_container_create(
+                Some("../testdata/"),
+                None,
+                &CreateOptions {
+                    dry_run: true,
+                    ..CreateOptions::default()
+                },
+            )
+            .await?;
+        assert!(files_map
+            .values()
+            .all(|file_item| file_item.created == fixed && file_item.modified == fixed));
+
+        let (_, _, other_files_map) = safe
+            .files_container_create(
+                Some("../testdata/"),
+                None,
+                &CreateOptions {
+                    dry_run: true,
+                    ..CreateOptions::default()
+                },
+            )
+            .await?;
+        assert_eq!(
+            files_map::serialise_files_map(&files_map, safe.files_config.files_map_encoding)?,
+            files_map::serialise_files_map(&other_files_map, safe.files_config.files_map_encoding)?
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_add_reproducible() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let fixed = gen_datetime_secs() - chrono::Duration::days(2);
+        safe.files_config.timestamps = FilesTimestamps::Fixed(fixed);
+        let (xorurl, _, _) = safe
+            .files_container_create(Some("../testdata/"), None, &CreateOptions::default())
+            .await?;
+
+        // only the entries changed get the new timestamps
+        let other_fixed = fixed + chrono::Duration::days(1);
+        safe.files_config.timestamps = FilesTimestamps::Fixed(other_fixed);
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
+        let (version, _, files_map) = safe
+            .files_container_add(
+                "../testdata/test.md",
+                &format!("{}/new.md", xorurl),
+                &AddOptions::default(),
+            )
+            .await?;
+        assert_eq!(version, 1);
+        assert_eq!(files_map["/new.md"].modified, other_fixed);
+        assert_eq!(files_map["/test.md"].modified, fixed);
+        assert_eq!(files_map["/subfolder"].created, fixed);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_ls() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, _, files_map) = safe
+            .files_container_create(Some("../testdata/"), None, &CreateOptions::default())
+            .await?;
+
+        let options = LsOptions {
+            recursive: true,
+            ..LsOptions::default()
+        };
+        let listing = retry_loop!(safe.files_container_ls(&xorurl, &options));
+        assert_eq!(listing.entries.len(), files_map.len());
+        assert_eq!(
+            listing.files_count,
+            files_map.values().filter(|item| item.is_file()).count() as u64
+        );
+        assert!(listing.next_cursor.is_none());
+
+        let subfolder = listing
+            .entries
+            .iter()
+            .find(|entry| entry.path == "subfolder")
+            .ok_or_else(|| anyhow!("subfolder not listed"))?;
+        let subfolder_size: u64 = files_map
+            .iter()
+            .filter(|(path, item)| path.starts_with("/subfolder/") && item.is_file())
+            .map(|(_, item)| item.size)
+            .sum();
+        assert_eq!(subfolder.size, subfolder_size);
+
+        let url = format!("{}/subfolder", xorurl);
+        let listing = safe.files_container_ls(&url, &LsOptions::default()).await?;
+        assert!(listing
+            .entries
+            .iter()
+            .all(|entry| !entry.path.contains('/')));
+        assert_eq!(listing.size, subfolder_size);
+
+        // Paginate the listing sorted by size
+        let mut options = LsOptions {
+            sort_by: LsSortBy::Size,
+            reverse: true,
+            limit: Some(2),
+            ..LsOptions::default()
+        };
+        let all = safe
+            .files_container_ls(&xorurl, &LsOptions::default())
+            .await?;
+        let mut paginated = Vec::new();
+        loop {
+            let page = safe.files_container_ls(&xorurl, &options).await?;
+            assert!(page.entries.len() <= 2);
+            paginated.extend(page.entries);
+            match page.next_cursor {
+                Some(cursor) => options.cursor = Some(cursor),
+                None => break,
+            }
+        }
+        assert_eq!(paginated.len(), all.entries.len());
+        assert!(paginated
+            .windows(2)
+            .all(|pair| pair[0].size >= pair[1].size));
+
+        // pages cannot be empty
+        options.limit = Some(0);
+        match safe.files_container_ls(&xorurl, &options).await {
+            Err(Error::InvalidInput(_)) => {}
+            other => bail!(
+                "Unexpected result when listing with a zero limit: {:?}",
+                other
+            ),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_create_with_checksums() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        safe.files_config.checksum = Some(ChecksumAlgorithm::Sha256);
+        let (xorurl, _, files_map) = safe
+            .files_container_create(Some("../testdata/"), None, &CreateOptions::default())
+            .await?;
+
+        let content = fs::read("../testdata/test.md")?;
+        assert_eq!(
+            files_map["/test.md"].checksum,
+            Some(checksums::checksum_data(
+                ChecksumAlgorithm::Sha256,
+                &content
+            ))
+        );
+        assert!(files_map["/subfolder"].checksum.is_none());
+
+        let url = format!("{}/test.md", xorurl);
+        match retry_loop!(safe.fetch(&url, None)) {
+            SafeData::PublicBlob { data, metadata, .. } => {
+                assert_eq!(data, content);
+                assert_eq!(
+                    metadata.and_then(|file_item| file_item.checksum),
+                    files_map["/test.md"].checksum
+                );
+                Ok(())
+            }
+            other => Err(anyhow!("Content fetched is not a file: {:?}", other)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_files_container_upload_plan_dry_run() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (_, processed_files, files_map) = safe
+            .files_container_create(
+                Some("../testdata/"),
+                None,
+                &CreateOptions {
+                    dry_run: true,
+                    ..CreateOptions::default()
+                },
+            )
+            .await?;
+
+        let plan = safe
+            .files_container_upload_plan(None, &processed_files, &files_map)
+            .await?;
 
-        // check sizes are the same but links are different
-        assert_eq!(
-            files_map["/test.md"][PREDICATE_SIZE],
-            new_files_map["/test.md"][PREDICATE_SIZE]
-        );
-        assert_ne!(
-            files_map["/test.md"][PREDICATE_LINK],
-            new_files_map["/test.md"][PREDICATE_LINK]
-        );
+        let links: BTreeSet<&str> = files_map
+            .values()
+            .filter_map(|file_item| file_item.link())
+            .collect();
+        let files_count = files_map
+            .values()
+            .filter(|file_item| file_item.is_file())
+            .count() as u64;
+        assert!(files_count > 0);
+        // One Blob per distinct content, plus the FilesMap
+        assert_eq!(plan.new_blobs, links.len() as u64 + 1);
+        assert_eq!(plan.deduplicated_files, files_count - links.len() as u64);
+        assert!(plan.new_bytes > plan.files_map_size);
+        assert_eq!(plan.sequence_appends, 1);
 
         Ok(())
     }
@@ -1701,7 +4464,14 @@ mod tests {
     async fn test_files_container_sync_with_versioned_target() -> Result<()> {
         let mut safe = new_safe_instance().await?;
         let (xorurl, _, _) = safe
-            .files_container_create(Some("../testdata/"), None, true, true, false)
+            .files_container_create(
+                Some("../testdata/"),
+                None,
+                &CreateOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    ..CreateOptions::default()
+                },
+            )
             .await?;
 
         let _ = retry_loop!(safe.fetch(&xorurl, None));
@@ -1711,11 +4481,11 @@ mod tests {
             .files_container_sync(
                 "../testdata/subfolder/",
                 &versioned_xorurl,
-                false,
-                false,
-                false,
-                true, // this flag requests the update-nrs
-                false,
+                &SyncOptions {
+                    recursive: false,
+                    update_nrs: true, // this flag requests the update-nrs
+                    ..SyncOptions::default()
+                },
             )
             .await
         {
@@ -1741,7 +4511,14 @@ mod tests {
     async fn test_files_container_sync_with_delete() -> Result<()> {
         let mut safe = new_safe_instance().await?;
         let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata/"), None, true, true, false)
+            .files_container_create(
+                Some("../testdata/"),
+                None,
+                &CreateOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    ..CreateOptions::default()
+                },
+            )
             .await?;
 
         assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
@@ -1753,11 +4530,10 @@ mod tests {
             .files_container_sync(
                 "../testdata/subfolder/",
                 &xorurl,
-                true,
-                false,
-                true, // this sets the delete flag
-                false,
-                false,
+                &SyncOptions {
+                    delete: true, // this sets the delete flag
+                    ..SyncOptions::default()
+                },
             )
             .await?;
 
@@ -1773,28 +4549,28 @@ mod tests {
         assert_eq!(new_processed_files[file_path1].0, CONTENT_DELETED_SIGN);
         assert_eq!(
             new_processed_files[file_path1].1,
-            files_map[file_path1][PREDICATE_LINK]
+            files_map[file_path1].link().unwrap_or_default()
         );
 
         let file_path2 = "/another.md";
         assert_eq!(new_processed_files[file_path2].0, CONTENT_DELETED_SIGN);
         assert_eq!(
             new_processed_files[file_path2].1,
-            files_map[file_path2][PREDICATE_LINK]
+            files_map[file_path2].link().unwrap_or_default()
         );
 
         let file_path3 = "/subfolder/subexists.md";
         assert_eq!(new_processed_files[file_path3].0, CONTENT_DELETED_SIGN);
         assert_eq!(
             new_processed_files[file_path3].1,
-            files_map[file_path3][PREDICATE_LINK]
+            files_map[file_path3].link().unwrap_or_default()
         );
 
         let file_path4 = "/noextension";
         assert_eq!(new_processed_files[file_path4].0, CONTENT_DELETED_SIGN);
         assert_eq!(
             new_processed_files[file_path4].1,
-            files_map[file_path4][PREDICATE_LINK]
+            files_map[file_path4].link().unwrap_or_default()
         );
 
         // and finally check the synced file was added
@@ -1802,7 +4578,7 @@ mod tests {
         assert_eq!(new_processed_files[filename5].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             new_processed_files[filename5].1,
-            new_files_map["/subexists.md"][PREDICATE_LINK]
+            new_files_map["/subexists.md"].link().unwrap_or_default()
         );
 
         Ok(())
@@ -1815,11 +4591,11 @@ mod tests {
             .files_container_sync(
                 "../testdata/subfolder/",
                 "some-url",
-                false, // this sets the recursive flag to off
-                false, // do not follow links
-                true,  // this sets the delete flag
-                false,
-                false,
+                &SyncOptions {
+                    recursive: false, // this sets the recursive flag to off
+                    delete: true,     // this sets the delete flag
+                    ..SyncOptions::default()
+                },
             )
             .await
         {
@@ -1842,7 +4618,14 @@ mod tests {
     async fn test_files_container_sync_update_nrs_unversioned_link() -> Result<()> {
         let mut safe = new_safe_instance().await?;
         let (xorurl, _, _) = safe
-            .files_container_create(Some("../testdata/"), None, true, true, false)
+            .files_container_create(
+                Some("../testdata/"),
+                None,
+                &CreateOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    ..CreateOptions::default()
+                },
+            )
             .await?;
 
         let nrsurl = random_nrs_name();
@@ -1877,7 +4660,14 @@ mod tests {
     async fn test_files_container_sync_update_nrs_with_xorurl() -> Result<()> {
         let mut safe = new_safe_instance().await?;
         let (xorurl, _, _) = safe
-            .files_container_create(Some("../testdata/"), None, true, true, false)
+            .files_container_create(
+                Some("../testdata/"),
+                None,
+                &CreateOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    ..CreateOptions::default()
+                },
+            )
             .await?;
 
         let _ = retry_loop!(safe.fetch(&xorurl, None));
@@ -1886,11 +4676,11 @@ mod tests {
             .files_container_sync(
                 "../testdata/subfolder/",
                 &xorurl,
-                false,
-                false,
-                false,
-                true, // this flag requests the update-nrs
-                false,
+                &SyncOptions {
+                    recursive: false,
+                    update_nrs: true, // this flag requests the update-nrs
+                    ..SyncOptions::default()
+                },
             )
             .await
         {
@@ -1913,8 +4703,14 @@ mod tests {
     #[tokio::test]
     async fn test_files_container_sync_update_nrs_versioned_link() -> Result<()> {
         let mut safe = new_safe_instance().await?;
-        let (xorurl, _, _) =
-            retry_loop!(safe.files_container_create(Some("../testdata/"), None, true, true, false));
+        let (xorurl, _, _) = retry_loop!(safe.files_container_create(
+            Some("../testdata/"),
+            None,
+            &CreateOptions {
+                symlinks: SymlinkPolicy::FollowAll,
+                ..CreateOptions::default()
+            },
+        ));
 
         let _ = retry_loop!(safe.fetch(&xorurl, None));
 
@@ -1934,11 +4730,11 @@ mod tests {
         let _ = retry_loop!(safe.files_container_sync(
             "../testdata/subfolder/",
             &nrsurl,
-            false,
-            false,
-            false,
-            true, // this flag requests the update-nrs
-            false,
+            &SyncOptions {
+                recursive: false,
+                update_nrs: true, // this flag requests the update-nrs
+                ..SyncOptions::default()
+            },
         ));
 
         let mut safe_url = SafeUrl::from_url(&xorurl)?;
@@ -1952,8 +4748,14 @@ mod tests {
     #[tokio::test]
     async fn test_files_container_sync_target_path_without_trailing_slash() -> Result<()> {
         let mut safe = new_safe_instance().await?;
-        let (xorurl, processed_files, files_map) =
-            retry_loop!(safe.files_container_create(Some("../testdata/"), None, true, true, false));
+        let (xorurl, processed_files, files_map) = retry_loop!(safe.files_container_create(
+            Some("../testdata/"),
+            None,
+            &CreateOptions {
+                symlinks: SymlinkPolicy::FollowAll,
+                ..CreateOptions::default()
+            },
+        ));
         let _ = retry_loop!(safe.fetch(&xorurl, None));
 
         assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
@@ -1963,11 +4765,7 @@ mod tests {
         let (version, new_processed_files, new_files_map) = retry_loop!(safe.files_container_sync(
             "../testdata/subfolder",
             &safe_url.to_string(),
-            true,
-            false,
-            false,
-            false,
-            false,
+            &SyncOptions::default(),
         ));
 
         assert_eq!(version, 1);
@@ -1984,28 +4782,30 @@ mod tests {
         assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename1].1,
-            new_files_map["/test.md"][PREDICATE_LINK]
+            new_files_map["/test.md"].link().unwrap_or_default()
         );
 
         let filename2 = "../testdata/another.md";
         assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename2].1,
-            new_files_map["/another.md"][PREDICATE_LINK]
+            new_files_map["/another.md"].link().unwrap_or_default()
         );
 
         let filename3 = "../testdata/subfolder/subexists.md";
         assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename3].1,
-            new_files_map["/subfolder/subexists.md"][PREDICATE_LINK]
+            new_files_map["/subfolder/subexists.md"]
+                .link()
+                .unwrap_or_default()
         );
 
         let filename4 = "../testdata/noextension";
         assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename4].1,
-            new_files_map["/noextension"][PREDICATE_LINK]
+            new_files_map["/noextension"].link().unwrap_or_default()
         );
 
         // and finally check the synced file is there
@@ -2013,7 +4813,9 @@ mod tests {
         assert_eq!(new_processed_files[filename5].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             new_processed_files[filename5].1,
-            new_files_map["/path/when/sync/subexists.md"][PREDICATE_LINK]
+            new_files_map["/path/when/sync/subexists.md"]
+                .link()
+                .unwrap_or_default()
         );
 
         Ok(())
@@ -2022,8 +4824,14 @@ mod tests {
     #[tokio::test]
     async fn test_files_container_sync_target_path_with_trailing_slash() -> Result<()> {
         let mut safe = new_safe_instance().await?;
-        let (xorurl, processed_files, files_map) =
-            retry_loop!(safe.files_container_create(Some("../testdata/"), None, true, true, false));
+        let (xorurl, processed_files, files_map) = retry_loop!(safe.files_container_create(
+            Some("../testdata/"),
+            None,
+            &CreateOptions {
+                symlinks: SymlinkPolicy::FollowAll,
+                ..CreateOptions::default()
+            },
+        ));
         let _ = retry_loop!(safe.fetch(&xorurl, None));
 
         assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
@@ -2033,11 +4841,7 @@ mod tests {
         let (version, new_processed_files, new_files_map) = retry_loop!(safe.files_container_sync(
             "../testdata/subfolder",
             &safe_url.to_string(),
-            true,
-            false,
-            false,
-            false,
-            false,
+            &SyncOptions::default(),
         ));
 
         assert_eq!(version, 1);
@@ -2054,28 +4858,30 @@ mod tests {
         assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename1].1,
-            new_files_map["/test.md"][PREDICATE_LINK]
+            new_files_map["/test.md"].link().unwrap_or_default()
         );
 
         let filename2 = "../testdata/another.md";
         assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename2].1,
-            new_files_map["/another.md"][PREDICATE_LINK]
+            new_files_map["/another.md"].link().unwrap_or_default()
         );
 
         let filename3 = "../testdata/subfolder/subexists.md";
         assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename3].1,
-            new_files_map["/subfolder/subexists.md"][PREDICATE_LINK]
+            new_files_map["/subfolder/subexists.md"]
+                .link()
+                .unwrap_or_default()
         );
 
         let filename4 = "../testdata/noextension";
         assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename4].1,
-            new_files_map["/noextension"][PREDICATE_LINK]
+            new_files_map["/noextension"].link().unwrap_or_default()
         );
 
         // and finally check the synced file is there
@@ -2083,7 +4889,9 @@ mod tests {
         assert_eq!(new_processed_files[filename5].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             new_processed_files[filename5].1,
-            new_files_map["/path/when/sync/subfolder/subexists.md"][PREDICATE_LINK]
+            new_files_map["/path/when/sync/subfolder/subexists.md"]
+                .link()
+                .unwrap_or_default()
         );
 
         Ok(())
@@ -2092,8 +4900,14 @@ mod tests {
     #[tokio::test]
     async fn test_files_container_get() -> Result<()> {
         let mut safe = new_safe_instance().await?;
-        let (xorurl, _, files_map) =
-            retry_loop!(safe.files_container_create(Some("../testdata/"), None, true, true, false));
+        let (xorurl, _, files_map) = retry_loop!(safe.files_container_create(
+            Some("../testdata/"),
+            None,
+            &CreateOptions {
+                symlinks: SymlinkPolicy::FollowAll,
+                ..CreateOptions::default()
+            },
+        ));
 
         let (version, fetched_files_map) = retry_loop!(safe.files_container_get(&xorurl));
 
@@ -2114,8 +4928,14 @@ mod tests {
     #[tokio::test]
     async fn test_files_container_version() -> Result<()> {
         let mut safe = new_safe_instance().await?;
-        let (xorurl, _, _) =
-            retry_loop!(safe.files_container_create(Some("../testdata/"), None, true, true, false));
+        let (xorurl, _, _) = retry_loop!(safe.files_container_create(
+            Some("../testdata/"),
+            None,
+            &CreateOptions {
+                symlinks: SymlinkPolicy::FollowAll,
+                ..CreateOptions::default()
+            },
+        ));
 
         let (version, _) = retry_loop!(safe.files_container_get(&xorurl));
         assert_eq!(version, 0);
@@ -2123,11 +4943,10 @@ mod tests {
         let (version, _, _) = retry_loop!(safe.files_container_sync(
             "../testdata/subfolder/",
             &xorurl,
-            true,
-            false,
-            true, // this sets the delete flag,
-            false,
-            false,
+            &SyncOptions {
+                delete: true, // this sets the delete flag,
+                ..SyncOptions::default()
+            },
         ));
         assert_eq!(version, 1);
 
@@ -2143,8 +4962,14 @@ mod tests {
     #[tokio::test]
     async fn test_files_container_get_with_version() -> Result<()> {
         let mut safe = new_safe_instance().await?;
-        let (xorurl, _processed_files, files_map) =
-            retry_loop!(safe.files_container_create(Some("../testdata/"), None, true, true, false));
+        let (xorurl, _processed_files, files_map) = retry_loop!(safe.files_container_create(
+            Some("../testdata/"),
+            None,
+            &CreateOptions {
+                symlinks: SymlinkPolicy::FollowAll,
+                ..CreateOptions::default()
+            },
+        ));
         let _ = retry_loop!(safe.fetch(&xorurl, None));
 
         // let's create a new version of the files container
@@ -2152,11 +4977,10 @@ mod tests {
             .files_container_sync(
                 "../testdata/subfolder/",
                 &xorurl,
-                true,
-                false,
-                true, // this sets the delete flag
-                false,
-                false,
+                &SyncOptions {
+                    delete: true, // this sets the delete flag
+                    ..SyncOptions::default()
+                },
             ));
 
         // let's fetch version 0
@@ -2169,8 +4993,8 @@ mod tests {
         // let's check that one of the files in v1 is still there
         let file_path1 = "/test.md";
         assert_eq!(
-            files_map[file_path1][PREDICATE_LINK],
-            v0_files_map[file_path1][PREDICATE_LINK]
+            files_map[file_path1].link().unwrap_or_default(),
+            v0_files_map[file_path1].link().unwrap_or_default()
         );
 
         // let's fetch version 1
@@ -2215,15 +5039,21 @@ mod tests {
     #[tokio::test]
     async fn test_files_container_create_get_empty_folder() -> Result<()> {
         let mut safe = new_safe_instance().await?;
-        let (xorurl, _processed_files, files_map) =
-            retry_loop!(safe.files_container_create(Some("../testdata/"), None, true, true, false));
+        let (xorurl, _processed_files, files_map) = retry_loop!(safe.files_container_create(
+            Some("../testdata/"),
+            None,
+            &CreateOptions {
+                symlinks: SymlinkPolicy::FollowAll,
+                ..CreateOptions::default()
+            },
+        ));
 
         let (_, files_map_get) = retry_loop!(safe.files_container_get(&xorurl.to_string()));
 
         assert_eq!(files_map, files_map_get);
         assert_eq!(files_map_get["/emptyfolder"], files_map["/emptyfolder"]);
         assert_eq!(
-            files_map_get["/emptyfolder"]["type"],
+            files_map_get["/emptyfolder"].media_type(),
             MIMETYPE_FILESYSTEM_DIR
         );
 
@@ -2236,9 +5066,11 @@ mod tests {
         let (xorurl, _, _) = retry_loop!(safe.files_container_create(
             Some("../testdata/test.md"),
             None,
-            false,
-            true,
-            false
+            &CreateOptions {
+                recursive: false,
+                symlinks: SymlinkPolicy::FollowAll,
+                ..CreateOptions::default()
+            },
         ));
         let _ = retry_loop!(safe.fetch(&xorurl, None));
 
@@ -2257,21 +5089,20 @@ mod tests {
         let _ = retry_loop!(safe.files_container_sync(
             "../testdata/subfolder/",
             &xorurl,
-            false,
-            false,
-            false,
-            false,
-            false,
+            &SyncOptions {
+                recursive: false,
+                ..SyncOptions::default()
+            },
         ));
 
         let _ = retry_loop!(safe.files_container_sync(
             "../testdata/",
             &nrsurl,
-            false,
-            false,
-            false,
-            true, // this flag requests the update-nrs
-            false,
+            &SyncOptions {
+                recursive: false,
+                update_nrs: true, // this flag requests the update-nrs
+                ..SyncOptions::default()
+            },
         ));
 
         // now it should look like:
@@ -2297,9 +5128,11 @@ mod tests {
         let (xorurl, processed_files, files_map) = retry_loop!(safe.files_container_create(
             Some("../testdata/subfolder/"),
             None,
-            false,
-            true,
-            false
+            &CreateOptions {
+                recursive: false,
+                symlinks: SymlinkPolicy::FollowAll,
+                ..CreateOptions::default()
+            },
         ));
         assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
@@ -2308,10 +5141,7 @@ mod tests {
         let (version, new_processed_files, new_files_map) = retry_loop!(safe.files_container_add(
             "../testdata/test.md",
             &format!("{}/new_filename_test.md", xorurl),
-            false,
-            false,
-            false,
-            false,
+            &AddOptions::default(),
         ));
 
         assert_eq!(version, 1);
@@ -2322,21 +5152,23 @@ mod tests {
         assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename1].1,
-            new_files_map["/subexists.md"][PREDICATE_LINK]
+            new_files_map["/subexists.md"].link().unwrap_or_default()
         );
 
         let filename2 = "../testdata/subfolder/sub2.md";
         assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename2].1,
-            new_files_map["/sub2.md"][PREDICATE_LINK]
+            new_files_map["/sub2.md"].link().unwrap_or_default()
         );
 
         let filename3 = "../testdata/test.md";
         assert_eq!(new_processed_files[filename3].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             new_processed_files[filename3].1,
-            new_files_map["/new_filename_test.md"][PREDICATE_LINK]
+            new_files_map["/new_filename_test.md"]
+                .link()
+                .unwrap_or_default()
         );
         Ok(())
     }
@@ -2347,9 +5179,11 @@ mod tests {
         let (xorurl, processed_files, files_map) = retry_loop!(safe.files_container_create(
             Some("../testdata/subfolder/"),
             None,
-            false,
-            true,
-            false
+            &CreateOptions {
+                recursive: false,
+                symlinks: SymlinkPolicy::FollowAll,
+                ..CreateOptions::default()
+            },
         ));
         assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
@@ -2358,10 +5192,11 @@ mod tests {
         let (version, new_processed_files, new_files_map) = retry_loop!(safe.files_container_add(
             "../testdata/test.md",
             &format!("{}/new_filename_test.md", xorurl),
-            false,
-            false,
-            false,
-            true, // dry run
+            &AddOptions {
+                expected_version: ExpectedVersion::Any, // dry run
+                dry_run: true,
+                ..AddOptions::default()
+            },
         ));
 
         assert_eq!(version, 1);
@@ -2373,10 +5208,11 @@ mod tests {
             .files_container_add(
                 "../testdata/test.md",
                 &format!("{}/new_filename_test.md", xorurl),
-                false,
-                false,
-                false,
-                true, // dry run
+                &AddOptions {
+                    expected_version: ExpectedVersion::Any, // dry run
+                    dry_run: true,
+                    ..AddOptions::default()
+                },
             ));
 
         assert_eq!(version, version2);
@@ -2388,11 +5224,15 @@ mod tests {
         assert_eq!(new_processed_files2[filename].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             new_processed_files[filename].1,
-            new_files_map["/new_filename_test.md"][PREDICATE_LINK]
+            new_files_map["/new_filename_test.md"]
+                .link()
+                .unwrap_or_default()
         );
         assert_eq!(
             new_processed_files2[filename].1,
-            new_files_map2["/new_filename_test.md"][PREDICATE_LINK]
+            new_files_map2["/new_filename_test.md"]
+                .link()
+                .unwrap_or_default()
         );
 
         Ok(())
@@ -2404,16 +5244,18 @@ mod tests {
         let (xorurl, processed_files, files_map) = retry_loop!(safe.files_container_create(
             Some("../testdata/subfolder/"),
             None,
-            false,
-            true,
-            false
+            &CreateOptions {
+                recursive: false,
+                symlinks: SymlinkPolicy::FollowAll,
+                ..CreateOptions::default()
+            },
         ));
         assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT); // root "/" + 2 files
         assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         let _ = retry_loop!(safe.fetch(&xorurl, None));
 
         match safe
-            .files_container_add("../testdata", &xorurl, false, false, false, false)
+            .files_container_add("../testdata", &xorurl, &AddOptions::default())
             .await
         {
             Ok(_) => Err(anyhow!(
@@ -2439,9 +5281,11 @@ mod tests {
         let (xorurl, processed_files, files_map) = retry_loop!(safe.files_container_create(
             Some("../testdata/subfolder/"),
             None,
-            false,
-            true,
-            false
+            &CreateOptions {
+                recursive: false,
+                symlinks: SymlinkPolicy::FollowAll,
+                ..CreateOptions::default()
+            },
         ));
         assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
@@ -2451,10 +5295,7 @@ mod tests {
         let (version, new_processed_files, new_files_map) = retry_loop!(safe.files_container_add(
             "../testdata/subfolder/sub2.md",
             &format!("{}/sub2.md", xorurl),
-            false,
-            false,
-            false,
-            false,
+            &AddOptions::default(),
         ));
 
         assert_eq!(version, 0);
@@ -2470,10 +5311,7 @@ mod tests {
         let (version, new_processed_files, new_files_map) = retry_loop!(safe.files_container_add(
             "../testdata/test.md",
             &format!("{}/sub2.md", xorurl),
-            false,
-            false,
-            false,
-            false,
+            &AddOptions::default(),
         ));
 
         assert_eq!(version, 0);
@@ -2489,10 +5327,10 @@ mod tests {
         let (version, new_processed_files, new_files_map) = retry_loop!(safe.files_container_add(
             "../testdata/test.md",
             &format!("{}/sub2.md", xorurl),
-            true, //force it
-            false,
-            false,
-            false,
+            &AddOptions {
+                force: true, //force it
+                ..AddOptions::default()
+            },
         ));
 
         assert_eq!(version, 1);
@@ -2504,7 +5342,7 @@ mod tests {
         );
         assert_eq!(
             new_processed_files["../testdata/test.md"].1,
-            new_files_map["/sub2.md"]["link"]
+            new_files_map["/sub2.md"].link().unwrap_or_default()
         );
 
         Ok(())
@@ -2516,9 +5354,11 @@ mod tests {
         let (xorurl, processed_files, files_map) = retry_loop!(safe.files_container_create(
             Some("../testdata/test.md"),
             None,
-            false,
-            true,
-            false
+            &CreateOptions {
+                recursive: false,
+                symlinks: SymlinkPolicy::FollowAll,
+                ..CreateOptions::default()
+            },
         ));
         assert_eq!(processed_files.len(), 1);
         assert_eq!(files_map.len(), 1);
@@ -2528,11 +5368,10 @@ mod tests {
             .files_container_sync(
                 "/non-existing-path",
                 &xorurl,
-                false,
-                false,
-                false,
-                false,
-                false,
+                &SyncOptions {
+                    recursive: false,
+                    ..SyncOptions::default()
+                },
             )
             .await
         {
@@ -2552,10 +5391,10 @@ mod tests {
             .files_container_add(
                 "/non-existing-path",
                 &format!("{}/test.md", xorurl),
-                true, // force it
-                false,
-                false,
-                false,
+                &AddOptions {
+                    force: true, // force it
+                    ..AddOptions::default()
+                },
             )
             .await
         {
@@ -2580,9 +5419,11 @@ mod tests {
         let (xorurl, processed_files, files_map) = retry_loop!(safe.files_container_create(
             Some("../testdata/subfolder/"),
             None,
-            false,
-            true,
-            false
+            &CreateOptions {
+                recursive: false,
+                symlinks: SymlinkPolicy::FollowAll,
+                ..CreateOptions::default()
+            },
         ));
         assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
@@ -2595,10 +5436,7 @@ mod tests {
         let (version, new_processed_files, new_files_map) = retry_loop!(safe.files_container_add(
             &file_xorurl,
             &format!("{}{}", xorurl, new_filename),
-            false,
-            false,
-            false,
-            false,
+            &AddOptions::default(),
         ));
 
         assert_eq!(version, 1);
@@ -2609,22 +5447,25 @@ mod tests {
         assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename1].1,
-            new_files_map["/subexists.md"][PREDICATE_LINK]
+            new_files_map["/subexists.md"].link().unwrap_or_default()
         );
 
         let filename2 = "../testdata/subfolder/sub2.md";
         assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename2].1,
-            new_files_map["/sub2.md"][PREDICATE_LINK]
+            new_files_map["/sub2.md"].link().unwrap_or_default()
         );
 
         assert_eq!(new_processed_files[new_filename].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             new_processed_files[new_filename].1,
-            new_files_map[new_filename][PREDICATE_LINK]
+            new_files_map[new_filename].link().unwrap_or_default()
+        );
+        assert_eq!(
+            new_files_map[new_filename].link().unwrap_or_default(),
+            file_xorurl
         );
-        assert_eq!(new_files_map[new_filename][PREDICATE_LINK], file_xorurl);
 
         // let's add another file but with the same name
         let data = b"9876543210";
@@ -2632,10 +5473,10 @@ mod tests {
         let (version, new_processed_files, new_files_map) = retry_loop!(safe.files_container_add(
             &other_file_xorurl,
             &format!("{}{}", xorurl, new_filename),
-            true, // force to overwrite it with new link
-            false,
-            false,
-            false,
+            &AddOptions {
+                force: true, // force to overwrite it with new link
+                ..AddOptions::default()
+            },
         ));
 
         assert_eq!(version, 2);
@@ -2644,10 +5485,10 @@ mod tests {
         assert_eq!(new_processed_files[new_filename].0, CONTENT_UPDATED_SIGN);
         assert_eq!(
             new_processed_files[new_filename].1,
-            new_files_map[new_filename][PREDICATE_LINK]
+            new_files_map[new_filename].link().unwrap_or_default()
         );
         assert_eq!(
-            new_files_map[new_filename][PREDICATE_LINK],
+            new_files_map[new_filename].link().unwrap_or_default(),
             other_file_xorurl
         );
 
@@ -2660,9 +5501,11 @@ mod tests {
         let (xorurl, processed_files, files_map) = retry_loop!(safe.files_container_create(
             Some("../testdata/subfolder/"),
             None,
-            false,
-            true,
-            false
+            &CreateOptions {
+                recursive: false,
+                symlinks: SymlinkPolicy::FollowAll,
+                ..CreateOptions::default()
+            },
         ));
         assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
@@ -2675,9 +5518,7 @@ mod tests {
             .files_container_add_from_raw(
                 data,
                 &format!("{}{}", xorurl, new_filename),
-                false,
-                false,
-                false,
+                &AddOptions::default(),
             ));
 
         assert_eq!(version, 1);
@@ -2687,7 +5528,7 @@ mod tests {
         assert_eq!(new_processed_files[new_filename].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             new_processed_files[new_filename].1,
-            new_files_map[new_filename][PREDICATE_LINK]
+            new_files_map[new_filename].link().unwrap_or_default()
         );
 
         // let's add another file but with the same name
@@ -2696,9 +5537,10 @@ mod tests {
             .files_container_add_from_raw(
                 data,
                 &format!("{}{}", xorurl, new_filename),
-                true, // force to overwrite it with new link
-                false,
-                false,
+                &AddOptions {
+                    force: true, // force to overwrite it with new link
+                    ..AddOptions::default()
+                },
             ));
 
         assert_eq!(version, 2);
@@ -2707,7 +5549,7 @@ mod tests {
         assert_eq!(new_processed_files[new_filename].0, CONTENT_UPDATED_SIGN);
         assert_eq!(
             new_processed_files[new_filename].1,
-            new_files_map[new_filename][PREDICATE_LINK]
+            new_files_map[new_filename].link().unwrap_or_default()
         );
         Ok(())
     }
@@ -2715,16 +5557,25 @@ mod tests {
     #[tokio::test]
     async fn test_files_container_remove_path() -> Result<()> {
         let mut safe = new_safe_instance().await?;
-        let (xorurl, processed_files, files_map) =
-            retry_loop!(safe.files_container_create(Some("../testdata/"), None, true, true, false));
+        let (xorurl, processed_files, files_map) = retry_loop!(safe.files_container_create(
+            Some("../testdata/"),
+            None,
+            &CreateOptions {
+                symlinks: SymlinkPolicy::FollowAll,
+                ..CreateOptions::default()
+            },
+        ));
         assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
         assert_eq!(files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
         let _ = retry_loop!(safe.fetch(&xorurl, None));
 
         // let's remove a file first
-        let (version, new_processed_files, new_files_map) = retry_loop!(
-            safe.files_container_remove_path(&format!("{}/test.md", xorurl), false, false, false)
-        );
+        let (version, new_processed_files, new_files_map) = retry_loop!(safe
+            .files_container_remove_path(
+                &format!("{}/test.md", xorurl),
+                false,
+                &UpdateOptions::default(),
+            ));
 
         assert_eq!(version, 1);
         assert_eq!(new_processed_files.len(), 1);
@@ -2734,13 +5585,16 @@ mod tests {
         assert_eq!(new_processed_files[filepath].0, CONTENT_DELETED_SIGN);
         assert_eq!(
             new_processed_files[filepath].1,
-            files_map[filepath][PREDICATE_LINK]
+            files_map[filepath].link().unwrap_or_default()
         );
 
         // let's remove an entire folder now with recursive flag
-        let (version, new_processed_files, new_files_map) = retry_loop!(
-            safe.files_container_remove_path(&format!("{}/subfolder", xorurl), true, false, false)
-        );
+        let (version, new_processed_files, new_files_map) = retry_loop!(safe
+            .files_container_remove_path(
+                &format!("{}/subfolder", xorurl),
+                true,
+                &UpdateOptions::default(),
+            ));
 
         assert_eq!(version, 2);
         assert_eq!(new_processed_files.len(), 2);
@@ -2753,15 +5607,171 @@ mod tests {
         assert_eq!(new_processed_files[filename1].0, CONTENT_DELETED_SIGN);
         assert_eq!(
             new_processed_files[filename1].1,
-            files_map[filename1][PREDICATE_LINK]
+            files_map[filename1].link().unwrap_or_default()
         );
 
         let filename2 = "/subfolder/sub2.md";
         assert_eq!(new_processed_files[filename2].0, CONTENT_DELETED_SIGN);
         assert_eq!(
             new_processed_files[filename2].1,
-            files_map[filename2][PREDICATE_LINK]
+            files_map[filename2].link().unwrap_or_default()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_move() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, processed_files, files_map) = retry_loop!(safe.files_container_create(
+            Some("../testdata/"),
+            None,
+            &CreateOptions {
+                symlinks: SymlinkPolicy::FollowAll,
+                ..CreateOptions::default()
+            },
+        ));
+        assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
+        assert_eq!(files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
+
+        // let's rename a file first
+        let (version, new_processed_files, new_files_map) = retry_loop!(safe.files_container_move(
+            &xorurl,
+            "/test.md",
+            "/renamed.md",
+            false,
+            &UpdateOptions::default(),
+        ));
+
+        assert_eq!(version, 1);
+        assert_eq!(new_processed_files.len(), 2);
+        assert_eq!(new_files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
+        assert!(new_files_map.get("/test.md").is_none());
+        assert_eq!(new_processed_files["/test.md"].0, CONTENT_DELETED_SIGN);
+        assert_eq!(new_processed_files["/renamed.md"].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            new_files_map["/renamed.md"].link().unwrap_or_default(),
+            files_map["/test.md"].link().unwrap_or_default()
+        );
+
+        // moving onto an existing path fails unless forced
+        match safe
+            .files_container_move(
+                &xorurl,
+                "/renamed.md",
+                "/another.md",
+                false,
+                &UpdateOptions::default(),
+            )
+            .await
+        {
+            Err(Error::EntryExists(_)) => {}
+            other => bail!(
+                "Unexpected result when moving onto existing path: {:?}",
+                other
+            ),
+        }
+
+        // let's now move an entire folder
+        let (version, new_processed_files, new_files_map) = retry_loop!(safe.files_container_move(
+            &xorurl,
+            "/subfolder",
+            "/moved",
+            false,
+            &UpdateOptions::default(),
+        ));
+
+        assert_eq!(version, 2);
+        assert_eq!(new_files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
+        let filename1 = "/subfolder/subexists.md";
+        assert_eq!(new_processed_files[filename1].0, CONTENT_DELETED_SIGN);
+        assert_eq!(
+            new_files_map["/moved/subexists.md"]
+                .link()
+                .unwrap_or_default(),
+            files_map[filename1].link().unwrap_or_default()
+        );
+        let filename2 = "/subfolder/sub2.md";
+        assert_eq!(new_processed_files[filename2].0, CONTENT_DELETED_SIGN);
+        assert_eq!(
+            new_files_map["/moved/sub2.md"].link().unwrap_or_default(),
+            files_map[filename2].link().unwrap_or_default()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_copy() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (src_xorurl, _, src_files_map) = retry_loop!(safe.files_container_create(
+            Some("../testdata/"),
+            None,
+            &CreateOptions {
+                symlinks: SymlinkPolicy::FollowAll,
+                ..CreateOptions::default()
+            },
+        ));
+        let (dest_xorurl, _, _) = retry_loop!(safe.files_container_create(
+            None,
+            None,
+            &CreateOptions {
+                symlinks: SymlinkPolicy::FollowAll,
+                ..CreateOptions::default()
+            },
+        ));
+        let _ = retry_loop!(safe.fetch(&src_xorurl, None));
+        let _ = retry_loop!(safe.fetch(&dest_xorurl, None));
+
+        // a folder cannot be copied without the recursive flag
+        let src_url = format!("{}/subfolder", src_xorurl);
+        let dest_url = format!("{}/assets/", dest_xorurl);
+        match safe
+            .files_container_copy(&src_url, &dest_url, false, false, &UpdateOptions::default())
+            .await
+        {
+            Err(Error::InvalidInput(_)) => {}
+            other => bail!("Unexpected result when copying a folder: {:?}", other),
+        }
+
+        let (version, processed_files, files_map) = retry_loop!(safe.files_container_copy(
+            &src_url,
+            &dest_url,
+            true,
+            false,
+            &UpdateOptions::default(),
+        ));
+
+        assert_eq!(version, 1);
+        assert_eq!(processed_files.len(), files_map.len());
+        assert!(files_map["/assets"].is_dir());
+        let filename1 = "/assets/subfolder/subexists.md";
+        assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            files_map[filename1].link().unwrap_or_default(),
+            src_files_map["/subfolder/subexists.md"]
+                .link()
+                .unwrap_or_default()
         );
+        let filename2 = "/assets/subfolder/sub2.md";
+        assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            files_map[filename2].link().unwrap_or_default(),
+            src_files_map["/subfolder/sub2.md"]
+                .link()
+                .unwrap_or_default()
+        );
+
+        // copying the same content again doesn't create a new version
+        let (version, _, _) = retry_loop!(safe.files_container_copy(
+            &src_url,
+            &dest_url,
+            true,
+            false,
+            &UpdateOptions::default(),
+        ));
+        assert_eq!(version, 1);
 
         Ok(())
     }
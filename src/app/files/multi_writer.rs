@@ -0,0 +1,355 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{
+    files_map::FilesMapFormat, is_private_files_container, merge::changed_paths,
+    parse_files_map_link, versions::fetch_files_map_version, CreateOptions, FilesMap,
+    ProcessedFiles, ERROR_MSG_NO_FILES_CONTAINER_FOUND, FILES_CONTAINER_TYPE_TAG,
+};
+use crate::{
+    app::register::EntryHash, Error, Result, Safe, SafeContentType, SafeDataType, SafeUrl, XorUrl,
+};
+use log::{debug, info};
+use safe_network::types::PublicKey;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+// Entry of a Register-based FilesContainer. Besides the link to the FilesMap of the version,
+// it keeps the link to the FilesMap the version was based on, i.e. the view of the
+// FilesContainer its writer had, so concurrent versions can be merged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RegisterFilesEntry {
+    files_map: String,
+    version: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base: Option<String>,
+}
+
+// The current heads of a Register-based FilesContainer, in the order they are merged
+type Heads = Vec<(EntryHash, RegisterFilesEntry)>;
+
+impl Safe {
+    /// # Create a FilesContainer which can be updated by several writers.
+    ///
+    /// The FilesContainer is stored on a Register rather than on a Sequence, allowing the owner
+    /// as well as the `writers` provided to update it. Each update references the versions it
+    /// was based on, and when updates are made concurrently, the FilesContainer is seen as the
+    /// deterministic merge of all of them, applying their changes in order of version, with
+    /// the latest change winning when several of them changed the same path.
+    /// Only the latest version of these FilesContainers can be fetched.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::{Safe, files::{CreateOptions, SymlinkPolicy}};
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    ///     safe.connect(None, None, None).await.unwrap();
+    ///     let writer = safe.keypair().public_key();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create_multi_writer(Some("../testdata"), None, &[writer], &CreateOptions { symlinks: SymlinkPolicy::FollowAll, ..CreateOptions::default() }).await.unwrap();
+    ///     assert!(xorurl.contains("safe://"))
+    /// # });
+    /// ```
+    pub async fn files_container_create_multi_writer(
+        &mut self,
+        location: Option<&str>,
+        dest: Option<&str>,
+        writers: &[PublicKey],
+        options: &CreateOptions,
+    ) -> Result<(XorUrl, ProcessedFiles, FilesMap)> {
+        let (processed_files, files_map) = self
+            .files_map_from_location(location, dest, options)
+            .await?;
+
+        if options.dry_run {
+            return Ok(("".to_string(), processed_files, files_map));
+        }
+
+        let xorname = self
+            .safe_client
+            .store_register(None, FILES_CONTAINER_TYPE_TAG, writers, options.private)
+            .await?;
+        let xorurl = SafeUrl::encode_register(
+            xorname,
+            FILES_CONTAINER_TYPE_TAG,
+            SafeContentType::FilesContainer,
+            self.xorurl_base,
+            options.private,
+        )?;
+
+        // The first version is written just like any other update
+        let safe_url = SafeUrl::from_url(&xorurl)?;
+        self.append_to_register_files_container(&safe_url, &files_map)
+            .await?;
+
+        Ok((xorurl, processed_files, files_map))
+    }
+
+    // Private helper to fetch the merged view of the current heads of a Register-based
+    // FilesContainer, together with its version, which is the highest version of the heads
+    pub(super) async fn fetch_register_files_container(
+        &self,
+        safe_url: &SafeUrl,
+    ) -> Result<(u64, FilesMap)> {
+        let heads = self.fetch_register_heads(safe_url).await?;
+        let version = match heads.last() {
+            Some((_, entry)) => entry.version,
+            None => return Ok((0, FilesMap::default())),
+        };
+        check_register_version(safe_url, version)?;
+
+        let files_map = self.merge_register_heads(safe_url, &heads).await?;
+        Ok((version, files_map))
+    }
+
+    // Private helper to fetch the version, link and format of the FilesMap of the last head of
+    // a Register-based FilesContainer, or None if it's empty. The format of the FilesMaps
+    // is the same for all the heads since it's set when creating the FilesContainer.
+    pub(super) async fn fetch_register_files_map_link(
+        &self,
+        safe_url: &SafeUrl,
+    ) -> Result<Option<(u64, SafeUrl, FilesMapFormat)>> {
+        let heads = self.fetch_register_heads(safe_url).await?;
+        match heads.last() {
+            Some((_, entry)) => {
+                check_register_version(safe_url, entry.version)?;
+                let (link, format) = parse_files_map_link(entry.files_map.as_bytes().to_vec())?;
+                Ok(Some((entry.version, link, format)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // Private helper to write a new version to a Register-based FilesContainer. The new
+    // version references all the current heads as its parents, and it keeps the link to
+    // the merged view of them as the FilesMap it's based on. It returns the new version.
+    pub(super) async fn append_to_register_files_container(
+        &mut self,
+        safe_url: &SafeUrl,
+        new_files_map: &FilesMap,
+    ) -> Result<u64> {
+        let private = is_private_files_container(safe_url);
+        let heads = self.fetch_register_heads(safe_url).await?;
+
+        let (format, previous_files_map, base, version) = match heads.as_slice() {
+            [] => (self.default_files_map_format(), None, None, 0),
+            [(_, head)] => {
+                let (link, format) = parse_files_map_link(head.files_map.as_bytes().to_vec())?;
+                (
+                    format,
+                    Some(link),
+                    Some(head.files_map.clone()),
+                    head.version + 1,
+                )
+            }
+            [.., (_, last)] => {
+                let (link, format) = parse_files_map_link(last.files_map.as_bytes().to_vec())?;
+                let merged = self.merge_register_heads(safe_url, &heads).await?;
+                let base = self
+                    .store_files_map(&merged, private, format, Some(link.clone()))
+                    .await?;
+                (format, Some(link), Some(base), last.version + 1)
+            }
+        };
+
+        let files_map = self
+            .store_files_map(new_files_map, private, format, previous_files_map)
+            .await?;
+        let entry = RegisterFilesEntry {
+            files_map,
+            version,
+            base,
+        };
+        let serialised_entry = serde_json::to_vec(&entry).map_err(|err| {
+            Error::Serialisation(format!(
+                "Couldn't serialise the entry of the FilesContainer: {:?}",
+                err
+            ))
+        })?;
+
+        let parents: BTreeSet<EntryHash> = heads.iter().map(|(hash, _)| *hash).collect();
+        info!(
+            "Writing version {} of FilesContainer at \"{}\" on top of {} head/s",
+            version,
+            safe_url,
+            parents.len()
+        );
+        self.safe_client
+            .write_to_register(safe_url.register_address()?, serialised_entry, parents)
+            .await?;
+
+        Ok(version)
+    }
+
+    // Fetch the current heads of a Register-based FilesContainer, sorted by version and hash
+    async fn fetch_register_heads(&self, safe_url: &SafeUrl) -> Result<Heads> {
+        let entries = match self.fetch_register_entries(safe_url).await {
+            Ok(entries) => entries,
+            Err(Error::EmptyContent(_)) => return Ok(Heads::default()),
+            Err(Error::ContentNotFound(_)) => {
+                return Err(Error::ContentNotFound(
+                    ERROR_MSG_NO_FILES_CONTAINER_FOUND.to_string(),
+                ))
+            }
+            Err(err) => return Err(err),
+        };
+
+        let mut heads = entries
+            .into_iter()
+            .map(|(hash, entry)| {
+                serde_json::from_slice::<RegisterFilesEntry>(&entry)
+                    .map(|entry| (hash, entry))
+                    .map_err(|err| {
+                        Error::ContentError(format!(
+                            "Invalid entry found in the FilesContainer: {:?}",
+                            err
+                        ))
+                    })
+            })
+            .collect::<Result<Heads>>()?;
+        heads.sort_by(|(hash, entry), (other_hash, other)| {
+            entry
+                .version
+                .cmp(&other.version)
+                .then_with(|| hash.cmp(other_hash))
+        });
+
+        Ok(heads)
+    }
+
+    // Merge the FilesMaps of the heads, applying the changes each of them
+    // made on the FilesMap it was based on, in the order of the heads
+    async fn merge_register_heads(
+        &self,
+        safe_url: &SafeUrl,
+        heads: &[(EntryHash, RegisterFilesEntry)],
+    ) -> Result<FilesMap> {
+        let mut files_maps = vec![];
+        for (_, entry) in heads {
+            let files_map = self
+                .fetch_register_files_map(safe_url, entry.version, &entry.files_map)
+                .await?;
+            let base = match &entry.base {
+                Some(base) if heads.len() > 1 => Some(
+                    self.fetch_register_files_map(safe_url, entry.version, base)
+                        .await?,
+                ),
+                _ => None,
+            };
+            files_maps.push((files_map, base));
+        }
+
+        if files_maps.len() > 1 {
+            debug!(
+                "Merging {} concurrent versions of FilesContainer at \"{}\"",
+                files_maps.len(),
+                safe_url
+            );
+        }
+        Ok(merge_heads(files_maps))
+    }
+
+    async fn fetch_register_files_map(
+        &self,
+        safe_url: &SafeUrl,
+        version: u64,
+        link: &str,
+    ) -> Result<FilesMap> {
+        let (link, format) = parse_files_map_link(link.as_bytes().to_vec())?;
+        fetch_files_map_version(self, safe_url, version, link, format).await
+    }
+}
+
+// Returns true if the FilesContainer is stored on a Register, i.e. it supports multiple writers
+pub(super) fn is_register_files_container(safe_url: &SafeUrl) -> bool {
+    matches!(
+        safe_url.data_type(),
+        SafeDataType::PublicRegister | SafeDataType::PrivateRegister
+    )
+}
+
+// Only the latest version of Register-based FilesContainers can be fetched
+fn check_register_version(safe_url: &SafeUrl, latest_version: u64) -> Result<()> {
+    match safe_url.content_version() {
+        Some(version) if version != latest_version => Err(Error::VersionNotFound(format!(
+            "Version '{}' is invalid for FilesContainer found at \"{}\", only its latest version {} can be fetched",
+            version, safe_url, latest_version
+        ))),
+        _ => Ok(()),
+    }
+}
+
+// Deterministic merge of the FilesMaps of concurrent heads, each with the FilesMap it was
+// based on. The first FilesMap is taken as is, and the changes each of the others made on its
+// base are applied on top of it in order, thus later changes win when they touch the same paths.
+fn merge_heads(heads: Vec<(FilesMap, Option<FilesMap>)>) -> FilesMap {
+    let mut heads = heads.into_iter();
+    let mut merged = match heads.next() {
+        Some((files_map, _)) => files_map,
+        None => return FilesMap::default(),
+    };
+
+    for (files_map, base) in heads {
+        let base = base.unwrap_or_default();
+        for path in changed_paths(&base, &files_map) {
+            match files_map.get(&path) {
+                Some(file_item) => {
+                    merged.insert(path, file_item.clone());
+                }
+                None => {
+                    merged.remove(&path);
+                }
+            }
+        }
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::files::{FileItem, FileItemType, FileMeta};
+
+    fn file_item(link: &str) -> FileItem {
+        FileMeta::from_type_and_size("text/plain", 1).to_file_item(FileItemType::File {
+            media_type: "text/plain".to_string(),
+            link: link.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_merge_heads() {
+        let mut base = FilesMap::default();
+        base.insert("/a.txt".to_string(), file_item("safe://a"));
+        base.insert("/b.txt".to_string(), file_item("safe://b"));
+
+        let mut first = base.clone();
+        first.insert("/c.txt".to_string(), file_item("safe://c"));
+        first.insert("/b.txt".to_string(), file_item("safe://b1"));
+
+        let mut second = base.clone();
+        second.remove("/a.txt");
+        second.insert("/b.txt".to_string(), file_item("safe://b2"));
+
+        let merged = merge_heads(vec![
+            (first.clone(), Some(base.clone())),
+            (second.clone(), Some(base.clone())),
+        ]);
+        let paths: Vec<&str> = merged.keys().map(String::as_str).collect();
+        assert_eq!(paths, vec!["/b.txt", "/c.txt"]);
+        assert_eq!(merged["/b.txt"].link(), Some("safe://b2"));
+
+        // The order of the heads decides which change wins
+        let merged = merge_heads(vec![(second, Some(base.clone())), (first, Some(base))]);
+        assert_eq!(merged["/b.txt"].link(), Some("safe://b1"));
+        assert!(!merged.contains_key("/a.txt"));
+    }
+}
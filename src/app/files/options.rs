@@ -0,0 +1,97 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{ExpectedVersion, FilesComparison, SymlinkPolicy};
+
+/// Options for creating a FilesContainer from a local folder or file
+#[derive(Debug, Clone)]
+pub struct CreateOptions {
+    /// Upload the content of the subdirectories too
+    pub recursive: bool,
+    /// Policy used to decide how the symlinks found are uploaded
+    pub symlinks: SymlinkPolicy,
+    /// Store the FilesContainer and the files uploaded as private data
+    pub private: bool,
+    /// Report the files which would be uploaded without uploading anything
+    pub dry_run: bool,
+}
+
+impl Default for CreateOptions {
+    fn default() -> Self {
+        Self {
+            recursive: true,
+            symlinks: SymlinkPolicy::default(),
+            private: false,
+            dry_run: false,
+        }
+    }
+}
+
+/// Options for syncing a local folder with a FilesContainer
+#[derive(Debug, Clone)]
+pub struct SyncOptions {
+    /// Sync the subdirectories too, which is required to `delete`
+    pub recursive: bool,
+    /// Policy used to decide how the symlinks found are synced
+    pub symlinks: SymlinkPolicy,
+    /// Policy used to decide if a local file differs from its FileItem
+    pub comparison: FilesComparison,
+    /// Remove the files which are not found locally from the FilesContainer
+    pub delete: bool,
+    /// Make the deletions even if they break any of the `FilesConfig::deletion_safeguards`,
+    /// other than the protected paths which are never deleted
+    pub confirm_deletions: bool,
+    /// Update the NRS name the URL was resolved from to link to the new version
+    pub update_nrs: bool,
+    /// Precondition on the version of the FilesContainer for the changes to be committed
+    pub expected_version: ExpectedVersion,
+    /// Report the changes without uploading nor committing anything
+    pub dry_run: bool,
+}
+
+impl Default for SyncOptions {
+    fn default() -> Self {
+        Self {
+            recursive: true,
+            symlinks: SymlinkPolicy::default(),
+            comparison: FilesComparison::default(),
+            delete: false,
+            confirm_deletions: false,
+            update_nrs: false,
+            expected_version: ExpectedVersion::default(),
+            dry_run: false,
+        }
+    }
+}
+
+/// Options for adding a file to a FilesContainer
+#[derive(Debug, Clone, Default)]
+pub struct AddOptions {
+    /// Replace the file if one already exists at the target path
+    pub force: bool,
+    /// Policy used to decide how a local symlink is added, not used when adding raw data
+    pub symlinks: SymlinkPolicy,
+    /// Update the NRS name the URL was resolved from to link to the new version
+    pub update_nrs: bool,
+    /// Precondition on the version of the FilesContainer for the change to be committed
+    pub expected_version: ExpectedVersion,
+    /// Report the change without uploading nor committing anything
+    pub dry_run: bool,
+}
+
+/// Options for committing a change on a FilesContainer, common to the APIs updating it
+#[derive(Debug, Clone, Default)]
+pub struct UpdateOptions {
+    /// Update the NRS name the URL was resolved from to link to the new version
+    pub update_nrs: bool,
+    /// Precondition on the version of the FilesContainer for the change to be committed
+    pub expected_version: ExpectedVersion,
+    /// Report the change without committing anything
+    pub dry_run: bool,
+}
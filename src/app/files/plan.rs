@@ -0,0 +1,233 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{files_map::serialise_files_map, FileItem, FilesMap, ProcessedFiles};
+use crate::{app::consts::*, Result, Safe};
+use log::debug;
+use safe_network::types::Token;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+};
+
+// Number of bytes the storage cost rate is set for
+const BYTES_PER_MIB: u128 = 1024 * 1024;
+
+/// Summary of what an operation on a FilesContainer stores on the network,
+/// built from the outcome of a dry run so it can be reviewed before running it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UploadPlan {
+    /// Number of Blobs to be stored, including the FilesMap
+    pub new_blobs: u64,
+    /// Total size in bytes of the Blobs to be stored
+    pub new_bytes: u64,
+    /// Number of files not stored since their content is already linked from the FilesContainer
+    pub deduplicated_files: u64,
+    /// Total size in bytes of the files deduplicated
+    pub deduplicated_bytes: u64,
+    /// Size in bytes of the serialised FilesMap of the new version
+    pub files_map_size: u64,
+    /// Number of entries to be appended to the FilesContainer's Sequence
+    pub sequence_appends: u64,
+    /// Estimated cost of storing the new Blobs, only available if a
+    /// storage cost rate was set with `FilesConfig::storage_cost_per_mib`
+    pub estimated_cost: Option<Token>,
+}
+
+impl Safe {
+    /// # Build the upload plan of an operation on a FilesContainer from the outcome of its dry run.
+    ///
+    /// The `url` is the FilesContainer the operation was run on, or None if
+    /// it was a dry run of the creation of a new FilesContainer.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::{Safe, files::{CreateOptions, SyncOptions}};
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata"), None, &CreateOptions::default()).await.unwrap();
+    ///     let (_version, processed_files, new_files_map) = safe.files_container_sync("../testdata", &xorurl, &SyncOptions { dry_run: true, ..SyncOptions::default() }).await.unwrap();
+    ///     let plan = safe.files_container_upload_plan(Some(&xorurl), &processed_files, &new_files_map).await.unwrap();
+    ///     println!("{} new Blobs with a total of {} bytes would be stored", plan.new_blobs, plan.new_bytes);
+    /// # });
+    /// ```
+    pub async fn files_container_upload_plan(
+        &self,
+        url: Option<&str>,
+        processed_files: &ProcessedFiles,
+        new_files_map: &FilesMap,
+    ) -> Result<UploadPlan> {
+        let (current_files_map, format) = match url {
+            Some(url) => {
+                let (mut safe_url, _) = self.parse_and_resolve_url(url).await?;
+                safe_url.set_content_version(None);
+                let format = match self.fetch_files_map_link(&safe_url).await? {
+                    Some((_, _, format)) => format,
+                    None => self.default_files_map_format(),
+                };
+                let (_, current_files_map) = self.fetch_files_container(&safe_url).await?;
+                (Some(current_files_map), format)
+            }
+            None => (None, self.default_files_map_format()),
+        };
+
+        let files_map_size = serialise_files_map(new_files_map, format.encoding)?.len() as u64;
+        let plan = upload_plan(
+            current_files_map.as_ref(),
+            processed_files,
+            new_files_map,
+            files_map_size,
+            self.files_config.storage_cost_per_mib,
+        );
+        debug!("Upload plan: {:?}", plan);
+
+        Ok(plan)
+    }
+}
+
+// Build the upload plan given the current FilesMap, or None for a new FilesContainer
+fn upload_plan(
+    current_files_map: Option<&FilesMap>,
+    processed_files: &ProcessedFiles,
+    new_files_map: &FilesMap,
+    files_map_size: u64,
+    cost_per_mib: Option<Token>,
+) -> UploadPlan {
+    let mut stored_links: BTreeSet<&str> = current_files_map
+        .into_iter()
+        .flat_map(|files_map| files_map.values())
+        .filter_map(|file_item| file_item.link())
+        .collect();
+
+    // The ProcessedFiles of local files are keyed by their local path, thus
+    // their FileItems are found by the link they were uploaded to instead
+    let items_by_link: BTreeMap<&str, &FileItem> = new_files_map
+        .values()
+        .filter_map(|file_item| file_item.link().map(|link| (link, file_item)))
+        .collect();
+
+    let mut plan = UploadPlan::default();
+    let mut changes = 0;
+    for (path, (change, link)) in processed_files {
+        if change == CONTENT_ERROR_SIGN {
+            continue;
+        }
+        changes += 1;
+        if change == CONTENT_DELETED_SIGN {
+            continue;
+        }
+
+        let file_item = match new_files_map
+            .get(path)
+            .or_else(|| items_by_link.get(link.as_str()).copied())
+        {
+            Some(file_item) => file_item,
+            None => continue,
+        };
+        if let Some(link) = file_item.link() {
+            if stored_links.insert(link) {
+                plan.new_blobs += 1;
+                plan.new_bytes += file_item.size;
+            } else {
+                plan.deduplicated_files += 1;
+                plan.deduplicated_bytes += file_item.size;
+            }
+        }
+    }
+
+    // A new FilesContainer is always created, even if it's empty,
+    // otherwise a new version is only stored if there was any change
+    if current_files_map.is_none() || changes > 0 {
+        plan.new_blobs += 1;
+        plan.new_bytes += files_map_size;
+        plan.sequence_appends = 1;
+    }
+    plan.files_map_size = files_map_size;
+    plan.estimated_cost = cost_per_mib.map(|rate| {
+        let nanos = (u128::from(plan.new_bytes) * u128::from(rate.as_nano()) + BYTES_PER_MIB - 1)
+            / BYTES_PER_MIB;
+        Token::from_nano(u64::try_from(nanos).unwrap_or(u64::MAX))
+    });
+
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::files::{FileItemType, FileMeta};
+
+    fn file_item(link: &str, size: u64) -> FileItem {
+        FileMeta::from_type_and_size("text/plain", size).to_file_item(FileItemType::File {
+            media_type: "text/plain".to_string(),
+            link: link.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_upload_plan() {
+        let mut current_files_map = FilesMap::default();
+        current_files_map.insert("/a.txt".to_string(), file_item("safe://a", 10));
+        current_files_map.insert("/old.txt".to_string(), file_item("safe://old", 5));
+
+        let mut new_files_map = current_files_map.clone();
+        new_files_map.remove("/old.txt");
+        new_files_map.insert("/copy.txt".to_string(), file_item("safe://a", 10));
+        new_files_map.insert("/b.txt".to_string(), file_item("safe://b", 1024 * 1024));
+        new_files_map.insert("/b2.txt".to_string(), file_item("safe://b", 1024 * 1024));
+
+        // Local files are keyed by their local path, and entries
+        // not read from the local filesystem by their FilesMap path
+        let mut processed_files = ProcessedFiles::new();
+        for (path, change, link) in &[
+            ("/old.txt", CONTENT_DELETED_SIGN, "safe://old"),
+            ("./local/copy.txt", CONTENT_ADDED_SIGN, "safe://a"),
+            ("./local/b.txt", CONTENT_ADDED_SIGN, "safe://b"),
+            ("/b2.txt", CONTENT_ADDED_SIGN, "safe://b"),
+            ("./local/c.txt", CONTENT_ERROR_SIGN, "<error>"),
+        ] {
+            processed_files.insert(path.to_string(), (change.to_string(), link.to_string()));
+        }
+
+        let plan = upload_plan(
+            Some(&current_files_map),
+            &processed_files,
+            &new_files_map,
+            100,
+            Some(Token::from_nano(1_000)),
+        );
+        assert_eq!(plan.new_blobs, 2);
+        assert_eq!(plan.new_bytes, 1024 * 1024 + 100);
+        assert_eq!(plan.deduplicated_files, 2);
+        assert_eq!(plan.deduplicated_bytes, 1024 * 1024 + 10);
+        assert_eq!(plan.files_map_size, 100);
+        assert_eq!(plan.sequence_appends, 1);
+        assert_eq!(plan.estimated_cost, Some(Token::from_nano(1_001)));
+
+        // No changes means no new version, but a new FilesContainer is always stored
+        let processed_files = ProcessedFiles::new();
+        let plan = upload_plan(
+            Some(&current_files_map),
+            &processed_files,
+            &current_files_map,
+            100,
+            None,
+        );
+        assert_eq!(plan.new_blobs, 0);
+        assert_eq!(plan.sequence_appends, 0);
+        assert_eq!(plan.estimated_cost, None);
+
+        let plan = upload_plan(None, &processed_files, &FilesMap::default(), 100, None);
+        assert_eq!(plan.new_blobs, 1);
+        assert_eq!(plan.sequence_appends, 1);
+    }
+}
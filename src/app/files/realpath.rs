@@ -7,10 +7,7 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-use super::{
-    files_map::{FilesMap, GetAttr},
-    metadata::FileMeta,
-};
+use super::files_map::FilesMap;
 use crate::{Error, Result};
 
 pub(crate) trait RealPath {
@@ -59,16 +56,13 @@ impl RealPath for FilesMap {
 
                     match &self.get(&tmppath) {
                         Some(fileitem) => {
-                            let meta = FileMeta::from_file_item(fileitem);
-
-                            if meta.is_symlink() {
+                            if let Some(target_str) = fileitem.symlink_target() {
                                 nlinks += 1;
                                 if nlinks > 16 {
                                     let msg = "Too many levels of symbolic links".to_string();
                                     return Err(Error::ContentNotFound(msg));
                                 }
 
-                                let target_str = &fileitem.getattr("symlink_target")?;
                                 if target_str.is_empty() {
                                     let msg = format!(
                                         "Invalid/corrupted symlink '{}'. missing target.",
@@ -97,7 +91,7 @@ impl RealPath for FilesMap {
                                 path = target;
                                 newpath = Vec::<&str>::new();
                                 break;
-                            } else if meta.is_dir() {
+                            } else if fileitem.is_dir() {
                                 if iter.peek() == None {
                                     ended = true;
                                 }
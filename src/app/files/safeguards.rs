@@ -0,0 +1,257 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{
+    file_system::normalise_path_separator, files_map_path_from_local, get_base_paths,
+    is_same_or_subpath, normalise_files_map_path, FilesMap, ProcessedFiles,
+};
+use crate::{app::consts::*, Error, Result};
+use std::collections::BTreeSet;
+
+/// Safeguards against deleting paths of a FilesContainer by accident when syncing it
+/// with `delete` set, e.g. with the wrong or an empty local folder. The deletions
+/// breaking any of them are refused, unless they are explicitly confirmed, except for
+/// the deletions of protected paths which are always refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeletionSafeguards {
+    /// Maximum number of paths deleted by a single sync
+    pub max_deletions: Option<u64>,
+    /// Maximum percentage of the paths of the FilesContainer deleted by a single sync
+    pub max_deletions_percentage: Option<u8>,
+    /// Refuse deleting any path if no file is found in the local folder
+    pub refuse_empty_source: bool,
+    /// Paths of the FilesContainer which cannot be deleted, along with everything under them,
+    /// not even when the deletions are confirmed
+    pub protected_paths: Vec<String>,
+}
+
+impl Default for DeletionSafeguards {
+    fn default() -> Self {
+        Self {
+            max_deletions: None,
+            max_deletions_percentage: None,
+            refuse_empty_source: true,
+            protected_paths: Vec::new(),
+        }
+    }
+}
+
+impl DeletionSafeguards {
+    // Check the deletions a sync would make against the safeguards, given the number of
+    // paths currently in the FilesContainer and the number of paths found locally.
+    // Confirmed deletions are only checked against the protected paths.
+    pub(super) fn check(
+        &self,
+        deletions: &[String],
+        current_paths: usize,
+        local_paths: usize,
+        confirmed: bool,
+    ) -> Result<()> {
+        if deletions.is_empty() {
+            return Ok(());
+        }
+
+        let protected: Vec<&String> = deletions
+            .iter()
+            .filter(|path| {
+                self.protected_paths
+                    .iter()
+                    .any(|protected| is_same_or_subpath(path, &normalise_files_map_path(protected)))
+            })
+            .collect();
+        if let Some(path) = protected.first() {
+            return Err(refused(
+                format!(
+                    "{} protected path(s) would be deleted, including '{}'",
+                    protected.len(),
+                    path
+                ),
+                deletions,
+            ));
+        }
+
+        if confirmed {
+            return Ok(());
+        }
+
+        if self.refuse_empty_source && local_paths == 0 {
+            return Err(refused(
+                "no files were found in the local folder".to_string(),
+                deletions,
+            ));
+        }
+
+        if let Some(max) = self.max_deletions {
+            if deletions.len() as u64 > max {
+                return Err(refused(
+                    format!(
+                        "{} paths would be deleted, over the maximum of {}",
+                        deletions.len(),
+                        max
+                    ),
+                    deletions,
+                ));
+            }
+        }
+
+        if let Some(max) = self.max_deletions_percentage {
+            if deletions.len() * 100 > usize::from(max) * current_paths {
+                return Err(refused(
+                    format!(
+                        "{} out of {} paths would be deleted, over the maximum of {}%",
+                        deletions.len(),
+                        current_paths,
+                        max
+                    ),
+                    deletions,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// The paths of the FilesContainer a sync with `delete` set would remove, i.e. those not
+// matching any of the local files found, nor being a parent directory of any of them.
+// It mirrors how the FilesMap is synced, without uploading anything.
+pub(super) fn sync_deletions(
+    current_files_map: &FilesMap,
+    location: &str,
+    new_content: &ProcessedFiles,
+    dest_path: Option<&str>,
+) -> Vec<String> {
+    let (location_base_path, dest_base_path) = get_base_paths(location, dest_path);
+    let mut kept = BTreeSet::new();
+    for local_file_name in local_paths(new_content) {
+        let path = files_map_path_from_local(local_file_name, &location_base_path, &dest_base_path);
+        let mut trail = Vec::<&str>::new();
+        for part in path.split('/') {
+            trail.push(part);
+            let ancestor = if trail.len() > 1 {
+                trail.join("/")
+            } else {
+                "/".to_string()
+            };
+            kept.insert(ancestor);
+        }
+    }
+
+    current_files_map
+        .keys()
+        .filter(|path| !kept.contains(*path))
+        .cloned()
+        .collect()
+}
+
+// Local paths found which are synced, i.e. excluding those which failed to be read
+pub(super) fn local_paths(new_content: &ProcessedFiles) -> impl Iterator<Item = &String> {
+    new_content
+        .iter()
+        .filter(|(_, (change, _))| change != CONTENT_ERROR_SIGN)
+        .map(|(local_file_name, _)| local_file_name)
+}
+
+// Number of local paths found which are synced from the location, excluding the location itself
+pub(super) fn local_paths_count(new_content: &ProcessedFiles, location: &str) -> usize {
+    let root = normalise_path_separator(location);
+    local_paths(new_content)
+        .filter(|path| path.trim_end_matches('/') != root.trim_end_matches('/'))
+        .count()
+}
+
+fn refused(reason: String, deletions: &[String]) -> Error {
+    Error::DeletionsNotConfirmed {
+        reason,
+        deletions: deletions.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::files::{FileItem, FileItemType, FileMeta};
+
+    fn file_item(link: &str) -> FileItem {
+        FileMeta::from_type_and_size("text/plain", 1).to_file_item(FileItemType::File {
+            media_type: "text/plain".to_string(),
+            link: link.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_sync_deletions() {
+        let mut current_files_map = FilesMap::default();
+        for path in &["/site", "/site/index.html", "/site/old.html", "/other.md"] {
+            current_files_map.insert(path.to_string(), file_item("safe://link"));
+        }
+
+        let mut new_content = ProcessedFiles::new();
+        new_content.insert(
+            "./local/index.html".to_string(),
+            (CONTENT_ADDED_SIGN.to_string(), String::new()),
+        );
+        new_content.insert(
+            "./local/broken.html".to_string(),
+            (CONTENT_ERROR_SIGN.to_string(), String::new()),
+        );
+
+        let deletions =
+            sync_deletions(&current_files_map, "./local/", &new_content, Some("/site/"));
+        assert_eq!(
+            deletions,
+            vec!["/other.md".to_string(), "/site/old.html".to_string()]
+        );
+        assert_eq!(local_paths(&new_content).count(), 1);
+        assert_eq!(local_paths_count(&new_content, "./local"), 1);
+    }
+
+    #[test]
+    fn test_deletion_safeguards() {
+        let deletions = vec!["/a.md".to_string(), "/docs/b.md".to_string()];
+        let refused_reason = |result: Result<()>| match result {
+            Err(Error::DeletionsNotConfirmed { reason, deletions }) => {
+                assert_eq!(deletions.len(), 2);
+                Some(reason)
+            }
+            _ => None,
+        };
+
+        let safeguards = DeletionSafeguards::default();
+        assert!(safeguards.check(&deletions, 2, 1, false).is_ok());
+        assert!(safeguards.check(&[], 2, 0, false).is_ok());
+        let reason = refused_reason(safeguards.check(&deletions, 2, 0, false));
+        assert!(reason.unwrap_or_default().contains("no files"));
+
+        let safeguards = DeletionSafeguards {
+            protected_paths: vec!["docs".to_string()],
+            ..DeletionSafeguards::default()
+        };
+        let reason = refused_reason(safeguards.check(&deletions, 10, 1, false));
+        assert!(reason.unwrap_or_default().contains("'/docs/b.md'"));
+        assert!(refused_reason(safeguards.check(&deletions, 10, 1, true)).is_some());
+
+        let safeguards = DeletionSafeguards {
+            max_deletions: Some(1),
+            ..DeletionSafeguards::default()
+        };
+        assert!(refused_reason(safeguards.check(&deletions, 10, 1, false)).is_some());
+        assert!(safeguards.check(&deletions, 10, 1, true).is_ok());
+        assert!(DeletionSafeguards::default()
+            .check(&deletions, 2, 0, true)
+            .is_ok());
+
+        let safeguards = DeletionSafeguards {
+            max_deletions_percentage: Some(20),
+            ..DeletionSafeguards::default()
+        };
+        assert!(safeguards.check(&deletions, 10, 1, false).is_ok());
+        assert!(refused_reason(safeguards.check(&deletions, 9, 1, false)).is_some());
+    }
+}
@@ -0,0 +1,295 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{
+    files_map::{
+        deserialise_files_map_shard, serialise_files_map_shard, FilesMap, FilesMapEncoding,
+        FilesMapShard,
+    },
+    RealPath,
+};
+use crate::{Result, Safe, SafeUrl, XorUrl};
+use log::debug;
+use std::collections::BTreeMap;
+
+// Path of the directory which shard is the root of a sharded FilesMap
+const ROOT_DIR: &str = "/";
+
+// Store a FilesMap using the sharded layout, i.e. one shard per directory, returning
+// the XOR-URL of the root shard. If the root shard of the previous version is
+// provided, only the shards which differ from those in it are stored.
+pub(crate) async fn store_sharded_files_map(
+    safe: &Safe,
+    files_map: &FilesMap,
+    private: bool,
+    encoding: FilesMapEncoding,
+    previous_root: Option<SafeUrl>,
+) -> Result<XorUrl> {
+    let (mut shards, children) = split_files_map(files_map);
+
+    // Serialise the shards bottom-up, since each of them contains the links to
+    // its subdirectories' shards, which addresses we can calculate without storing them
+    let mut dirs: Vec<String> = shards.keys().cloned().collect();
+    dirs.sort_by_key(|dir| std::cmp::Reverse(path_depth(dir)));
+    let mut serialised_shards = BTreeMap::<String, (Vec<u8>, XorUrl)>::new();
+    for dir in dirs {
+        let mut shard = shards.remove(&dir).unwrap_or_default();
+        for child in children.get(&dir).into_iter().flatten() {
+            if let Some((_, link)) = serialised_shards.get(child) {
+                shard.shards.insert(child.clone(), link.clone());
+            }
+        }
+        let serialised = serialise_files_map_shard(&shard, encoding)?;
+        let link = safe
+            .files_store_blob(&serialised, None, private, true)
+            .await?;
+        serialised_shards.insert(dir, (serialised, link));
+    }
+
+    // Now store them top-down, skipping the subtrees which are the same
+    // as in the previous version, thus already stored on the network
+    let mut pending = vec![(ROOT_DIR.to_string(), previous_root)];
+    while let Some((dir, previous_link)) = pending.pop() {
+        let (serialised, link) = match serialised_shards.get(&dir) {
+            Some(shard) => shard,
+            None => continue,
+        };
+        let previous_shard = match previous_link {
+            Some(previous) if previous.xorname() == SafeUrl::from_url(link)?.xorname() => {
+                debug!("Shard of FilesMap for \"{}\" is unchanged", dir);
+                continue;
+            }
+            Some(previous) => Some(fetch_files_map_shard(safe, &previous, encoding).await?),
+            None => None,
+        };
+
+        debug!("Storing shard of FilesMap for \"{}\"", dir);
+        let _ = safe
+            .files_store_blob(serialised, None, private, false)
+            .await?;
+
+        for child in children.get(&dir).into_iter().flatten() {
+            let previous_child = previous_shard
+                .as_ref()
+                .and_then(|shard| shard.shards.get(child))
+                .map(|link| SafeUrl::from_url(link))
+                .transpose()?;
+            pending.push((child.clone(), previous_child));
+        }
+    }
+
+    Ok(serialised_shards
+        .remove(ROOT_DIR)
+        .map(|(_, link)| link)
+        .unwrap_or_default())
+}
+
+// Loads the shards of a sharded FilesMap on demand, keeping the FileItems found
+// in the shards loaded so far, and the links to those not loaded yet
+pub(crate) struct ShardsLoader {
+    encoding: FilesMapEncoding,
+    files_map: FilesMap,
+    pending: BTreeMap<String, SafeUrl>,
+}
+
+impl ShardsLoader {
+    pub(crate) fn new(root: SafeUrl, encoding: FilesMapEncoding) -> Self {
+        let mut pending = BTreeMap::new();
+        pending.insert(ROOT_DIR.to_string(), root);
+        Self {
+            encoding,
+            files_map: FilesMap::default(),
+            pending,
+        }
+    }
+
+    // Load all the shards, returning the complete FilesMap
+    pub(crate) async fn load_all(mut self, safe: &Safe) -> Result<FilesMap> {
+        self.load_subtree(safe, ROOT_DIR).await?;
+        Ok(self.files_map)
+    }
+
+    // Resolve the real path of a path within the FilesContainer, loading the shards
+    // found on it, and on the targets of the symlinks found on it. If the real path
+    // is a directory, all the shards of its subtree are loaded too.
+    // It returns the real path and the FilesMap with the FileItems loaded.
+    pub(crate) async fn resolve_path(
+        mut self,
+        safe: &Safe,
+        path: &str,
+    ) -> Result<(String, FilesMap)> {
+        let mut realpath = path.to_string();
+        loop {
+            let loaded = self.load_path(safe, &realpath).await?;
+            let resolved = self.files_map.realpath(path)?;
+            if !loaded && resolved == realpath {
+                break;
+            }
+            realpath = resolved;
+        }
+
+        let is_file = self
+            .files_map
+            .get(&realpath)
+            .map_or(false, |file_item| !file_item.is_dir());
+        if !is_file {
+            self.load_subtree(safe, &realpath).await?;
+        }
+
+        Ok((realpath, self.files_map))
+    }
+
+    // Load the shards of the directories found on a path,
+    // returning true if any of them wasn't loaded yet
+    async fn load_path(&mut self, safe: &Safe, path: &str) -> Result<bool> {
+        let mut loaded = self.load_shard(safe, ROOT_DIR).await?;
+        let mut dir = String::new();
+        for part in path.split('/').filter(|part| !part.is_empty()) {
+            dir.push('/');
+            dir.push_str(part);
+            loaded |= self.load_shard(safe, &dir).await?;
+        }
+
+        Ok(loaded)
+    }
+
+    // Load the shards of a directory and all its subdirectories
+    async fn load_subtree(&mut self, safe: &Safe, path: &str) -> Result<()> {
+        let prefix = format!("{}/", path.trim_end_matches('/'));
+        loop {
+            let dirs: Vec<String> = self
+                .pending
+                .keys()
+                .filter(|dir| *dir == path || dir.starts_with(&prefix))
+                .cloned()
+                .collect();
+            if dirs.is_empty() {
+                return Ok(());
+            }
+
+            for dir in dirs {
+                self.load_shard(safe, &dir).await?;
+            }
+        }
+    }
+
+    // Load the shard of a directory if it's not been loaded yet
+    async fn load_shard(&mut self, safe: &Safe, dir: &str) -> Result<bool> {
+        let link = match self.pending.remove(dir) {
+            Some(link) => link,
+            None => return Ok(false),
+        };
+
+        debug!("Loading shard of FilesMap for \"{}\"", dir);
+        let shard = fetch_files_map_shard(safe, &link, self.encoding).await?;
+        self.files_map.extend(shard.files);
+        for (child, link) in shard.shards {
+            self.pending.insert(child, SafeUrl::from_url(&link)?);
+        }
+
+        Ok(true)
+    }
+}
+
+// Fetch a shard of a sharded FilesMap and deserialise it
+async fn fetch_files_map_shard(
+    safe: &Safe,
+    link: &SafeUrl,
+    encoding: FilesMapEncoding,
+) -> Result<FilesMapShard> {
+    let serialised = safe.fetch_blob(link, None).await?;
+    deserialise_files_map_shard(&serialised, encoding)
+}
+
+// Split a FilesMap into one shard per directory, returning the shards (without the links to
+// their subdirectories' shards yet), and the list of subdirectories of each of them
+fn split_files_map(
+    files_map: &FilesMap,
+) -> (
+    BTreeMap<String, FilesMapShard>,
+    BTreeMap<String, Vec<String>>,
+) {
+    let mut shards = BTreeMap::<String, FilesMapShard>::new();
+    let mut children = BTreeMap::<String, Vec<String>>::new();
+    shards.insert(ROOT_DIR.to_string(), FilesMapShard::default());
+
+    for (path, file_item) in files_map {
+        let parent = parent_dir(path);
+        shards
+            .entry(parent.clone())
+            .or_default()
+            .files
+            .insert(path.clone(), file_item.clone());
+
+        // Make sure all the ancestors have a shard, linked from their own parent
+        let mut dir = parent;
+        while dir != ROOT_DIR {
+            let parent = parent_dir(&dir);
+            let siblings = children.entry(parent.clone()).or_default();
+            if siblings.contains(&dir) {
+                break;
+            }
+            siblings.push(dir.clone());
+            shards.entry(dir).or_default();
+            dir = parent;
+        }
+    }
+
+    (shards, children)
+}
+
+// Path of the directory a path is found in, the root's parent is the root itself
+fn parent_dir(path: &str) -> String {
+    match path.trim_end_matches('/').rfind('/') {
+        Some(0) | None => ROOT_DIR.to_string(),
+        Some(index) => path[..index].to_string(),
+    }
+}
+
+fn path_depth(path: &str) -> usize {
+    path.split('/').filter(|part| !part.is_empty()).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::files::{FileItemType, FileMeta};
+
+    #[test]
+    fn test_split_files_map() {
+        let mut files_map = FilesMap::default();
+        for path in &[
+            "/a.txt",
+            "/dir/b.txt",
+            "/dir/sub/c.txt",
+            "/other/deep/d.txt",
+        ] {
+            let file_item =
+                FileMeta::from_type_and_size("text/plain", 1).to_file_item(FileItemType::File {
+                    media_type: "text/plain".to_string(),
+                    link: "safe://link".to_string(),
+                });
+            files_map.insert(path.to_string(), file_item);
+        }
+
+        let (shards, children) = split_files_map(&files_map);
+        let dirs: Vec<&str> = shards.keys().map(|dir| dir.as_str()).collect();
+        assert_eq!(dirs, vec!["/", "/dir", "/dir/sub", "/other", "/other/deep"]);
+        assert!(shards["/"].files.contains_key("/a.txt"));
+        assert!(shards["/dir/sub"].files.contains_key("/dir/sub/c.txt"));
+        assert!(shards["/other"].files.is_empty());
+
+        let mut root_children = children["/"].clone();
+        root_children.sort();
+        assert_eq!(root_children, vec!["/dir", "/other"]);
+        assert_eq!(children["/other"], vec!["/other/deep"]);
+        assert_eq!(parent_dir("/dir/sub/"), "/dir");
+        assert_eq!(parent_dir("/a.txt"), "/");
+    }
+}
@@ -0,0 +1,180 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{multi_writer::is_register_files_container, FileItem, FilesMap};
+use crate::{app::helpers::systemtime_to_datetime_secs, Error, Result, Safe, SafeUrl};
+use chrono::{DateTime, Utc};
+use std::{
+    env,
+    time::{Duration, UNIX_EPOCH},
+};
+
+// Environment variable with the fixed timestamp to use for reproducible outputs, as
+// defined by https://reproducible-builds.org/specs/source-date-epoch/
+const SOURCE_DATE_EPOCH: &str = "SOURCE_DATE_EPOCH";
+
+/// Source of the timestamps set on the FileItems stored in FilesContainers. Unless the
+/// current time is used, identical content always produces identical FilesMaps, and
+/// therefore the same FilesMap address, as long as it's not stored in private Blobs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilesTimestamps {
+    /// The time the FileItems are created or updated in the FilesContainer
+    Current,
+    /// The original timestamps of the files, i.e. those of the local files they were
+    /// uploaded from, or the Unix epoch for the entries which don't have them
+    Source,
+    /// A fixed time set on all the timestamps of the FileItems
+    Fixed(DateTime<Utc>),
+}
+
+impl Default for FilesTimestamps {
+    fn default() -> Self {
+        Self::Current
+    }
+}
+
+impl FilesTimestamps {
+    /// Fixed timestamps set to the time found in the `SOURCE_DATE_EPOCH`
+    /// environment variable, as seconds since the Unix epoch, if it's set
+    pub fn from_source_date_epoch() -> Result<Option<Self>> {
+        let value = match env::var(SOURCE_DATE_EPOCH) {
+            Ok(value) => value,
+            Err(env::VarError::NotPresent) => return Ok(None),
+            Err(err) => {
+                return Err(Error::InvalidInput(format!(
+                    "Invalid {} value: {}",
+                    SOURCE_DATE_EPOCH, err
+                )))
+            }
+        };
+
+        let secs = value.trim().parse::<u64>().map_err(|err| {
+            Error::InvalidInput(format!(
+                "Invalid {} value '{}': {}",
+                SOURCE_DATE_EPOCH, value, err
+            ))
+        })?;
+        let time = systemtime_to_datetime_secs(UNIX_EPOCH + Duration::from_secs(secs));
+
+        Ok(Some(Self::Fixed(time)))
+    }
+
+    /// Returns true if the timestamps don't depend on when the FileItems are stored
+    pub fn is_reproducible(&self) -> bool {
+        *self != Self::Current
+    }
+
+    // Set the timestamps of a FileItem according to their source
+    fn apply(&self, file_item: &mut FileItem) {
+        match self {
+            Self::Current => {}
+            Self::Source => {
+                let epoch = systemtime_to_datetime_secs(UNIX_EPOCH);
+                let modified = file_item.original_modified.unwrap_or(epoch);
+                file_item.created = file_item.original_created.unwrap_or(modified);
+                file_item.modified = modified;
+            }
+            Self::Fixed(time) => {
+                file_item.created = *time;
+                file_item.modified = *time;
+                file_item.original_created = file_item.original_created.map(|_| *time);
+                file_item.original_modified = file_item.original_modified.map(|_| *time);
+            }
+        }
+    }
+}
+
+impl Safe {
+    // Private helper to set the timestamps of the FileItems of a
+    // FilesMap about to be stored according to their source
+    pub(super) fn apply_files_timestamps(&self, files_map: &mut FilesMap) {
+        if self.files_config.timestamps.is_reproducible() {
+            files_map
+                .values_mut()
+                .for_each(|file_item| self.files_config.timestamps.apply(file_item));
+        }
+    }
+
+    // Private helper to set the timestamps of the FileItems of a new version of a FilesContainer
+    // according to their source, only for those changed since the version it's based on. The
+    // rest keep the timestamps they were stored with, even if the source of timestamps changed.
+    pub(super) async fn apply_files_timestamps_to_changes(
+        &self,
+        safe_url: &SafeUrl,
+        base_version: u64,
+        files_map: &mut FilesMap,
+    ) -> Result<()> {
+        if !self.files_config.timestamps.is_reproducible() {
+            return Ok(());
+        }
+
+        // Only the latest version of multi-writer FilesContainers can be fetched
+        let mut base_url = safe_url.clone();
+        if !is_register_files_container(safe_url) {
+            base_url.set_content_version(Some(base_version));
+        }
+        let (_, base_files_map) = self.fetch_files_container(&base_url).await?;
+
+        files_map
+            .iter_mut()
+            .filter(|(path, file_item)| base_files_map.get(*path) != Some(*file_item))
+            .for_each(|(_, file_item)| self.files_config.timestamps.apply(file_item));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{
+        files::{files_map::serialise_files_map, FileItemType, FileMeta, FilesMapEncoding},
+        helpers::gen_datetime_secs,
+    };
+
+    #[test]
+    fn test_files_timestamps() -> Result<()> {
+        let original = systemtime_to_datetime_secs(UNIX_EPOCH + Duration::from_secs(1_000));
+        let mut file_item =
+            FileMeta::from_type_and_size("text/plain", 5).to_file_item(FileItemType::File {
+                media_type: "text/plain".to_string(),
+                link: "safe://link".to_string(),
+            });
+        file_item.original_modified = Some(original);
+        let mut other = file_item.clone();
+        other.created = gen_datetime_secs() + chrono::Duration::seconds(10);
+        other.modified = other.created;
+
+        let mut files_map = FilesMap::default();
+        files_map.insert("/a.txt".to_string(), file_item);
+        let mut other_files_map = FilesMap::default();
+        other_files_map.insert("/a.txt".to_string(), other);
+
+        files_map
+            .values_mut()
+            .chain(other_files_map.values_mut())
+            .for_each(|file_item| FilesTimestamps::Source.apply(file_item));
+        assert_eq!(files_map["/a.txt"].created, original);
+        assert_eq!(files_map["/a.txt"].modified, original);
+        for encoding in &[FilesMapEncoding::JsonLd, FilesMapEncoding::MsgPackZstd] {
+            assert_eq!(
+                serialise_files_map(&files_map, *encoding)?,
+                serialise_files_map(&other_files_map, *encoding)?
+            );
+        }
+
+        let fixed = systemtime_to_datetime_secs(UNIX_EPOCH + Duration::from_secs(42));
+        let mut file_item = files_map["/a.txt"].clone();
+        FilesTimestamps::Fixed(fixed).apply(&mut file_item);
+        assert_eq!(file_item.created, fixed);
+        assert_eq!(file_item.original_modified, Some(fixed));
+        assert_eq!(file_item.original_created, None);
+
+        Ok(())
+    }
+}
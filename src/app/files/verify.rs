@@ -0,0 +1,229 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{
+    file_system::{file_system_dir_walk, follow_symlink, normalise_path_separator},
+    files_map_path_from_local, get_base_paths, is_private_files_container, is_same_or_subpath,
+    metadata::get_metadata,
+    FileItem, SymlinkPolicy,
+};
+use crate::{app::consts::*, Result, Safe, SafeDataType, SafeUrl};
+use log::{debug, info};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+
+/// Outcome of verifying a FilesContainer against a local folder.
+/// All the paths reported are paths within the FilesContainer.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// Paths found in the local folder but not in the FilesContainer
+    pub missing: BTreeSet<String>,
+    /// Paths found in the FilesContainer but not in the local folder
+    pub extra: BTreeSet<String>,
+    /// Paths found in both but which differ, with the reason why
+    pub mismatched: BTreeMap<String, String>,
+    /// Paths which linked Blob could not be retrieved, or which content doesn't
+    /// hash to its address, with the reason why. Only checked in deep mode.
+    pub corrupted: BTreeMap<String, String>,
+    /// Number of paths found to be the same in both
+    pub verified: u64,
+}
+
+impl VerifyReport {
+    /// Returns true if no difference nor corrupted content was found
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty()
+            && self.extra.is_empty()
+            && self.mismatched.is_empty()
+            && self.corrupted.is_empty()
+    }
+}
+
+impl Safe {
+    /// # Verify a FilesContainer against a local folder.
+    ///
+    /// Every path of the local folder is compared with the FilesContainer, recomputing the
+    /// XOR-URLs of the local files, and reporting the missing, extra and mismatched paths.
+    /// If `deep` is set, each Blob linked from the FilesContainer is fetched too, to confirm
+    /// it's retrievable and that its content hashes to the address it's linked with.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::{Safe, files::{CreateOptions, SymlinkPolicy}};
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, &CreateOptions::default()).await.unwrap();
+    ///     let report = safe.files_container_verify("../testdata/", &xorurl, SymlinkPolicy::Preserve, true).await.unwrap();
+    ///     assert!(report.is_ok());
+    /// # });
+    /// ```
+    pub async fn files_container_verify(
+        &mut self,
+        location: &str,
+        url: &str,
+        symlinks: SymlinkPolicy,
+        deep: bool,
+    ) -> Result<VerifyReport> {
+        let (safe_url, _) = self.parse_and_resolve_url(url).await?;
+        let (version, files_map) = self.fetch_files_container(&safe_url).await?;
+        info!(
+            "Verifying version {} of FilesContainer at \"{}\" against '{}'",
+            version, safe_url, location
+        );
+
+        // Local XOR-URLs are calculated the same way the files would be stored
+        let private = is_private_files_container(&safe_url);
+        let local_files =
+            file_system_dir_walk(self, location, true, symlinks, private, true).await?;
+
+        let (location_base_path, dest_base_path) = get_base_paths(location, Some(safe_url.path()));
+        let mut report = VerifyReport::default();
+        let mut local_paths = BTreeSet::new();
+        for (local_file_name, (change, local_link)) in &local_files {
+            let path =
+                files_map_path_from_local(local_file_name, &location_base_path, &dest_base_path);
+            local_paths.insert(path.clone());
+
+            if change == CONTENT_ERROR_SIGN {
+                report
+                    .mismatched
+                    .insert(path, format!("Failed to read local file: {}", local_link));
+                continue;
+            }
+
+            let file_item = match files_map.get(&path) {
+                Some(file_item) => file_item,
+                None => {
+                    report.missing.insert(path);
+                    continue;
+                }
+            };
+
+            let follow_links = follow_symlink(symlinks, Path::new(local_file_name), location);
+            match local_mismatch(local_file_name, local_link, file_item, follow_links) {
+                Some(reason) => {
+                    report.mismatched.insert(path, reason);
+                }
+                None => report.verified += 1,
+            }
+        }
+
+        let base_path = match dest_base_path.trim_end_matches('/') {
+            "" => "/",
+            base_path => base_path,
+        };
+        report.extra = files_map
+            .keys()
+            .filter(|path| is_same_or_subpath(path, base_path) && !local_paths.contains(*path))
+            .cloned()
+            .collect();
+
+        if deep {
+            for (path, file_item) in files_map
+                .iter()
+                .filter(|(path, _)| local_paths.contains(*path))
+            {
+                if let Some(link) = file_item.link() {
+                    if let Err(reason) = self.verify_blob(link).await {
+                        report.corrupted.insert(path.clone(), reason);
+                    }
+                }
+            }
+        }
+
+        debug!("Verification report: {:?}", report);
+        Ok(report)
+    }
+
+    // Fetch a Blob checking its content hashes to the address it's linked with
+    async fn verify_blob(&self, link: &str) -> std::result::Result<(), String> {
+        let safe_url = SafeUrl::from_url(link).map_err(|err| format!("Invalid link: {}", err))?;
+        let data = self
+            .fetch_blob(&safe_url, None)
+            .await
+            .map_err(|err| format!("Failed to fetch Blob: {}", err))?;
+
+        let private = safe_url.data_type() == SafeDataType::PrivateBlob;
+        let xorurl = self
+            .files_store_blob(&data, None, private, true)
+            .await
+            .map_err(|err| format!("Failed to calculate Blob's address: {}", err))?;
+        let xorname = SafeUrl::from_url(&xorurl)
+            .map_err(|err| format!("Failed to calculate Blob's address: {}", err))?
+            .xorname();
+        if xorname == safe_url.xorname() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Content of Blob hashes to {} instead of the address it's linked with",
+                xorurl
+            ))
+        }
+    }
+}
+
+// Compare a local path with its FileItem, returning the reason why they differ if they do
+fn local_mismatch(
+    local_file_name: &str,
+    local_link: &str,
+    file_item: &FileItem,
+    follow_links: bool,
+) -> Option<String> {
+    let local_path = Path::new(local_file_name);
+    let metadata = match get_metadata(local_path, follow_links) {
+        Ok((metadata, _)) => metadata,
+        Err(err) => return Some(err.to_string()),
+    };
+
+    if metadata.is_dir() {
+        if file_item.is_dir() {
+            None
+        } else {
+            Some("A directory is found locally but not in the FilesContainer".to_string())
+        }
+    } else if metadata.file_type().is_symlink() {
+        let local_target = match fs::read_link(local_path) {
+            Ok(target) => normalise_path_separator(&target.display().to_string()),
+            Err(err) => return Some(format!("Failed to read local symlink: {}", err)),
+        };
+        match file_item.symlink_target() {
+            Some(target) if target == local_target => None,
+            Some(target) => Some(format!(
+                "Symlink targets '{}' instead of '{}'",
+                target, local_target
+            )),
+            None => Some("A symlink is found locally but not in the FilesContainer".to_string()),
+        }
+    } else {
+        match file_item.link() {
+            Some(link) if same_blob(link, local_link) => None,
+            Some(link) => Some(format!(
+                "Content links to {} instead of {}",
+                link, local_link
+            )),
+            None => Some("A file is found locally but not in the FilesContainer".to_string()),
+        }
+    }
+}
+
+// Blobs are the same if their addresses are, regardless of the media type in their links
+fn same_blob(link: &str, other: &str) -> bool {
+    match (SafeUrl::from_url(link), SafeUrl::from_url(other)) {
+        (Ok(url), Ok(other_url)) => {
+            url.xorname() == other_url.xorname() && url.data_type() == other_url.data_type()
+        }
+        _ => link == other,
+    }
+}
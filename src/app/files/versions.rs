@@ -0,0 +1,230 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::files_map::{
+    deserialise_files_map_delta, serialise_files_map_delta, FilesMap, FilesMapDelta, FilesMapFormat,
+};
+use crate::{Error, Result, Safe, SafeUrl};
+use log::debug;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+// Maximum number of FilesMaps kept in the cache
+const FILES_MAPS_CACHE_SIZE: usize = 16;
+
+// Cache of the FilesMaps fetched or reconstructed from deltas, keyed by the link to the version's
+// FilesMap. Since links are to immutable content, cached FilesMaps never go stale, and the least
+// recently used one is evicted when the cache is full.
+#[derive(Clone, Default)]
+pub(crate) struct FilesMapsCache(Arc<Mutex<FilesMapsLru>>);
+
+#[derive(Default)]
+struct FilesMapsLru {
+    files_maps: BTreeMap<String, FilesMap>,
+    // Links of the FilesMaps cached, from the least to the most recently used
+    recent: VecDeque<String>,
+}
+
+impl FilesMapsLru {
+    fn touch(&mut self, key: &str) {
+        if let Some(index) = self.recent.iter().position(|recent| recent == key) {
+            if let Some(key) = self.recent.remove(index) {
+                self.recent.push_back(key);
+            }
+        }
+    }
+}
+
+impl FilesMapsCache {
+    fn get(&self, link: &SafeUrl) -> Option<FilesMap> {
+        let mut cache = self.0.lock().ok()?;
+        let key = link.to_string();
+        let files_map = cache.files_maps.get(&key).cloned()?;
+        cache.touch(&key);
+        Some(files_map)
+    }
+
+    fn insert(&self, link: &SafeUrl, files_map: &FilesMap) {
+        if let Ok(mut cache) = self.0.lock() {
+            let key = link.to_string();
+            if cache.files_maps.contains_key(&key) {
+                cache.touch(&key);
+            } else {
+                if cache.files_maps.len() >= FILES_MAPS_CACHE_SIZE {
+                    if let Some(oldest) = cache.recent.pop_front() {
+                        cache.files_maps.remove(&oldest);
+                    }
+                }
+                cache.recent.push_back(key.clone());
+            }
+            cache.files_maps.insert(key, files_map.clone());
+        }
+    }
+}
+
+// Fetch the FilesMap of a FilesContainer's version given the link to it. If the version
+// is stored as a delta, the FilesMap is rebuilt from the closest snapshot, or the closest
+// version found in the cache, applying the deltas stored since then.
+pub(crate) async fn fetch_files_map_version(
+    safe: &Safe,
+    safe_url: &SafeUrl,
+    version: u64,
+    link: SafeUrl,
+    format: FilesMapFormat,
+) -> Result<FilesMap> {
+    let mut deltas = vec![];
+    let (mut version, mut link, mut format) = (version, link, format);
+    let mut files_map = loop {
+        if let Some(files_map) = safe.files_maps_cache.get(&link) {
+            break files_map;
+        }
+
+        match format.delta_base {
+            None => {
+                let files_map = safe.fetch_files_map_snapshot(&link, format).await?;
+                safe.files_maps_cache.insert(&link, &files_map);
+                break files_map;
+            }
+            Some(base) => {
+                deltas.push((link, format));
+                let (base_link, base_format) =
+                    fetch_base_link(safe, safe_url, version, base).await?;
+                version = base;
+                link = base_link;
+                format = base_format;
+            }
+        }
+    };
+
+    for (delta_link, delta_format) in deltas.into_iter().rev() {
+        debug!("Applying FilesMap delta from: {}", delta_link);
+        let serialised = safe.fetch_blob(&delta_link, None).await?;
+        let delta = deserialise_files_map_delta(&serialised, delta_format.encoding)?;
+        delta.apply(&mut files_map);
+        safe.files_maps_cache.insert(&delta_link, &files_map);
+    }
+
+    Ok(files_map)
+}
+
+// Store the FilesMap of a new version of a FilesContainer, given the link to its current
+// version. It's stored as a delta against the current version, unless deltas are not
+// enabled or the new version is due for a snapshot, i.e. a complete FilesMap.
+pub(crate) async fn store_files_map_version(
+    safe: &mut Safe,
+    safe_url: &SafeUrl,
+    current: Option<(u64, SafeUrl, FilesMapFormat)>,
+    new_files_map: &FilesMap,
+    private: bool,
+) -> Result<String> {
+    let (version, link, format) = match current {
+        Some(current) => current,
+        None => {
+            let format = safe.default_files_map_format();
+            return safe
+                .store_files_map(new_files_map, private, format, None)
+                .await;
+        }
+    };
+
+    let new_version = version + 1;
+    match safe.files_config.files_map_snapshot_interval {
+        Some(interval) if interval > 1 && new_version % interval != 0 => {
+            let base_files_map =
+                fetch_files_map_version(safe, safe_url, version, link, format).await?;
+            let delta = FilesMapDelta::new(version, &base_files_map, new_files_map);
+            let serialised_delta = serialise_files_map_delta(&delta, format.encoding)?;
+            let delta_xorurl = safe
+                .files_store_blob(&serialised_delta, None, private, false)
+                .await?;
+
+            let mut delta_url = SafeUrl::from_url(&delta_xorurl)?;
+            let delta_format = FilesMapFormat {
+                delta_base: Some(version),
+                ..format
+            };
+            delta_format.set_in_files_map_link(&mut delta_url)?;
+            safe.files_maps_cache.insert(&delta_url, new_files_map);
+
+            Ok(delta_url.to_string())
+        }
+        _ => {
+            // Sharded FilesMaps only store the shards which changed since the last snapshot
+            let (mut snapshot_version, mut snapshot_link, mut snapshot_format) =
+                (version, link, format);
+            while let Some(base) = snapshot_format.delta_base {
+                let (base_link, base_format) =
+                    fetch_base_link(safe, safe_url, snapshot_version, base).await?;
+                snapshot_version = base;
+                snapshot_link = base_link;
+                snapshot_format = base_format;
+            }
+
+            let format = FilesMapFormat {
+                delta_base: None,
+                ..format
+            };
+            safe.store_files_map(new_files_map, private, format, Some(snapshot_link))
+                .await
+        }
+    }
+}
+
+// Fetch the link to the FilesMap of the version a delta is based on
+async fn fetch_base_link(
+    safe: &Safe,
+    safe_url: &SafeUrl,
+    version: u64,
+    base: u64,
+) -> Result<(SafeUrl, FilesMapFormat)> {
+    if base >= version {
+        return Err(Error::ContentError(format!(
+            "Invalid base version {} found for the FilesMap delta of version {}",
+            base, version
+        )));
+    }
+
+    let mut base_url = safe_url.clone();
+    base_url.set_content_version(Some(base));
+    match safe.fetch_files_map_link(&base_url).await? {
+        Some((_, link, format)) => Ok((link, format)),
+        None => Err(Error::ContentError(format!(
+            "Base version {} of the FilesMap delta of version {} was not found",
+            base, version
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_files_maps_cache_evicts_least_recently_used() -> Result<()> {
+        let cache = FilesMapsCache::default();
+        let links = (0..=FILES_MAPS_CACHE_SIZE)
+            .map(|i| SafeUrl::from_url(&format!("safe://files-map-{}", i)))
+            .collect::<Result<Vec<_>>>()?;
+
+        for link in &links[..FILES_MAPS_CACHE_SIZE] {
+            cache.insert(link, &FilesMap::default());
+        }
+        // The first one is used again, thus the second one is the least recently used
+        assert!(cache.get(&links[0]).is_some());
+        cache.insert(&links[FILES_MAPS_CACHE_SIZE], &FilesMap::default());
+
+        assert!(cache.get(&links[0]).is_some());
+        assert!(cache.get(&links[1]).is_none());
+        assert!(cache.get(&links[FILES_MAPS_CACHE_SIZE]).is_some());
+
+        Ok(())
+    }
+}
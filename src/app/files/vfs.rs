@@ -0,0 +1,830 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{
+    checksums::checksum_data,
+    is_private_files_container, is_same_or_subpath,
+    media_type::{detect_media_type, xorurl_media_type, RAW_MEDIA_TYPE},
+    metadata::FileMeta,
+    normalise_files_map_path, resolve_symlinks_target_type, ExpectedVersion, FileAttributes,
+    FileItem, FileItemType, FilesMap, ProcessedFiles, RealPath, SymlinkTargetType,
+};
+use crate::{
+    app::{consts::*, helpers::gen_datetime_secs},
+    Error, Result, Safe, SafeUrl,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::{debug, info};
+use std::{
+    cmp::min,
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+/// Type of an entry of a filesystem view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VfsFileType {
+    File,
+    Dir,
+    Symlink,
+}
+
+/// Attributes of an entry of a filesystem view
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VfsAttr {
+    /// Absolute path of the entry, with the symlinks on it resolved
+    pub path: String,
+    pub file_type: VfsFileType,
+    /// Size of the file content in bytes, 0 for directories and symlinks
+    pub size: u64,
+    pub media_type: String,
+    pub created: DateTime<Utc>,
+    pub modified: DateTime<Utc>,
+    pub readonly: Option<bool>,
+    pub mode_bits: Option<u32>,
+}
+
+/// Entry of a directory listed from a filesystem view
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VfsDirEntry {
+    /// Name of the entry within the directory
+    pub name: String,
+    pub attr: VfsAttr,
+}
+
+/// Filesystem view over content stored on the network, e.g. a FilesContainer,
+/// so a mount layer or an application can browse it without its own path logic.
+/// All paths are absolute, and changes are batched until they are committed.
+#[async_trait]
+pub trait Vfs {
+    /// Attributes of the entry at a path, following symlinks
+    async fn lookup(&self, path: &str) -> Result<VfsAttr>;
+
+    /// Attributes of the entry at a path, without following it if it's a symlink
+    async fn getattr(&self, path: &str) -> Result<VfsAttr>;
+
+    /// Entries of the directory at a path, following symlinks
+    async fn readdir(&self, path: &str) -> Result<Vec<VfsDirEntry>>;
+
+    /// Read up to `size` bytes of the file at a path, starting at `offset`
+    async fn read(&self, path: &str, offset: u64, size: u64) -> Result<Vec<u8>>;
+
+    /// Target of the symlink at a path
+    async fn readlink(&self, path: &str) -> Result<String>;
+
+    /// Create an empty file at a path, truncating it if it's an existing file
+    async fn create(&mut self, path: &str) -> Result<VfsAttr>;
+
+    /// Write data to the file at a path starting at `offset`, returning the number of bytes written
+    async fn write(&mut self, path: &str, offset: u64, data: &[u8]) -> Result<u64>;
+
+    /// Create an empty directory at a path
+    async fn mkdir(&mut self, path: &str) -> Result<VfsAttr>;
+
+    /// Create a symlink at a path pointing to the target provided
+    async fn symlink(&mut self, path: &str, target: &str) -> Result<VfsAttr>;
+
+    /// Update the metadata of the entry at a path, following symlinks,
+    /// only the attributes which are set are updated
+    async fn setattr(&mut self, path: &str, attributes: FileAttributes) -> Result<VfsAttr>;
+
+    /// Remove the file, symlink or empty directory at a path
+    async fn unlink(&mut self, path: &str) -> Result<()>;
+
+    /// Rename an entry, together with all its content if it's a directory,
+    /// replacing the entry at the destination if it's not a directory
+    async fn rename(&mut self, from: &str, to: &str) -> Result<()>;
+
+    /// Commit the changes made since the last commit, returning the current version
+    async fn commit(&mut self) -> Result<u64>;
+}
+
+/// Filesystem view over a FilesContainer. The FilesMap of the version opened is fetched once,
+/// and the changes made are applied to it locally, keeping the content written in memory
+/// until the changes are committed as a single new version of the FilesContainer.
+pub struct FilesContainerVfs<'a> {
+    safe: &'a mut Safe,
+    url: String,
+    safe_url: SafeUrl,
+    version: u64,
+    files_map: FilesMap,
+    // Content of the files created or written since the last commit
+    written: BTreeMap<String, Vec<u8>>,
+    changes: ProcessedFiles,
+    read_only: bool,
+}
+
+impl Safe {
+    /// # Open a FilesContainer as a filesystem view.
+    ///
+    /// If the URL targets a specific version the view is read-only, otherwise the changes
+    /// made are committed on top of the latest version, merging them with the versions
+    /// committed in the meantime as long as they didn't change the same paths.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::{Safe, files::{CreateOptions, SymlinkPolicy, Vfs}};
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, &CreateOptions { symlinks: SymlinkPolicy::FollowAll, ..CreateOptions::default() }).await.unwrap();
+    ///     let mut vfs = safe.files_container_vfs(&xorurl).await.unwrap();
+    ///     for entry in vfs.readdir("/").await.unwrap() {
+    ///         println!("{}: {} bytes", entry.name, entry.attr.size);
+    ///     }
+    ///     vfs.create("/new.txt").await.unwrap();
+    ///     vfs.write("/new.txt", 0, b"hello").await.unwrap();
+    ///     let version = vfs.commit().await.unwrap();
+    ///     println!("FilesContainer is now at version: {}", version);
+    /// # });
+    /// ```
+    pub async fn files_container_vfs(&mut self, url: &str) -> Result<FilesContainerVfs<'_>> {
+        let read_only = Safe::parse_url(url)?.content_version().is_some();
+        let (mut safe_url, _) = self.parse_and_resolve_url(url).await?;
+        if !read_only {
+            // The version is removed if the URL was resolved from an NRS name
+            safe_url.set_content_version(None);
+        }
+        safe_url.set_path("");
+
+        let (version, files_map) = self.fetch_files_container(&safe_url).await?;
+        debug!(
+            "Opened version {} of FilesContainer at \"{}\" as a filesystem view",
+            version, safe_url
+        );
+
+        Ok(FilesContainerVfs {
+            safe: self,
+            url: url.to_string(),
+            safe_url,
+            version,
+            files_map,
+            written: BTreeMap::new(),
+            changes: ProcessedFiles::new(),
+            read_only,
+        })
+    }
+}
+
+impl<'a> FilesContainerVfs<'a> {
+    /// Version of the FilesContainer the view is on
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns true if there are changes not yet committed
+    pub fn has_changes(&self) -> bool {
+        !self.changes.is_empty()
+    }
+
+    // Resolve all the symlinks on a path
+    fn resolve(&self, path: &str) -> Result<String> {
+        self.files_map.realpath(&vfs_path(path)?)
+    }
+
+    // Resolve the symlinks on the parent of a path, but not on its last component
+    fn resolve_parent(&self, path: &str) -> Result<String> {
+        let path = vfs_path(path)?;
+        match split_parent(&path) {
+            (parent, name) if !name.is_empty() => {
+                let parent = self.files_map.realpath(&normalise_files_map_path(parent))?;
+                Ok(normalise_files_map_path(&format!("{}/{}", parent, name)))
+            }
+            _ => Ok(path),
+        }
+    }
+
+    // Attributes of the entry at a path with no symlinks on it. Directories may not have
+    // a FileItem, like the root and those of FilesContainers created before empty
+    // directories were supported, in which case they exist if they have any content.
+    fn attr(&self, path: &str) -> Result<VfsAttr> {
+        match self.files_map.get(path) {
+            Some(file_item) => Ok(file_item_attr(path, file_item)),
+            None if path == "/" || self.has_children(path) => {
+                let file_item = FileMeta::from_type_and_size(MIMETYPE_FILESYSTEM_DIR, 0)
+                    .to_file_item(FileItemType::Dir);
+                Ok(file_item_attr(path, &file_item))
+            }
+            None => Err(no_entry_error(path)),
+        }
+    }
+
+    fn has_children(&self, dir: &str) -> bool {
+        self.files_map
+            .keys()
+            .any(|path| path != dir && is_same_or_subpath(path, dir))
+    }
+
+    // Content of a file, either the one written in the view or the one stored on the network
+    async fn content(&self, path: &str, offset: u64, size: u64) -> Result<Vec<u8>> {
+        if let Some(data) = self.written.get(path) {
+            let start = min(offset, data.len() as u64) as usize;
+            let end = min(offset.saturating_add(size), data.len() as u64) as usize;
+            return Ok(data[start..end].to_vec());
+        }
+
+        let file_item = self
+            .files_map
+            .get(path)
+            .ok_or_else(|| no_entry_error(path))?;
+        let end = min(offset.saturating_add(size), file_item.size);
+        match file_item.link() {
+            Some(link) if offset < end => {
+                let safe_url = SafeUrl::from_url(link)?;
+                self.safe
+                    .fetch_blob(&safe_url, Some((Some(offset), Some(end))))
+                    .await
+            }
+            _ => Ok(vec![]),
+        }
+    }
+
+    fn check_writable(&self) -> Result<()> {
+        if self.read_only {
+            Err(Error::InvalidInput(format!(
+                "The filesystem view of version {} of the FilesContainer is read-only",
+                self.version
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_parent_dir(&self, path: &str) -> Result<()> {
+        let parent = normalise_files_map_path(split_parent(path).0);
+        match self.attr(&parent)? {
+            attr if attr.file_type == VfsFileType::Dir => Ok(()),
+            _ => Err(Error::InvalidInput(format!(
+                "The parent of '{}' is not a directory",
+                path
+            ))),
+        }
+    }
+
+    // Make sure a new entry can be created at a path with no symlinks on its parent
+    fn check_new_entry(&self, path: &str) -> Result<()> {
+        if path == "/" || self.files_map.contains_key(path) || self.has_children(path) {
+            return Err(Error::InvalidInput(format!(
+                "An entry already exists at '{}'",
+                path
+            )));
+        }
+        self.check_parent_dir(path)
+    }
+
+    // Record a change made on a path, keeping track of the paths added since the last commit
+    fn record_change(&mut self, path: &str, change: &str, link: &str) {
+        let added = self
+            .changes
+            .get(path)
+            .map_or(false, |(prev, _)| prev == CONTENT_ADDED_SIGN);
+        if added && change == CONTENT_DELETED_SIGN {
+            self.changes.remove(path);
+        } else {
+            let change = if added { CONTENT_ADDED_SIGN } else { change };
+            self.changes
+                .insert(path.to_string(), (change.to_string(), link.to_string()));
+        }
+    }
+}
+
+#[async_trait]
+impl<'a> Vfs for FilesContainerVfs<'a> {
+    async fn lookup(&self, path: &str) -> Result<VfsAttr> {
+        let path = self.resolve(path)?;
+        self.attr(&path)
+    }
+
+    async fn getattr(&self, path: &str) -> Result<VfsAttr> {
+        let path = self.resolve_parent(path)?;
+        self.attr(&path)
+    }
+
+    async fn readdir(&self, path: &str) -> Result<Vec<VfsDirEntry>> {
+        let dir = self.lookup(path).await?;
+        if dir.file_type != VfsFileType::Dir {
+            return Err(Error::InvalidInput(format!(
+                "'{}' is not a directory",
+                dir.path
+            )));
+        }
+
+        // Subdirectories without a FileItem are only found from their content
+        let prefix = format!("{}/", dir.path.trim_end_matches('/'));
+        let names: BTreeSet<&str> = self
+            .files_map
+            .keys()
+            .filter_map(|path| path.strip_prefix(&prefix))
+            .map(|relative| relative.split('/').next().unwrap_or(relative))
+            .filter(|name| !name.is_empty())
+            .collect();
+
+        names
+            .into_iter()
+            .map(|name| {
+                let attr = self.attr(&format!("{}{}", prefix, name))?;
+                Ok(VfsDirEntry {
+                    name: name.to_string(),
+                    attr,
+                })
+            })
+            .collect()
+    }
+
+    async fn read(&self, path: &str, offset: u64, size: u64) -> Result<Vec<u8>> {
+        let attr = self.lookup(path).await?;
+        if attr.file_type != VfsFileType::File {
+            return Err(Error::InvalidInput(format!(
+                "'{}' is not a file",
+                attr.path
+            )));
+        }
+
+        self.content(&attr.path, offset, size).await
+    }
+
+    async fn readlink(&self, path: &str) -> Result<String> {
+        let path = self.resolve_parent(path)?;
+        match self.files_map.get(&path).and_then(FileItem::symlink_target) {
+            Some(target) => Ok(target.to_string()),
+            None => Err(Error::InvalidInput(format!("'{}' is not a symlink", path))),
+        }
+    }
+
+    async fn create(&mut self, path: &str) -> Result<VfsAttr> {
+        self.check_writable()?;
+        let path = self.resolve(path)?;
+        let change = match self.files_map.get(&path) {
+            Some(file_item) if file_item.is_file() => CONTENT_UPDATED_SIGN,
+            Some(_) => {
+                return Err(Error::InvalidInput(format!(
+                    "Cannot create a file at '{}' since it's not a file",
+                    path
+                )))
+            }
+            None => {
+                if self.has_children(&path) {
+                    return Err(Error::InvalidInput(format!(
+                        "Cannot create a file at '{}' since it's a directory",
+                        path
+                    )));
+                }
+                self.check_parent_dir(&path)?;
+                CONTENT_ADDED_SIGN
+            }
+        };
+
+        let media_type = mime_guess::from_path(&path)
+            .first_raw()
+            .unwrap_or(RAW_MEDIA_TYPE)
+            .to_string();
+        let file_item =
+            FileMeta::from_type_and_size(&media_type, 0).to_file_item(FileItemType::File {
+                media_type,
+                link: String::new(),
+            });
+        let attr = file_item_attr(&path, &file_item);
+        self.files_map.insert(path.clone(), file_item);
+        self.written.insert(path.clone(), vec![]);
+        self.record_change(&path, change, "");
+
+        Ok(attr)
+    }
+
+    async fn write(&mut self, path: &str, offset: u64, data: &[u8]) -> Result<u64> {
+        self.check_writable()?;
+        let attr = self.lookup(path).await?;
+        if attr.file_type != VfsFileType::File {
+            return Err(Error::InvalidInput(format!(
+                "'{}' is not a file",
+                attr.path
+            )));
+        }
+
+        let mut content = match self.written.remove(&attr.path) {
+            Some(content) => content,
+            None => self.content(&attr.path, 0, attr.size).await?,
+        };
+        let offset = offset as usize;
+        if content.len() < offset + data.len() {
+            content.resize(offset + data.len(), 0);
+        }
+        content[offset..offset + data.len()].copy_from_slice(data);
+
+        if let Some(file_item) = self.files_map.get_mut(&attr.path) {
+            file_item.size = content.len() as u64;
+            file_item.modified = gen_datetime_secs();
+        }
+        self.written.insert(attr.path.clone(), content);
+        self.record_change(&attr.path, CONTENT_UPDATED_SIGN, "");
+
+        Ok(data.len() as u64)
+    }
+
+    async fn mkdir(&mut self, path: &str) -> Result<VfsAttr> {
+        self.check_writable()?;
+        let path = self.resolve_parent(path)?;
+        self.check_new_entry(&path)?;
+
+        let file_item = FileMeta::from_type_and_size(MIMETYPE_FILESYSTEM_DIR, 0)
+            .to_file_item(FileItemType::Dir);
+        let attr = file_item_attr(&path, &file_item);
+        self.files_map.insert(path.clone(), file_item);
+        self.record_change(&path, CONTENT_ADDED_SIGN, "");
+
+        Ok(attr)
+    }
+
+    async fn symlink(&mut self, path: &str, target: &str) -> Result<VfsAttr> {
+        self.check_writable()?;
+        if target.is_empty() {
+            return Err(Error::InvalidInput(
+                "The target of the symlink cannot be empty".to_string(),
+            ));
+        }
+        let path = self.resolve_parent(path)?;
+        self.check_new_entry(&path)?;
+
+        let file_item = FileMeta::from_type_and_size(MIMETYPE_FILESYSTEM_SYMLINK, 0).to_file_item(
+            FileItemType::Symlink {
+                target: target.to_string(),
+                target_type: SymlinkTargetType::Unknown,
+            },
+        );
+        self.files_map.insert(path.clone(), file_item);
+        resolve_symlinks_target_type(&mut self.files_map);
+        self.record_change(&path, CONTENT_ADDED_SIGN, "");
+
+        self.attr(&path)
+    }
+
+    async fn setattr(&mut self, path: &str, attributes: FileAttributes) -> Result<VfsAttr> {
+        self.check_writable()?;
+        let attr = self.lookup(path).await?;
+        if !self.files_map.contains_key(&attr.path) {
+            // Directories without a FileItem get one to keep their metadata
+            let file_item = FileMeta::from_type_and_size(MIMETYPE_FILESYSTEM_DIR, 0)
+                .to_file_item(FileItemType::Dir);
+            self.files_map.insert(attr.path.clone(), file_item);
+            self.record_change(&attr.path, CONTENT_ADDED_SIGN, "");
+        }
+
+        let link = match self.files_map.get_mut(&attr.path) {
+            Some(file_item) if file_item.set_metadata(&attributes) => {
+                file_item.modified = gen_datetime_secs();
+                Some(file_item.link().unwrap_or_default().to_string())
+            }
+            _ => None,
+        };
+        if let Some(link) = link {
+            self.record_change(&attr.path, CONTENT_UPDATED_SIGN, &link);
+        }
+
+        self.attr(&attr.path)
+    }
+
+    async fn unlink(&mut self, path: &str) -> Result<()> {
+        self.check_writable()?;
+        let attr = self.getattr(path).await?;
+        if attr.file_type == VfsFileType::Dir && self.has_children(&attr.path) {
+            return Err(Error::InvalidInput(format!(
+                "Cannot remove '{}' since it's a directory which is not empty",
+                attr.path
+            )));
+        }
+
+        if let Some(file_item) = self.files_map.remove(&attr.path) {
+            self.written.remove(&attr.path);
+            let link = file_item.link().unwrap_or_default().to_string();
+            self.record_change(&attr.path, CONTENT_DELETED_SIGN, &link);
+        }
+
+        Ok(())
+    }
+
+    async fn rename(&mut self, from: &str, to: &str) -> Result<()> {
+        self.check_writable()?;
+        let from = self.getattr(from).await?.path;
+        let to = self.resolve_parent(to)?;
+        if from == to {
+            return Ok(());
+        }
+        if is_same_or_subpath(&to, &from) {
+            return Err(Error::InvalidInput(format!(
+                "Cannot move '{}' into itself",
+                from
+            )));
+        }
+        self.check_parent_dir(&to)?;
+        if let Ok(existing) = self.attr(&to) {
+            if existing.file_type == VfsFileType::Dir {
+                return Err(Error::InvalidInput(format!(
+                    "Cannot replace '{}' since it's a directory",
+                    to
+                )));
+            }
+            self.unlink(&to).await?;
+        }
+
+        let paths: Vec<String> = self
+            .files_map
+            .keys()
+            .filter(|path| is_same_or_subpath(path, &from))
+            .cloned()
+            .collect();
+        for path in paths {
+            let new_path = format!("{}{}", to, &path[from.len()..]);
+            if let Some(file_item) = self.files_map.remove(&path) {
+                let link = file_item.link().unwrap_or_default().to_string();
+                self.record_change(&path, CONTENT_DELETED_SIGN, &link);
+                self.record_change(&new_path, CONTENT_ADDED_SIGN, &link);
+                self.files_map.insert(new_path.clone(), file_item);
+            }
+            if let Some(content) = self.written.remove(&path) {
+                self.written.insert(new_path, content);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn commit(&mut self) -> Result<u64> {
+        self.check_writable()?;
+        if self.changes.is_empty() {
+            return Ok(self.version);
+        }
+
+        // The content written is uploaded only now, since a file can be written several
+        // times before committing. It's kept until the new version is committed, so
+        // the commit can be retried if anything fails.
+        let private = is_private_files_container(&self.safe_url);
+        for (path, content) in &self.written {
+            let file_item = match self.files_map.get_mut(path) {
+                Some(file_item) => file_item,
+                None => continue,
+            };
+            // Files without a known extension get their media type from their content
+            let media_type = match file_item.media_type() {
+                RAW_MEDIA_TYPE => detect_media_type(Path::new(path), content)
+                    .unwrap_or_else(|| RAW_MEDIA_TYPE.to_string()),
+                media_type => media_type.to_string(),
+            };
+            let link = self
+                .safe
+                .files_store_blob(
+                    content,
+                    xorurl_media_type(Some(&media_type)),
+                    private,
+                    false,
+                )
+                .await?;
+            file_item.file_type = FileItemType::File {
+                media_type,
+                link: link.clone(),
+            };
+            file_item.checksum = self
+                .safe
+                .files_config
+                .checksum
+                .map(|algorithm| checksum_data(algorithm, content));
+            if let Some((_, change_link)) = self.changes.get_mut(path) {
+                *change_link = link;
+            }
+        }
+
+        info!(
+            "Committing {} change/s made on version {} of FilesContainer at \"{}\"",
+            self.changes.len(),
+            self.version,
+            self.safe_url
+        );
+        let (version, files_map) = self
+            .safe
+            .append_version_to_files_container(
+                self.changes.len() as u64,
+                self.version,
+                self.files_map.clone(),
+                &self.url,
+                self.safe_url.clone(),
+                ExpectedVersion::VersionOrMerge(self.version),
+                false,
+                false,
+            )
+            .await?;
+
+        self.version = version;
+        self.files_map = files_map;
+        self.written.clear();
+        self.changes.clear();
+
+        Ok(version)
+    }
+}
+
+// Normalise an absolute path of a filesystem view
+fn vfs_path(path: &str) -> Result<String> {
+    if !path.starts_with('/') {
+        return Err(Error::InvalidInput(format!(
+            "The path '{}' is not an absolute path",
+            path
+        )));
+    }
+
+    Ok(normalise_files_map_path(path))
+}
+
+// Split a path into its parent and its last component
+fn split_parent(path: &str) -> (&str, &str) {
+    match path.rfind('/') {
+        Some(index) => (&path[..index], &path[index + 1..]),
+        None => ("", path),
+    }
+}
+
+fn file_item_attr(path: &str, file_item: &FileItem) -> VfsAttr {
+    let file_type = if file_item.is_dir() {
+        VfsFileType::Dir
+    } else if file_item.is_symlink() {
+        VfsFileType::Symlink
+    } else {
+        VfsFileType::File
+    };
+
+    VfsAttr {
+        path: path.to_string(),
+        file_type,
+        size: file_item.size,
+        media_type: file_item.media_type().to_string(),
+        created: file_item.created,
+        modified: file_item.modified,
+        readonly: file_item.readonly,
+        mode_bits: file_item.mode_bits,
+    }
+}
+
+fn no_entry_error(path: &str) -> Error {
+    Error::ContentNotFound(format!(
+        "No entry found at \"{}\" on the target FilesContainer",
+        path
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::files::SymlinkTargetType;
+    use anyhow::Result;
+
+    fn file_item(file_type: FileItemType) -> FileItem {
+        FileMeta::from_type_and_size("text/plain", 5).to_file_item(file_type)
+    }
+
+    fn vfs(safe: &mut Safe) -> Result<FilesContainerVfs<'_>> {
+        let mut files_map = FilesMap::default();
+        files_map.insert("/dir".to_string(), file_item(FileItemType::Dir));
+        files_map.insert(
+            "/dir/a.txt".to_string(),
+            file_item(FileItemType::File {
+                media_type: "text/plain".to_string(),
+                link: "safe://a".to_string(),
+            }),
+        );
+        // Directory without a FileItem, as in FilesContainers created before empty dirs
+        files_map.insert(
+            "/old/b.txt".to_string(),
+            file_item(FileItemType::File {
+                media_type: "text/plain".to_string(),
+                link: "safe://b".to_string(),
+            }),
+        );
+        files_map.insert(
+            "/link".to_string(),
+            file_item(FileItemType::Symlink {
+                target: "dir".to_string(),
+                target_type: SymlinkTargetType::Unknown,
+            }),
+        );
+
+        Ok(FilesContainerVfs {
+            safe,
+            url: "safe://test".to_string(),
+            safe_url: SafeUrl::from_url("safe://test")?,
+            version: 0,
+            files_map,
+            written: BTreeMap::new(),
+            changes: ProcessedFiles::new(),
+            read_only: false,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_vfs_view() -> Result<()> {
+        let mut safe = Safe::default();
+        let mut vfs = vfs(&mut safe)?;
+
+        let names: Vec<String> = vfs
+            .readdir("/")
+            .await?
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect();
+        assert_eq!(names, vec!["dir", "link", "old"]);
+
+        let attr = vfs.lookup("/link/a.txt").await?;
+        assert_eq!(attr.path, "/dir/a.txt");
+        assert_eq!(attr.file_type, VfsFileType::File);
+        assert_eq!(vfs.getattr("/link").await?.file_type, VfsFileType::Symlink);
+        assert_eq!(vfs.lookup("/link").await?.file_type, VfsFileType::Dir);
+        assert_eq!(vfs.lookup("/old").await?.file_type, VfsFileType::Dir);
+        assert_eq!(vfs.readlink("/link").await?, "dir");
+        assert!(vfs.lookup("/missing").await.is_err());
+        assert!(vfs.lookup("relative").await.is_err());
+
+        // Changes are applied locally until committed
+        vfs.create("/link/new.txt").await?;
+        let _ = vfs.write("/dir/new.txt", 2, b"hello").await?;
+        assert_eq!(vfs.read("/dir/new.txt", 1, 3).await?, b"\0he".to_vec());
+        assert_eq!(vfs.lookup("/dir/new.txt").await?.size, 7);
+
+        vfs.rename("/dir", "/moved").await?;
+        let names: Vec<String> = vfs
+            .readdir("/moved")
+            .await?
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect();
+        assert_eq!(names, vec!["a.txt", "new.txt"]);
+        assert_eq!(vfs.read("/moved/new.txt", 0, 100).await?.len(), 7);
+
+        vfs.unlink("/moved/new.txt").await?;
+        assert!(vfs.unlink("/moved").await.is_err());
+        let changes: Vec<(&str, &str)> = vfs
+            .changes
+            .iter()
+            .map(|(path, (change, _))| (path.as_str(), change.as_str()))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("/dir", CONTENT_DELETED_SIGN),
+                ("/dir/a.txt", CONTENT_DELETED_SIGN),
+                ("/moved", CONTENT_ADDED_SIGN),
+                ("/moved/a.txt", CONTENT_ADDED_SIGN),
+            ]
+        );
+        assert!(vfs.written.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_vfs_mkdir_symlink_setattr() -> Result<()> {
+        let mut safe = Safe::default();
+        let mut vfs = vfs(&mut safe)?;
+
+        assert_eq!(vfs.mkdir("/link/sub").await?.path, "/dir/sub");
+        assert!(vfs.mkdir("/dir/sub").await.is_err());
+        assert!(vfs.mkdir("/dir/a.txt/sub").await.is_err());
+        assert_eq!(vfs.readdir("/dir/sub").await?, vec![]);
+
+        let attr = vfs.symlink("/dir/sub/up", "../a.txt").await?;
+        assert_eq!(attr.file_type, VfsFileType::Symlink);
+        assert_eq!(vfs.lookup("/dir/sub/up").await?.path, "/dir/a.txt");
+        assert!(vfs.symlink("/dir/sub/empty", "").await.is_err());
+
+        let attributes = FileAttributes {
+            mode_bits: Some(0o100444),
+            ..FileAttributes::default()
+        };
+        let attr = vfs.setattr("/link/a.txt", attributes.clone()).await?;
+        assert_eq!(attr.mode_bits, Some(0o100444));
+        assert_eq!(attr.readonly, Some(true));
+        let _ = vfs.setattr("/old", attributes).await?;
+
+        let changes: Vec<(&str, &str)> = vfs
+            .changes
+            .iter()
+            .map(|(path, (change, _))| (path.as_str(), change.as_str()))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("/dir/a.txt", CONTENT_UPDATED_SIGN),
+                ("/dir/sub", CONTENT_ADDED_SIGN),
+                ("/dir/sub/up", CONTENT_ADDED_SIGN),
+                ("/old", CONTENT_ADDED_SIGN),
+            ]
+        );
+
+        Ok(())
+    }
+}
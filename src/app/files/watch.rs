@@ -0,0 +1,440 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{
+    file_system::{file_system_dir_walk, follow_symlink, normalise_path_separator},
+    files_map_path_from_local, files_map_sync, get_base_paths, is_private_files_container,
+    is_same_or_subpath,
+    safeguards::local_paths_count,
+    ExpectedVersion, FilesComparison, FilesMap, ProcessedFiles, SymlinkPolicy, SyncOptions,
+};
+use crate::{app::consts::*, Error, Result, Safe, SafeContentType};
+use futures::{channel::mpsc, StreamExt};
+use log::{debug, info, warn};
+use notify::{op, RawEvent, RecursiveMode, Watcher};
+use std::{
+    cmp::min,
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tokio::time::timeout;
+
+// How often the cancellation handle is checked while waiting for changes
+const CANCELLATION_CHECK_INTERVAL: Duration = Duration::from_millis(200);
+
+// Default time to wait for a burst of changes to settle before syncing them
+const DEFAULT_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Options for watching a local directory to sync it to a FilesContainer
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    /// Watch and sync the subdirectories too
+    pub recursive: bool,
+    /// Policy used to decide how the symlinks found are synced
+    pub symlinks: SymlinkPolicy,
+    /// Policy used to decide if a local file differs from its FileItem
+    pub comparison: FilesComparison,
+    /// Remove the files which are not found locally anymore from the FilesContainer
+    pub delete: bool,
+    /// Make the deletions even if they break any of the `FilesConfig::deletion_safeguards`,
+    /// other than the protected paths which are never deleted
+    pub confirm_deletions: bool,
+    /// Update the NRS name the URL was resolved from to link to each new version
+    pub update_nrs: bool,
+    /// Time without changes to wait for before syncing a batch of changes
+    pub debounce: Duration,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            recursive: true,
+            symlinks: SymlinkPolicy::default(),
+            comparison: FilesComparison::default(),
+            delete: false,
+            confirm_deletions: false,
+            update_nrs: false,
+            debounce: DEFAULT_DEBOUNCE,
+        }
+    }
+}
+
+/// Handle to stop watching a local directory. It can be cloned to be
+/// passed to other tasks or threads which need to stop the watch.
+#[derive(Debug, Clone, Default)]
+pub struct WatchCancellation {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl WatchCancellation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop watching. The batch being synced, if any, is completed before stopping,
+    /// while the changes notified but not being synced yet are discarded.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+impl Safe {
+    /// # Watch a local folder, continuously syncing its changes to a FilesContainer.
+    /// Changes are debounced and synced in batches, each of them producing a new version
+    /// of the FilesContainer if there was any change, with the same semantics as
+    /// `files_container_sync`. Only the paths notified as changed are read and synced,
+    /// unless the notifications were incomplete, in which case the whole folder is
+    /// synced. The outcome of each batch is reported to the callback,
+    /// and watching continues even if a batch fails. It stops when it's cancelled
+    /// through the cancellation handle.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::{Safe, files::{CreateOptions, WatchCancellation, WatchOptions}};
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata"), None, &CreateOptions::default()).await.unwrap();
+    ///     let cancellation = WatchCancellation::new();
+    ///     safe.files_container_watch("../testdata", &xorurl, WatchOptions::default(), cancellation, |batch| {
+    ///         match batch {
+    ///             Ok((version, processed_files, _)) => println!("Synced up version {}: {:?}", version, processed_files),
+    ///             Err(err) => println!("Failed to sync changes: {}", err),
+    ///         }
+    ///     }).await.unwrap();
+    /// # });
+    /// ```
+    pub async fn files_container_watch<F>(
+        &mut self,
+        location: &str,
+        url: &str,
+        options: WatchOptions,
+        cancellation: WatchCancellation,
+        mut on_batch: F,
+    ) -> Result<()>
+    where
+        F: FnMut(Result<(u64, ProcessedFiles, FilesMap)>),
+    {
+        if options.delete && !options.recursive {
+            return Err(Error::InvalidInput(
+                "'delete' is not allowed if 'recursive' is not set".to_string(),
+            ));
+        }
+
+        // The batches may not go through a full sync, thus the target URL is checked now
+        let safe_url = Safe::parse_url(url)?;
+        if safe_url.content_version().is_some() {
+            return Err(Error::InvalidInput(format!(
+                "The target URL cannot contain a version: {}",
+                url
+            )));
+        };
+        if options.update_nrs && safe_url.content_type() != SafeContentType::NrsMapContainer {
+            return Err(Error::InvalidInput(
+                "'update-nrs' is not allowed since the URL provided is not an NRS URL".to_string(),
+            ));
+        }
+
+        // The watcher notifies the events through a std channel, we forward them
+        // from a separate thread to an async channel we can wait on without blocking
+        let (events_sender, events_receiver) = std::sync::mpsc::channel();
+        let mut watcher = notify::raw_watcher(events_sender).map_err(|err| {
+            Error::FileSystemError(format!("Failed to start watching local files: {}", err))
+        })?;
+        let mode = if options.recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        watcher.watch(location, mode).map_err(|err| {
+            Error::FileSystemError(format!("Failed to watch '{}': {}", location, err))
+        })?;
+
+        let (sender, mut events) = mpsc::unbounded::<RawEvent>();
+        let _ = thread::spawn(move || {
+            for event in events_receiver {
+                if sender.unbounded_send(event).is_err() {
+                    break;
+                }
+            }
+        });
+
+        info!("Watching '{}' to sync changes to {}", location, url);
+        while !cancellation.is_cancelled() {
+            let mut changes = LocalChanges::default();
+
+            // Wait for the first change of a batch
+            match timeout(CANCELLATION_CHECK_INTERVAL, events.next()).await {
+                Err(_) => continue,
+                Ok(None) => {
+                    return Err(Error::FileSystemError(format!(
+                        "Stopped receiving notifications of changes in '{}'",
+                        location
+                    )))
+                }
+                Ok(Some(event)) => changes.add(event),
+            }
+
+            // Wait for the burst of changes to settle, checking for cancellation meanwhile
+            let mut last_change = Instant::now();
+            loop {
+                if cancellation.is_cancelled() {
+                    info!("Stopped watching '{}'", location);
+                    return Ok(());
+                }
+                let elapsed = last_change.elapsed();
+                if elapsed >= options.debounce {
+                    break;
+                }
+                let wait = min(options.debounce - elapsed, CANCELLATION_CHECK_INTERVAL);
+                match timeout(wait, events.next()).await {
+                    Err(_) => {}
+                    Ok(None) => break,
+                    Ok(Some(event)) => {
+                        changes.add(event);
+                        last_change = Instant::now();
+                    }
+                }
+            }
+
+            let batch = if changes.full_sync {
+                debug!("Syncing all the content found in '{}'", location);
+                self.files_container_sync(
+                    location,
+                    url,
+                    &SyncOptions {
+                        recursive: options.recursive,
+                        symlinks: options.symlinks,
+                        comparison: options.comparison,
+                        delete: options.delete,
+                        confirm_deletions: options.confirm_deletions,
+                        update_nrs: options.update_nrs,
+                        ..SyncOptions::default()
+                    },
+                )
+                .await
+            } else {
+                debug!(
+                    "Syncing {} path(s) changed in '{}'",
+                    changes.paths.len(),
+                    location
+                );
+                self.files_container_sync_changes(location, url, &changes.paths, &options)
+                    .await
+            };
+            on_batch(batch);
+        }
+
+        info!("Stopped watching '{}'", location);
+        Ok(())
+    }
+
+    // Sync only the local paths changed to a FilesContainer. The paths which exist are
+    // added or updated, directories with all their content, and those which don't exist
+    // anymore are removed from the FilesContainer if 'delete' is set.
+    async fn files_container_sync_changes(
+        &mut self,
+        location: &str,
+        url: &str,
+        changed: &BTreeSet<PathBuf>,
+        options: &WatchOptions,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        let (mut safe_url, _) = self.parse_and_resolve_url(url).await?;
+        safe_url.set_content_version(None);
+        let (current_version, current_files_map) = self
+            .fetch_files_container_for_update(&safe_url, ExpectedVersion::Any)
+            .await?;
+
+        let private = is_private_files_container(&safe_url);
+        let dest_path = safe_url.path().to_string();
+        let (location_base_path, dest_base_path) = get_base_paths(location, Some(&dest_path));
+
+        let root = fs::canonicalize(location).ok();
+        let mut new_content = ProcessedFiles::new();
+        let mut removed = BTreeSet::new();
+        for path in changed {
+            let local_path = match local_change_path(location, root.as_deref(), path) {
+                Some(local_path) => local_path,
+                None => continue,
+            };
+            let follow = follow_symlink(options.symlinks, Path::new(&local_path), location);
+            let metadata = if follow {
+                fs::metadata(&local_path)
+            } else {
+                fs::symlink_metadata(&local_path)
+            };
+            match metadata {
+                Ok(metadata) if metadata.is_dir() => {
+                    if options.recursive {
+                        let content = file_system_dir_walk(
+                            self,
+                            &local_path,
+                            true,
+                            options.symlinks,
+                            private,
+                            true,
+                        )
+                        .await?;
+                        new_content.extend(content);
+                    }
+                }
+                Ok(metadata)
+                    if metadata.file_type().is_symlink()
+                        && options.symlinks == SymlinkPolicy::Skip => {}
+                Ok(_) => {
+                    new_content.insert(
+                        normalise_path_separator(&local_path),
+                        (CONTENT_ADDED_SIGN.to_string(), String::default()),
+                    );
+                }
+                Err(_) => {
+                    removed.insert(files_map_path_from_local(
+                        &local_path,
+                        &location_base_path,
+                        &dest_base_path,
+                    ));
+                }
+            }
+        }
+
+        // Check the paths which would be deleted before uploading anything
+        let deletions: Vec<String> = if options.delete {
+            current_files_map
+                .keys()
+                .filter(|path| {
+                    removed
+                        .iter()
+                        .any(|removed| is_same_or_subpath(path, removed))
+                })
+                .cloned()
+                .collect()
+        } else {
+            Vec::new()
+        };
+        // The local folder is only walked if the number of paths found is needed,
+        // counting them the same way as when syncing the whole folder
+        let local_count = if deletions.is_empty() || options.confirm_deletions {
+            0
+        } else {
+            let local_content = file_system_dir_walk(
+                self,
+                location,
+                options.recursive,
+                options.symlinks,
+                private,
+                true,
+            )
+            .await?;
+            local_paths_count(&local_content, location)
+        };
+        self.files_config.deletion_safeguards.check(
+            &deletions,
+            current_files_map.len(),
+            local_count,
+            options.confirm_deletions,
+        )?;
+
+        let (mut processed_files, mut new_files_map, mut success_count) = files_map_sync(
+            self,
+            current_files_map,
+            location,
+            new_content,
+            Some(&dest_path),
+            false,
+            private,
+            false,
+            false,
+            Some(options.comparison),
+            options.symlinks,
+        )
+        .await?;
+
+        for path in deletions {
+            if let Some(file_item) = new_files_map.remove(&path) {
+                processed_files.insert(
+                    path,
+                    (
+                        CONTENT_DELETED_SIGN.to_string(),
+                        file_item.link().unwrap_or_default().to_string(),
+                    ),
+                );
+                success_count += 1;
+            }
+        }
+
+        let (version, new_files_map) = self
+            .append_version_to_files_container(
+                success_count,
+                current_version,
+                new_files_map,
+                url,
+                safe_url,
+                ExpectedVersion::Any,
+                false,
+                options.update_nrs,
+            )
+            .await?;
+
+        Ok((version, processed_files, new_files_map))
+    }
+}
+
+// Local changes notified during a batch
+#[derive(Default)]
+struct LocalChanges {
+    paths: BTreeSet<PathBuf>,
+    // Set when the notifications don't tell all the paths changed
+    full_sync: bool,
+}
+
+impl LocalChanges {
+    fn add(&mut self, event: RawEvent) {
+        log_event(&event);
+        match (event.op, event.path) {
+            (Ok(kind), _) if kind.contains(op::RESCAN) => self.full_sync = true,
+            (Ok(_), Some(path)) => {
+                let _ = self.paths.insert(path);
+            }
+            _ => self.full_sync = true,
+        }
+    }
+}
+
+// Local path of a change notified, in the same form as the location watched, i.e. starting
+// with it, or None if it's the location itself or it's not found under it
+fn local_change_path(location: &str, root: Option<&Path>, path: &Path) -> Option<String> {
+    let relative = root
+        .and_then(|root| path.strip_prefix(root).ok())
+        .or_else(|| path.strip_prefix(location).ok())?;
+    if relative.as_os_str().is_empty() {
+        return None;
+    }
+    let local_path = Path::new(location).join(relative);
+    Some(normalise_path_separator(&local_path.display().to_string()))
+}
+
+fn log_event(event: &RawEvent) {
+    match &event.op {
+        Ok(op) => debug!("Change found in local files: {:?} {:?}", op, event.path),
+        Err(err) => warn!("Error notified while watching local files: {}", err),
+    }
+}
@@ -9,7 +9,7 @@
 
 #[cfg(feature = "app")]
 use crate::{Error, Result};
-use chrono::{DateTime, SecondsFormat, Utc};
+use chrono::{DateTime, SecondsFormat, Timelike, Utc};
 
 use safe_network::types::{Error as SafeNdError, PublicKey, Token};
 use std::{
@@ -52,11 +52,27 @@ pub fn parse_tokens_amount(amount_str: &str) -> Result<Token> {
     })
 }
 
-pub fn systemtime_to_rfc3339(t: time::SystemTime) -> String {
+pub fn gen_timestamp_secs() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+// Converts a SystemTime into a DateTime truncated to seconds, which is the
+// precision used for the timestamps stored in FileItems
+pub fn systemtime_to_datetime_secs(t: time::SystemTime) -> DateTime<Utc> {
     let datetime: DateTime<Utc> = t.into();
+    datetime.with_nanosecond(0).unwrap_or(datetime)
+}
+
+pub fn gen_datetime_secs() -> DateTime<Utc> {
+    systemtime_to_datetime_secs(time::SystemTime::now())
+}
+
+pub fn datetime_to_rfc3339(datetime: &DateTime<Utc>) -> String {
     datetime.to_rfc3339_opts(SecondsFormat::Secs, true)
 }
 
-pub fn gen_timestamp_secs() -> String {
-    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+pub fn rfc3339_to_datetime(timestamp: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|datetime| datetime.with_timezone(&Utc))
+        .map_err(|err| Error::InvalidInput(format!("Invalid timestamp '{}': {}", timestamp, err)))
 }
@@ -11,6 +11,7 @@ mod auth;
 mod consts;
 mod helpers;
 mod keys;
+mod rdf;
 mod safe_client;
 mod sequence;
 #[cfg(test)]
@@ -33,6 +34,7 @@ pub mod nrs;
 pub mod register;
 pub use consts::DEFAULT_XORURL_BASE;
 pub use helpers::parse_tokens_amount;
+pub use rdf::{RdfFormat, ToRdf};
 pub use safe_network::url::*;
 pub use xor_name::{XorName, XOR_NAME_LEN};
 
@@ -40,6 +42,9 @@ pub use xor_name::{XorName, XOR_NAME_LEN};
 pub struct Safe {
     safe_client: SafeAppClient,
     pub xorurl_base: XorUrlBase,
+    /// Settings used for the FilesContainers created, updated and fetched with this instance
+    pub files_config: files::FilesConfig,
+    files_maps_cache: files::FilesMapsCache,
 }
 
 impl Default for Safe {
@@ -56,6 +61,8 @@ impl Safe {
         Self {
             safe_client: SafeAppClient::new(timeout),
             xorurl_base: xorurl_base.unwrap_or(DEFAULT_XORURL_BASE),
+            files_config: files::FilesConfig::default(),
+            files_maps_cache: files::FilesMapsCache::default(),
         }
     }
 
@@ -32,7 +32,7 @@ impl Safe {
         debug!("Creating a Multimap");
         let xorname = self
             .safe_client
-            .store_register(name, type_tag, None, private)
+            .store_register(name, type_tag, &[], private)
             .await?;
 
         let xorurl = SafeUrl::encode_register(
@@ -9,6 +9,7 @@
 
 mod nrs_map;
 
+use nrs_map::{deserialise_nrs_map, serialise_nrs_map};
 pub use nrs_map::{DefaultRdf, NrsMap};
 
 use crate::{
@@ -290,14 +291,7 @@ impl Safe {
                 let serialised_nrs_map = self.fetch_public_blob(&nrs_map_xorurl, None).await?;
 
                 debug!("Nrs map v{} retrieved: {:?} ", version, &serialised_nrs_map);
-                let nrs_map =
-                    serde_json::from_str(&String::from_utf8_lossy(serialised_nrs_map.as_slice()))
-                        .map_err(|err| {
-                        Error::ContentError(format!(
-                            "Couldn't deserialise the NrsMap stored in the NrsContainer: {:?}",
-                            err
-                        ))
-                    })?;
+                let nrs_map = deserialise_nrs_map(&serialised_nrs_map)?;
 
                 Ok((version, nrs_map))
             }
@@ -320,16 +314,11 @@ impl Safe {
     async fn store_nrs_map(&self, nrs_map: &NrsMap) -> Result<String> {
         // The NrsMapContainer is a Sequence where each NRS Map version is
         // an entry containing the XOR-URL of the Blob that contains the serialised NrsMap.
-        // TODO: use RDF format
-        let serialised_nrs_map = serde_json::to_string(nrs_map).map_err(|err| {
-            Error::Serialisation(format!(
-                "Couldn't serialise the NrsMap generated: {:?}",
-                err
-            ))
-        })?;
+        // The NrsMap is stored as a JSON-LD document
+        let serialised_nrs_map = serialise_nrs_map(nrs_map)?;
 
         let nrs_map_xorurl = self
-            .files_store_public_blob(serialised_nrs_map.as_bytes(), None, false)
+            .files_store_public_blob(&serialised_nrs_map, None, false)
             .await?;
 
         Ok(nrs_map_xorurl)
@@ -367,11 +356,41 @@ mod tests {
         app::{
             consts::PREDICATE_LINK,
             test_helpers::{new_safe_instance, random_nrs_name},
+            RdfFormat, ToRdf,
         },
+        files::{CreateOptions, SymlinkPolicy},
         retry_loop, retry_loop_for_pattern,
     };
     use anyhow::{anyhow, bail, Result};
 
+    #[test]
+    fn test_nrs_map_rdf_serialisation() -> Result<()> {
+        let mut nrs_map = NrsMap::default();
+        nrs_map.update("www.mysite", "safe://linked-from-www?v=0", true, false)?;
+        nrs_map.update("blog.mysite", "safe://linked-from-blog?v=0", false, false)?;
+
+        // NrsMaps stored with the legacy format can still be read
+        let legacy = serde_json::to_vec(&nrs_map)?;
+        assert_eq!(deserialise_nrs_map(&legacy)?, nrs_map);
+
+        let serialised = serialise_nrs_map(&nrs_map)?;
+        let jsonld: serde_json::Value = serde_json::from_slice(&serialised)?;
+        assert_eq!(jsonld["@type"], "NrsMap");
+        assert_eq!(jsonld["sub_names"]["www"]["@type"], "Definition");
+        assert_eq!(
+            jsonld["sub_names"]["blog"]["link"],
+            "safe://linked-from-blog?v=0"
+        );
+        assert_eq!(deserialise_nrs_map(&serialised)?, nrs_map);
+
+        let turtle = nrs_map.to_rdf(RdfFormat::Turtle)?;
+        assert!(turtle.starts_with("@prefix : <https://safenetwork.tech/ns/nrs#> ."));
+        assert!(turtle.contains(":name \"blog\""));
+        assert!(turtle.contains(":link <safe://linked-from-blog?v=0>"));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_nrs_map_container_create() -> Result<()> {
         let site_name = random_nrs_name();
@@ -420,7 +439,14 @@ mod tests {
 
         // let's create an empty files container so we have a valid to link
         let (link, _, _) = safe
-            .files_container_create(None, None, true, true, false)
+            .files_container_create(
+                None,
+                None,
+                &CreateOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    ..CreateOptions::default()
+                },
+            )
             .await?;
         let link_v0 = format!("{}?v=0", link);
 
@@ -450,7 +476,14 @@ mod tests {
 
         // let's create an empty files container so we have a valid to link
         let (link, _, _) = safe
-            .files_container_create(None, None, true, true, false)
+            .files_container_create(
+                None,
+                None,
+                &CreateOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    ..CreateOptions::default()
+                },
+            )
             .await?;
         let link_v0 = format!("{}?v=0", link);
 
@@ -517,7 +550,14 @@ mod tests {
 
         // let's create an empty files container so we have a valid to link
         let (link, _, _) = safe
-            .files_container_create(None, None, true, true, false)
+            .files_container_create(
+                None,
+                None,
+                &CreateOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    ..CreateOptions::default()
+                },
+            )
             .await?;
         let link_v0 = format!("{}?v=0", link);
 
@@ -553,7 +593,14 @@ mod tests {
 
         // let's create an empty files container so we have a valid to link
         let (link, _, _) = safe
-            .files_container_create(None, None, true, true, false)
+            .files_container_create(
+                None,
+                None,
+                &CreateOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    ..CreateOptions::default()
+                },
+            )
             .await?;
         let link_v0 = format!("{}?v=0", link);
 
@@ -590,7 +637,14 @@ mod tests {
 
         // let's create an empty files container so we have a valid to link
         let (link, _, _) = safe
-            .files_container_create(None, None, true, true, false)
+            .files_container_create(
+                None,
+                None,
+                &CreateOptions {
+                    symlinks: SymlinkPolicy::FollowAll,
+                    ..CreateOptions::default()
+                },
+            )
             .await?;
         let link_v0 = format!("{}?v=0", link);
 
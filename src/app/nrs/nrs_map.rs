@@ -9,17 +9,28 @@
 
 use crate::{
     app::{
-        consts::{PREDICATE_CREATED, PREDICATE_LINK, PREDICATE_MODIFIED},
+        consts::{PREDICATE_CREATED, PREDICATE_LINK, PREDICATE_MODIFIED, SAFE_NRS_VOCAB},
         fetch::{SafeContentType, SafeDataType},
         helpers::gen_timestamp_secs,
+        rdf::{RdfFormat, ToRdf, TurtleNode, TurtleObject, JSONLD_CONTEXT, JSONLD_TYPE},
         Safe,
     },
     Error, Result, XorUrl,
 };
 use log::{debug, info};
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
 use std::{collections::BTreeMap, fmt};
 
+// Keys and types used in the JSON-LD representation of an NrsMap
+const SUB_NAMES_KEY: &str = "sub_names";
+const DEFAULT_KEY: &str = "default";
+const NAME_KEY: &str = "name";
+const NRS_MAP_TYPE: &str = "NrsMap";
+const DEFINITION_TYPE: &str = "Definition";
+const EXISTING_SUB_NAME_TYPE: &str = "ExistingSubName";
+const NOT_SET_TYPE: &str = "NotSet";
+
 pub(crate) type SubName = String;
 pub(crate) type DefinitionData = BTreeMap<String, String>;
 
@@ -258,6 +269,221 @@ impl NrsMap {
     }
 }
 
+impl ToRdf for NrsMap {
+    fn to_rdf(&self, format: RdfFormat) -> Result<String> {
+        match format {
+            RdfFormat::JsonLd => {
+                serde_json::to_string_pretty(&nrs_map_to_jsonld(self)).map_err(|err| {
+                    Error::Serialisation(format!(
+                        "Couldn't serialise the NrsMap as JSON-LD: {:?}",
+                        err
+                    ))
+                })
+            }
+            RdfFormat::Turtle => Ok(nrs_map_to_turtle(self).to_document(SAFE_NRS_VOCAB)),
+        }
+    }
+}
+
+// Serialise an NrsMap as a JSON-LD document
+pub(crate) fn serialise_nrs_map(nrs_map: &NrsMap) -> Result<Vec<u8>> {
+    serde_json::to_vec(&nrs_map_to_jsonld(nrs_map)).map_err(|err| {
+        Error::Serialisation(format!(
+            "Couldn't serialise the NrsMap generated: {:?}",
+            err
+        ))
+    })
+}
+
+// Deserialise an NrsMap, auto-detecting if it's a JSON-LD document or it uses the legacy format
+pub(crate) fn deserialise_nrs_map(serialised: &[u8]) -> Result<NrsMap> {
+    let deserialisation_error = |err: serde_json::Error| {
+        Error::ContentError(format!(
+            "Couldn't deserialise the NrsMap stored in the NrsContainer: {:?}",
+            err
+        ))
+    };
+
+    let value: Value = serde_json::from_slice(serialised).map_err(deserialisation_error)?;
+    match &value {
+        Value::Object(map) if map.contains_key(JSONLD_CONTEXT) => nrs_map_from_jsonld(map),
+        _ => serde_json::from_value(value).map_err(deserialisation_error),
+    }
+}
+
+// Generate the JSON-LD representation of an NrsMap. Sub names are kept in an index
+// map keyed by their name, which is also set as a property of each of them.
+fn nrs_map_to_jsonld(nrs_map: &NrsMap) -> Value {
+    let mut jsonld = nrs_map_to_jsonld_node(nrs_map);
+    if let Value::Object(map) = &mut jsonld {
+        map.insert(
+            JSONLD_CONTEXT.to_string(),
+            json!({
+                "@vocab": SAFE_NRS_VOCAB,
+                SUB_NAMES_KEY: { "@container": "@index" },
+                PREDICATE_LINK: { "@type": "@id" },
+            }),
+        );
+    }
+    jsonld
+}
+
+fn nrs_map_to_jsonld_node(nrs_map: &NrsMap) -> Value {
+    let mut sub_names = Map::new();
+    for (sub_name, sub_name_rdf) in &nrs_map.sub_names_map {
+        let mut node = match sub_name_rdf {
+            SubNameRdf::Definition(def_data) => definition_to_jsonld(def_data),
+            SubNameRdf::SubName(nrs_sub_map) => nrs_map_to_jsonld_node(nrs_sub_map),
+        };
+        if let Value::Object(map) = &mut node {
+            map.insert(NAME_KEY.to_string(), json!(sub_name));
+        }
+        sub_names.insert(sub_name.to_string(), node);
+    }
+
+    let default = match &nrs_map.default {
+        DefaultRdf::NotSet => json!({ JSONLD_TYPE: NOT_SET_TYPE }),
+        DefaultRdf::ExistingRdf(sub_name) => {
+            json!({ JSONLD_TYPE: EXISTING_SUB_NAME_TYPE, NAME_KEY: sub_name })
+        }
+        DefaultRdf::OtherRdf(def_data) => definition_to_jsonld(def_data),
+    };
+
+    json!({
+        JSONLD_TYPE: NRS_MAP_TYPE,
+        SUB_NAMES_KEY: sub_names,
+        DEFAULT_KEY: default,
+    })
+}
+
+fn definition_to_jsonld(def_data: &DefinitionData) -> Value {
+    let mut node = Map::new();
+    node.insert(JSONLD_TYPE.to_string(), json!(DEFINITION_TYPE));
+    for (key, value) in def_data {
+        node.insert(key.to_string(), json!(value));
+    }
+    Value::Object(node)
+}
+
+fn nrs_map_from_jsonld(node: &Map<String, Value>) -> Result<NrsMap> {
+    let mut sub_names_map = SubNamesMap::new();
+    match node.get(SUB_NAMES_KEY) {
+        Some(Value::Object(sub_names)) => {
+            for (sub_name, sub_name_node) in sub_names {
+                let sub_name_node = jsonld_node(sub_name_node)?;
+                let sub_name_rdf = match jsonld_type(sub_name_node)? {
+                    NRS_MAP_TYPE => SubNameRdf::SubName(nrs_map_from_jsonld(sub_name_node)?),
+                    DEFINITION_TYPE => {
+                        SubNameRdf::Definition(definition_from_jsonld(sub_name_node)?)
+                    }
+                    other => return Err(invalid_jsonld(&format!("unknown type '{}'", other))),
+                };
+                sub_names_map.insert(sub_name.to_string(), sub_name_rdf);
+            }
+        }
+        None => {}
+        Some(_) => return Err(invalid_jsonld("invalid sub names")),
+    }
+
+    let default = match node.get(DEFAULT_KEY) {
+        None => DefaultRdf::NotSet,
+        Some(default_node) => {
+            let default_node = jsonld_node(default_node)?;
+            match jsonld_type(default_node)? {
+                NOT_SET_TYPE => DefaultRdf::NotSet,
+                EXISTING_SUB_NAME_TYPE => match default_node.get(NAME_KEY) {
+                    Some(Value::String(sub_name)) => DefaultRdf::ExistingRdf(sub_name.to_string()),
+                    _ => return Err(invalid_jsonld("default sub name is missing its name")),
+                },
+                DEFINITION_TYPE => DefaultRdf::OtherRdf(definition_from_jsonld(default_node)?),
+                other => return Err(invalid_jsonld(&format!("unknown type '{}'", other))),
+            }
+        }
+    };
+
+    Ok(NrsMap {
+        sub_names_map,
+        default,
+    })
+}
+
+fn definition_from_jsonld(node: &Map<String, Value>) -> Result<DefinitionData> {
+    let mut def_data = DefinitionData::new();
+    for (key, value) in node {
+        if key == JSONLD_TYPE || key == NAME_KEY {
+            continue;
+        }
+        match value {
+            Value::String(v) => def_data.insert(key.to_string(), v.to_string()),
+            _ => return Err(invalid_jsonld(&format!("invalid value for '{}'", key))),
+        };
+    }
+    Ok(def_data)
+}
+
+fn jsonld_node(value: &Value) -> Result<&Map<String, Value>> {
+    match value {
+        Value::Object(node) => Ok(node),
+        _ => Err(invalid_jsonld("expected a node object")),
+    }
+}
+
+fn jsonld_type(node: &Map<String, Value>) -> Result<&str> {
+    match node.get(JSONLD_TYPE) {
+        Some(Value::String(rdf_type)) => Ok(rdf_type),
+        _ => Err(invalid_jsonld("node is missing its type")),
+    }
+}
+
+fn invalid_jsonld(msg: &str) -> Error {
+    Error::ContentError(format!(
+        "Couldn't deserialise the NrsMap stored in the NrsContainer, {}",
+        msg
+    ))
+}
+
+// Generate the RDF graph of an NrsMap to be written as a Turtle document
+fn nrs_map_to_turtle(nrs_map: &NrsMap) -> TurtleNode {
+    let mut node = TurtleNode::new(NRS_MAP_TYPE);
+    for (sub_name, sub_name_rdf) in &nrs_map.sub_names_map {
+        let mut sub_name_node = match sub_name_rdf {
+            SubNameRdf::Definition(def_data) => definition_to_turtle(def_data),
+            SubNameRdf::SubName(nrs_sub_map) => nrs_map_to_turtle(nrs_sub_map),
+        };
+        sub_name_node.add(NAME_KEY, TurtleObject::Literal(sub_name.to_string()));
+        node.add(SUB_NAMES_KEY, TurtleObject::Node(sub_name_node));
+    }
+
+    let default = match &nrs_map.default {
+        DefaultRdf::NotSet => TurtleNode::new(NOT_SET_TYPE),
+        DefaultRdf::ExistingRdf(sub_name) => {
+            let mut default = TurtleNode::new(EXISTING_SUB_NAME_TYPE);
+            default.add(NAME_KEY, TurtleObject::Literal(sub_name.to_string()));
+            default
+        }
+        DefaultRdf::OtherRdf(def_data) => definition_to_turtle(def_data),
+    };
+    node.add(DEFAULT_KEY, TurtleObject::Node(default));
+
+    node
+}
+
+fn definition_to_turtle(def_data: &DefinitionData) -> TurtleNode {
+    let mut node = TurtleNode::new(DEFINITION_TYPE);
+    for (key, value) in def_data {
+        if key == PREDICATE_LINK {
+            node.add(PREDICATE_LINK, TurtleObject::Iri(value.to_string()));
+        } else {
+            node.add_with_iri(
+                SAFE_NRS_VOCAB,
+                key,
+                TurtleObject::Literal(value.to_string()),
+            );
+        }
+    }
+    node
+}
+
 fn create_nrs_name_metadata(link: &str) -> DefinitionData {
     let now = gen_timestamp_secs();
     let mut public_name = DefinitionData::new();
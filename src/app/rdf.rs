@@ -0,0 +1,133 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::consts::XSD_NAMESPACE;
+use crate::Result;
+
+// JSON-LD keywords used in the documents we generate
+pub(crate) const JSONLD_CONTEXT: &str = "@context";
+pub(crate) const JSONLD_TYPE: &str = "@type";
+
+/// RDF formats FilesMaps and NrsMaps can be serialised to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RdfFormat {
+    /// JSON-LD, which is the format used to store them on the network
+    JsonLd,
+    /// Turtle (Terse RDF Triple Language)
+    Turtle,
+}
+
+/// Serialisation of a data structure as an RDF document, using the Safe vocabularies
+pub trait ToRdf {
+    fn to_rdf(&self, format: RdfFormat) -> Result<String>;
+}
+
+// The object of an RDF statement, as written in a Turtle document
+pub(crate) enum TurtleObject {
+    Iri(String),
+    Literal(String),
+    TypedLiteral(String, &'static str),
+    Integer(u64),
+    Boolean(bool),
+    Node(TurtleNode),
+}
+
+// A blank node of an RDF graph with its type and properties. Predicates are
+// either terms of the document's default vocabulary or full IRIs.
+pub(crate) struct TurtleNode {
+    rdf_type: String,
+    properties: Vec<(String, TurtleObject)>,
+}
+
+impl TurtleNode {
+    pub(crate) fn new(rdf_type: &str) -> Self {
+        Self {
+            rdf_type: rdf_type.to_string(),
+            properties: vec![],
+        }
+    }
+
+    // Add a property which predicate is a term of the default vocabulary
+    pub(crate) fn add(&mut self, term: &str, object: TurtleObject) {
+        self.properties.push((format!(":{}", term), object));
+    }
+
+    // Add a property which predicate is an arbitrary name within the vocabulary provided
+    pub(crate) fn add_with_iri(&mut self, vocab: &str, name: &str, object: TurtleObject) {
+        let iri = format!("{}{}", vocab, urlencoding::encode(name));
+        self.properties.push((turtle_iri(&iri), object));
+    }
+
+    // Generate a Turtle document with this node as its root, using the
+    // vocabulary provided as the default (empty) prefix
+    pub(crate) fn to_document(&self, vocab: &str) -> String {
+        format!(
+            "@prefix : <{}> .\n@prefix xsd: <{}> .\n\n[\n{}\n] .\n",
+            vocab,
+            XSD_NAMESPACE,
+            self.properties_to_turtle(1)
+        )
+    }
+
+    fn properties_to_turtle(&self, indent: usize) -> String {
+        let pad = "    ".repeat(indent);
+        let mut statements = vec![format!("{}a :{}", pad, self.rdf_type)];
+        for (predicate, object) in &self.properties {
+            let object = match object {
+                TurtleObject::Iri(iri) => turtle_iri(iri),
+                TurtleObject::Literal(value) => turtle_literal(value),
+                TurtleObject::TypedLiteral(value, datatype) => {
+                    format!("{}^^{}", turtle_literal(value), datatype)
+                }
+                TurtleObject::Integer(value) => value.to_string(),
+                TurtleObject::Boolean(value) => value.to_string(),
+                TurtleObject::Node(node) => {
+                    format!("[\n{}\n{}]", node.properties_to_turtle(indent + 1), pad)
+                }
+            };
+            statements.push(format!("{}{} {}", pad, predicate, object));
+        }
+        statements.join(" ;\n")
+    }
+}
+
+// Quote and escape a string to be used as a Turtle literal
+fn turtle_literal(value: &str) -> String {
+    let mut literal = String::with_capacity(value.len() + 2);
+    literal.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+// Enclose an IRI in angle brackets, percent-encoding the characters not allowed in it
+fn turtle_iri(iri: &str) -> String {
+    let mut escaped = String::with_capacity(iri.len() + 2);
+    escaped.push('<');
+    for c in iri.chars() {
+        match c {
+            '<' | '>' | '"' | '{' | '}' | '|' | '^' | '`' | '\\' => {
+                escaped.push_str(&format!("%{:02X}", c as u32))
+            }
+            c if c <= ' ' => escaped.push_str(&format!("%{:02X}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('>');
+    escaped
+}
@@ -25,7 +25,7 @@ impl Safe {
     ) -> Result<XorUrl> {
         let xorname = self
             .safe_client
-            .store_register(name, type_tag, None, private)
+            .store_register(name, type_tag, &[], private)
             .await?;
 
         let xorurl = SafeUrl::encode_register(
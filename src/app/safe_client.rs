@@ -14,8 +14,8 @@ use log::{debug, info};
 use safe_network::client::{Client, Error as ClientError};
 use safe_network::types::{
     register::{Address, Entry, EntryHash, PrivatePermissions, PublicPermissions, User},
-    ChunkAddress, Error as SafeNdError, Keypair, SequenceAddress, SequencePrivatePermissions,
-    SequencePublicPermissions, SequenceUser,
+    ChunkAddress, Error as SafeNdError, Keypair, PublicKey, SequenceAddress,
+    SequencePrivatePermissions, SequencePublicPermissions, SequenceUser,
 };
 use std::{
     collections::{BTreeMap, BTreeSet, HashSet},
@@ -114,12 +114,55 @@ impl SafeAppClient {
         Ok(*address.name())
     }
 
+    pub async fn store_private_blob(&self, data: &[u8], dry_run: bool) -> Result<XorName> {
+        let client = self.get_safe_client()?;
+        let address = if dry_run {
+            // The Blob's owner will be the client's public key
+            let owner = client.public_key();
+            let (_, address) = Client::blob_data_map(data.to_vec(), Some(owner)).await?;
+            address
+        } else {
+            client
+                .store_private_blob(data)
+                .await
+                .map_err(|e| Error::NetDataError(format!("Failed to PUT Private Blob: {:?}", e)))?
+        };
+
+        Ok(*address.name())
+    }
+
     pub async fn get_public_blob(&self, xorname: XorName, range: Range) -> Result<Vec<u8>> {
         debug!("Fetching immutable data: {:?}", &xorname);
 
+        let data = self.read_blob(ChunkAddress::Public(xorname), range).await?;
+
+        debug!(
+            "Public Blob data successfully retrieved from: {:?}",
+            &xorname
+        );
+
+        Ok(data)
+    }
+
+    pub async fn get_private_blob(&self, xorname: XorName, range: Range) -> Result<Vec<u8>> {
+        debug!("Fetching private immutable data: {:?}", &xorname);
+
+        let data = self
+            .read_blob(ChunkAddress::Private(xorname), range)
+            .await?;
+
+        debug!(
+            "Private Blob data successfully retrieved from: {:?}",
+            &xorname
+        );
+
+        Ok(data)
+    }
+
+    // Private helper to read a Blob, or a range of it, from its head chunk address
+    async fn read_blob(&self, blob_address: ChunkAddress, range: Range) -> Result<Vec<u8>> {
         let client = self.get_safe_client()?;
-        let blob_address = ChunkAddress::Public(xorname);
-        let data = if let Some((start, end)) = range {
+        if let Some((start, end)) = range {
             let len = end.map(|end_index| end_index - start.unwrap_or(0));
             client
                 .read_blob(
@@ -131,14 +174,17 @@ impl SafeAppClient {
         } else {
             client.read_blob(blob_address, None, None).await
         }
-        .map_err(|e| Error::NetDataError(format!("Failed to GET Public Blob: {:?}", e)))?;
-
-        debug!(
-            "Public Blob data successfully retrieved from: {:?}",
-            &xorname
-        );
-
-        Ok(data)
+        .map_err(|e| {
+            Error::NetDataError(format!(
+                "Failed to GET {} Blob: {:?}",
+                if blob_address.is_public() {
+                    "Public"
+                } else {
+                    "Private"
+                },
+                e
+            ))
+        })
     }
 
     // === Sequence data operations ===
@@ -304,7 +350,7 @@ impl SafeAppClient {
         &self,
         name: Option<XorName>,
         tag: u64,
-        _permissions: Option<String>,
+        writers: &[PublicKey],
         private: bool,
     ) -> Result<XorName> {
         debug!(
@@ -323,9 +369,12 @@ impl SafeAppClient {
 
         // Store the Register on the network
         let _ = if private {
-            // Set read and write  permissions to this application
+            // Set read and write  permissions to this application, and to the writers provided
             let mut perms = BTreeMap::default();
             let _ = perms.insert(my_pk, PrivatePermissions::new(true, true));
+            for writer in writers {
+                let _ = perms.insert(*writer, PrivatePermissions::new(true, true));
+            }
 
             client
                 .store_private_register(xorname, tag, my_pk, perms)
@@ -334,10 +383,13 @@ impl SafeAppClient {
                     Error::NetDataError(format!("Failed to store Private Register data: {:?}", e))
                 })?
         } else {
-            // Set write permissions to this application
+            // Set write permissions to this application, and to the writers provided
             let user_app = User::Key(my_pk);
             let mut perms = BTreeMap::default();
             let _ = perms.insert(user_app, PublicPermissions::new(true));
+            for writer in writers {
+                let _ = perms.insert(User::Key(*writer), PublicPermissions::new(true));
+            }
 
             client
                 .store_public_register(xorname, tag, my_pk, perms)
@@ -61,6 +61,30 @@ pub enum Error {
     /// VersionNotFound
     #[error("VersionNotFound: {0}")]
     VersionNotFound(String),
+    /// VersionConflict
+    #[error("VersionConflict: expected version {expected} but the latest version is {current}")]
+    VersionConflict {
+        /// The version expected by the change
+        expected: u64,
+        /// The latest version found
+        current: u64,
+    },
+    /// DeletionsNotConfirmed
+    #[error("DeletionsNotConfirmed: {reason} ({} path(s) would be deleted)", .deletions.len())]
+    DeletionsNotConfirmed {
+        /// The safeguard the deletions break
+        reason: String,
+        /// The paths which would be deleted
+        deletions: Vec<String>,
+    },
+    /// ChecksumMismatch
+    #[error("ChecksumMismatch: expected checksum {expected} but the content has {actual}")]
+    ChecksumMismatch {
+        /// The checksum recorded for the content
+        expected: String,
+        /// The checksum of the content retrieved
+        actual: String,
+    },
     #[cfg(feature = "app")]
     /// HashNotFound
     #[error("No entry with hash {0:?}")]